@@ -45,7 +45,7 @@ impl std::fmt::Display for BibTeXEntryKind {
             Self::InCollection  => write!(f, "incollection"), 
             Self::InProceedings => write!(f, "inproceedings"), 
             Self::Manual        => write!(f, "manual"), 
-            Self::MasterThesis  => write!(f, "masterthesis"), 
+            Self::MasterThesis  => write!(f, "mastersthesis"), 
             Self::Misc          => write!(f, "misc"), 
             Self::PhdThesis     => write!(f, "phdthesis"), 
             Self::Proceedings   => write!(f, "proceedings"), 
@@ -106,6 +106,20 @@ impl BibTeXEntry {
         self.fields.push(field);
     }
 
+    /// Replaces the field with the same key where it is, or adds it if the
+    /// entry has none
+    pub fn set_field(&mut self, field: BibTeXField) {
+        match self.fields.iter_mut().find(|old| old.key().eq_ignore_ascii_case(field.key())) {
+            Some(old) => *old = field,
+            None => self.fields.push(field),
+        }
+    }
+
+    /// All fields of the entry, in the order they were added
+    pub fn fields(&self) -> &[BibTeXField] {
+        &self.fields
+    }
+
     getters_impl!(
         address, Address -> String;
         annote, Annote -> String;
@@ -131,7 +145,8 @@ impl BibTeXEntry {
         school, School -> String;
         series, Series -> String;
         title, Title -> String;
-        volume, Volume -> u8;
+        entry_type, Type -> String;
+        volume, Volume -> u16;
     );
 
     pub fn non_standard_field(&self, key: &str) -> Option<&String> {
@@ -148,8 +163,21 @@ impl BibTeXEntry {
 
 impl std::fmt::Display for BibTeXEntry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "@{}{{{}", self.kind, self.citekey);
-
-        todo!()
+        write!(f, "@{}{{{}", self.kind, self.citekey)?;
+        for field in &self.fields {
+            write!(f, ",\n  {} = ", field.key())?;
+            match field {
+                // written as the standard macros, e.g. `month = oct`
+                BibTeXField::Month(month @ 1..=12) => {
+                    const MONTHS: [&str; 12] = [
+                        "jan", "feb", "mar", "apr", "may", "jun", 
+                        "jul", "aug", "sep", "oct", "nov", "dec", 
+                    ];
+                    write!(f, "{}", MONTHS[*month as usize - 1])?;
+                }
+                field => write!(f, "{{{}}}", field.value())?,
+            }
+        }
+        write!(f, "\n}}\n")
     }
 }
\ No newline at end of file
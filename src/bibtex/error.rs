@@ -1,17 +1,19 @@
 #[derive(Debug, PartialEq)]
 pub enum ParseError {
-    UnexpectedCharacter(char), 
+    UnexpectedCharacter { expected: char, received: char }, 
     InvalidEntryKind(String), 
     EmptyBibliography,
     UnexpectedEOF, 
     InvalidValue, 
 }
 
+impl std::error::Error for ParseError {}
+
 impl std::fmt::Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::UnexpectedCharacter(c) => {
-                write!(f, "unexpected character '{}'", c)
+            Self::UnexpectedCharacter { expected, received } => {
+                write!(f, "expected '{}' but found '{}'", expected, received)
             }
             Self::InvalidEntryKind(s) => {
                 write!(f, "invalid entry type '{}'", s)
@@ -27,4 +29,18 @@ impl std::fmt::Display for ParseError {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// An entry that lenient parsing skipped over because it failed to parse
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic {
+    /// The line the broken entry starts on, counting from 1
+    pub line: usize, 
+    pub error: ParseError, 
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
@@ -10,10 +10,101 @@ pub enum BibTeXField {
     Email(String), HowPublished(String), Institution(String), Journal(String), 
     Day(u8), Month(u8), Year(u16), Note(String), Number(u16), Organization(String), 
     Pages(Pages), Publisher(String), School(String), Series(String), Title(String), 
-    Type(String), Volume(u8), NonStandard { key: String, value: String }, 
+    Type(String), Volume(u16), NonStandard { key: String, value: String }, 
 } 
 
 impl BibTeXField {
+    /// Builds a typed field from a raw key and value. Values that fail to
+    /// parse into their typed form (e.g. `volume = {3A}`) are kept as
+    /// non-standard fields so that no data is lost
+    pub fn from_key_value(key: &str, value: &str) -> Self {
+        let key_lower = key.to_lowercase();
+        let trimmed = value.trim();
+        let parsed = match key_lower.as_str() {
+            "address"      => Some(Self::Address(value.to_string())),
+            "annote"       => Some(Self::Annote(value.to_string())),
+            "author"       => trimmed.parse().ok().map(Self::Author),
+            "booktitle"    => Some(Self::BookTitle(value.to_string())),
+            "chapter"      => trimmed.parse().ok().map(Self::Chapter),
+            "crossref"     => Some(Self::Crossref(value.to_string())),
+            "doi"          => Some(Self::Doi(value.to_string())),
+            "edition"      => trimmed.parse().ok().map(Self::Edition),
+            "editor"       => trimmed.parse().ok().map(Self::Editor),
+            "email"        => Some(Self::Email(value.to_string())),
+            "howpublished" => Some(Self::HowPublished(value.to_string())),
+            "institution"  => Some(Self::Institution(value.to_string())),
+            "journal"      => Some(Self::Journal(value.to_string())),
+            "day"          => trimmed.parse().ok().map(Self::Day),
+            "month"        => trimmed.parse::<Month>().ok().map(|m| Self::Month(m as u8 + 1)),
+            "year"         => trimmed.parse().ok().map(Self::Year),
+            "note"         => Some(Self::Note(value.to_string())),
+            "number"       => trimmed.parse().ok().map(Self::Number),
+            "organization" => Some(Self::Organization(value.to_string())),
+            "pages"        => trimmed.parse().ok().map(Self::Pages),
+            "publisher"    => Some(Self::Publisher(value.to_string())),
+            "school"       => Some(Self::School(value.to_string())),
+            "series"       => Some(Self::Series(value.to_string())),
+            "title"        => Some(Self::Title(value.to_string())),
+            "type"         => Some(Self::Type(value.to_string())),
+            "volume"       => trimmed.parse().ok().map(Self::Volume),
+            _ => None,
+        };
+
+        parsed.unwrap_or_else(|| Self::NonStandard { 
+            key: key_lower, 
+            value: value.to_string(),
+        })
+    }
+
+    /// The lowercase BibTeX key of the field
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Address(_)      => "address",
+            Self::Annote(_)       => "annote",
+            Self::Author(_)       => "author",
+            Self::BookTitle(_)    => "booktitle",
+            Self::Chapter(_)      => "chapter",
+            Self::Crossref(_)     => "crossref",
+            Self::Doi(_)          => "doi",
+            Self::Edition(_)      => "edition",
+            Self::Editor(_)       => "editor",
+            Self::Email(_)        => "email",
+            Self::HowPublished(_) => "howpublished",
+            Self::Institution(_)  => "institution",
+            Self::Journal(_)      => "journal",
+            Self::Day(_)          => "day",
+            Self::Month(_)        => "month",
+            Self::Year(_)         => "year",
+            Self::Note(_)         => "note",
+            Self::Number(_)       => "number",
+            Self::Organization(_) => "organization",
+            Self::Pages(_)        => "pages",
+            Self::Publisher(_)    => "publisher",
+            Self::School(_)       => "school",
+            Self::Series(_)       => "series",
+            Self::Title(_)        => "title",
+            Self::Type(_)         => "type",
+            Self::Volume(_)       => "volume",
+            Self::NonStandard { key, .. } => key,
+        }
+    }
+
+    /// The value as it is written in a `.bib` file, without the
+    /// surrounding braces
+    pub fn value(&self) -> String {
+        match self {
+            Self::Author(authors) | Self::Editor(authors) => authors.to_bibtex_names(),
+            Self::Chapter(n) | Self::Day(n) | Self::Month(n) => n.to_string(),
+            Self::Edition(n) | Self::Number(n) | Self::Year(n) | Self::Volume(n) => n.to_string(),
+            Self::Pages(pages) => pages.to_string(),
+            Self::Address(s) | Self::Annote(s) | Self::BookTitle(s) | Self::Crossref(s) 
+            | Self::Doi(s) | Self::Email(s) | Self::HowPublished(s) | Self::Institution(s) 
+            | Self::Journal(s) | Self::Note(s) | Self::Organization(s) | Self::Publisher(s) 
+            | Self::School(s) | Self::Series(s) | Self::Title(s) | Self::Type(s) => s.clone(),
+            Self::NonStandard { value, .. } => value.clone(),
+        }
+    }
+
     pub fn is_non_standard_field(&self) -> bool {
         matches!(self, Self::NonStandard { .. })
     }
@@ -31,9 +122,150 @@ pub struct Author {
     prefix: Option<String>,  
 }
 
+impl Author {
+    pub fn new(forename: &str, surname: &str) -> Self {
+        Self { 
+            forename: forename.to_string(), 
+            surname: surname.to_string(), 
+            suffix: None, 
+            prefix: None, 
+        }
+    }
+
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.to_string());
+        self
+    }
+
+    pub fn with_suffix(mut self, suffix: &str) -> Self {
+        self.suffix = Some(suffix.to_string());
+        self
+    }
+
+    pub fn forename(&self) -> &str {
+        &self.forename
+    }
+
+    pub fn surname(&self) -> &str {
+        &self.surname
+    }
+
+    /// The "von" part of a name, e.g. "van der" in "Johannes van der Waals"
+    pub fn prefix(&self) -> Option<&str> {
+        self.prefix.as_deref()
+    }
+
+    /// The "Jr" part of a name
+    pub fn suffix(&self) -> Option<&str> {
+        self.suffix.as_deref()
+    }
+
+    /// Formats the name the way BibTeX expects it, i.e. "von Last, Jr, First".
+    /// Parts with spaces or commas in them that would otherwise be read back
+    /// as more than one part, such as the corporate name
+    /// "{NASA Langley Research Center}", are wrapped in braces
+    pub fn to_bibtex_name(&self) -> String {
+        let plain = self.join_parts(str::to_string);
+        let reads_back = !plain.contains(" and ") && plain.parse::<Author>().is_ok_and(|author| author == *self);
+        if reads_back {
+            return plain;
+        }
+        self.join_parts(|part| match part.contains([' ', ',']) {
+            true => format!("{{{part}}}"),
+            false => part.to_string(),
+        })
+    }
+
+    fn join_parts(&self, write: impl Fn(&str) -> String) -> String {
+        let mut name = String::new();
+        if let Some(prefix) = &self.prefix {
+            name.push_str(prefix);
+            name.push(' ');
+        }
+        name.push_str(&write(&self.surname));
+        if let Some(suffix) = &self.suffix {
+            name.push_str(", ");
+            name.push_str(&write(suffix));
+        }
+        if !self.forename.is_empty() {
+            name.push_str(", ");
+            name.push_str(&write(&self.forename));
+        }
+        name
+    }
+}
+
+impl FromStr for Author {
+    type Err = String;
+
+    /// Parses any of the three BibTeX name forms: "First von Last", 
+    /// "von Last, First" and "von Last, Jr, First"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = split_top_level(s.trim(), ",")
+            .into_iter()
+            .map(str::trim)
+            .collect();
+
+        let (forename, last_part, suffix) = match parts.as_slice() {
+            [whole] => {
+                let words = split_top_level(whole, " ");
+                let mut words: Vec<&str> = words.into_iter().filter(|w| !w.is_empty()).collect();
+                let Some(last) = words.pop() else {
+                    return Err(format!("invalid author name: {s}"));
+                };
+
+                // the von part starts at the first lowercase word
+                let von_start = words
+                    .iter()
+                    .position(|w| w.starts_with(|c: char| c.is_lowercase()))
+                    .unwrap_or(words.len());
+                let forename = words[..von_start].join(" ");
+                let mut last_part = words[von_start..].to_vec();
+                last_part.push(last);
+                (forename, last_part.join(" "), None)
+            }
+            [last, first] => (first.to_string(), last.to_string(), None),
+            [last, jr, first] => (first.to_string(), last.to_string(), Some(jr.to_string())),
+            _ => return Err(format!("invalid author name: {s}")),
+        };
+
+        let mut words: Vec<&str> = split_top_level(&last_part, " ")
+            .into_iter()
+            .filter(|w| !w.is_empty())
+            .collect();
+        let von_end = words
+            .iter()
+            .rposition(|w| w.starts_with(|c: char| c.is_lowercase()))
+            .map(|i| i + 1)
+            .filter(|&i| i < words.len())
+            .unwrap_or(0);
+        let surname = words.split_off(von_end).join(" ");
+        if surname.is_empty() {
+            return Err(format!("invalid author name: {s}"));
+        }
+
+        Ok(Self {
+            forename: strip_braces(&forename), 
+            surname: strip_braces(&surname), 
+            suffix: suffix.filter(|s| !s.is_empty()),
+            prefix: Some(words.join(" ")).filter(|p| !p.is_empty()), 
+        })
+    }
+}
+
 impl std::fmt::Display for Author {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} {}", self.forename, self.surname)
+        let parts = [
+            Some(self.forename.as_str()), 
+            self.prefix.as_deref(), 
+            Some(self.surname.as_str()),
+        ];
+        let name: Vec<&str> = parts.into_iter().flatten().filter(|p| !p.is_empty()).collect();
+        write!(f, "{}", name.join(" "))?;
+        if let Some(suffix) = &self.suffix {
+            write!(f, ", {}", suffix)?;
+        }
+        Ok(())
     }
 }
 
@@ -42,6 +274,55 @@ pub struct Authors {
     authors: Vec<Author>, 
 }
 
+impl Authors {
+    pub fn new(authors: Vec<Author>) -> Self {
+        Self { authors }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Author> {
+        self.authors.iter()
+    }
+
+    pub fn first(&self) -> Option<&Author> {
+        self.authors.first()
+    }
+
+    pub fn len(&self) -> usize {
+        self.authors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.authors.is_empty()
+    }
+
+    /// Formats the list the way BibTeX expects it, separated by "and"
+    pub fn to_bibtex_names(&self) -> String {
+        self.authors
+            .iter()
+            .map(Author::to_bibtex_name)
+            .collect::<Vec<_>>()
+            .join(" and ")
+    }
+}
+
+impl FromStr for Authors {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let authors = split_top_level(s, " and ")
+            .into_iter()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(Author::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if authors.is_empty() {
+            return Err(format!("invalid author list: {s}"));
+        }
+        Ok(Self { authors })
+    }
+}
+
 impl std::fmt::Display for Authors {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut iterator = self.authors.iter().peekable();
@@ -207,7 +488,207 @@ pub struct PageRange {
     end: Option<i32>, // if we just have one page then no end page is given 
 }
 
+impl PageRange {
+    pub fn new(start: i32, end: Option<i32>) -> Self {
+        Self { start, end }
+    }
+
+    pub fn start(&self) -> i32 {
+        self.start
+    }
+
+    pub fn end(&self) -> Option<i32> {
+        self.end
+    }
+}
+
+impl FromStr for PageRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid page range: {s}");
+        let mut bounds = s
+            .split(['-', '\u{2013}', '\u{2014}'])
+            .map(str::trim)
+            .filter(|b| !b.is_empty());
+
+        let start = bounds.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+        let end = match bounds.next() {
+            Some(end) => Some(end.parse().map_err(|_| invalid())?),
+            None => None,
+        };
+        if bounds.next().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(Self { start, end })
+    }
+}
+
+impl std::fmt::Display for PageRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.end {
+            Some(end) => write!(f, "{}--{}", self.start, end),
+            None => write!(f, "{}", self.start),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Pages {
     pages: Vec<PageRange>
-}
\ No newline at end of file
+}
+
+impl Pages {
+    pub fn new(pages: Vec<PageRange>) -> Self {
+        Self { pages }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, PageRange> {
+        self.pages.iter()
+    }
+
+    /// The first page of the first range
+    pub fn first_page(&self) -> Option<i32> {
+        self.pages.first().map(PageRange::start)
+    }
+
+    /// The last page of the last range, if it has one
+    pub fn last_page(&self) -> Option<i32> {
+        self.pages.last().map(|range| range.end.unwrap_or(range.start))
+    }
+}
+
+impl FromStr for Pages {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pages = s
+            .split(',')
+            .map(str::trim)
+            .filter(|range| !range.is_empty())
+            .map(PageRange::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if pages.is_empty() {
+            return Err(format!("invalid pages: {s}"));
+        }
+        Ok(Self { pages })
+    }
+}
+
+impl std::fmt::Display for Pages {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut iterator = self.pages.iter().peekable();
+        
+        while let Some(range) = iterator.next() {
+            write!(f, "{}", range)?;
+
+            if iterator.peek().is_some() {
+                write!(f, ", ")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits `s` on `separator`, ignoring any separators nested inside braces
+fn split_top_level<'a>(s: &'a str, separator: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < s.len() {
+        let rest = &s[i..];
+        if rest.starts_with('{') {
+            depth += 1;
+        } else if rest.starts_with('}') {
+            depth = depth.saturating_sub(1);
+        } else if depth == 0 && rest.starts_with(separator) {
+            parts.push(&s[start..i]);
+            i += separator.len();
+            start = i;
+            continue;
+        }
+        i += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    parts.push(&s[start..]);
+
+    parts
+}
+
+/// Removes the braces that group words in a name, but keeps those around a
+/// special character such as `{\'\i}`, which the command inside needs
+fn strip_braces(s: &str) -> String {
+    let mut stripped = String::with_capacity(s.len());
+    let mut kept = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                let special = chars.peek() == Some(&'\\');
+                if special {
+                    stripped.push(c);
+                }
+                kept.push(special);
+            }
+            '}' => {
+                if kept.pop() == Some(true) {
+                    stripped.push(c);
+                }
+            }
+            c => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_author_name_forms() {
+        let author: Author = "Johannes van der Waals".parse().unwrap();
+        assert_eq!(author.forename(), "Johannes");
+        assert_eq!(author.prefix(), Some("van der"));
+        assert_eq!(author.surname(), "Waals");
+
+        let author: Author = "Martins, Jr, Joaquim R. R. A.".parse().unwrap();
+        assert_eq!(author.forename(), "Joaquim R. R. A.");
+        assert_eq!(author.surname(), "Martins");
+        assert_eq!(author.suffix(), Some("Jr"));
+        assert_eq!(author.to_bibtex_name(), "Martins, Jr, Joaquim R. R. A.");
+
+        let author: Author = "{NASA Langley Research Center}".parse().unwrap();
+        assert_eq!(author.surname(), "NASA Langley Research Center");
+        assert_eq!(author.forename(), "");
+    }
+
+    #[test]
+    fn test_authors_ignore_nested_and() {
+        let authors: Authors = "{Barnes and Noble} and Doe, Jane".parse().unwrap();
+        assert_eq!(authors.len(), 2);
+        assert_eq!(authors.first().unwrap().surname(), "Barnes and Noble");
+    }
+
+    #[test]
+    fn test_bibtex_names_read_back() {
+        let names = r#"{NASA Langley Research Center} and {Barnes and Noble} and Sm{\'\i}th, Jr., John and {van Dyke}, Milton"#;
+        let authors: Authors = names.parse().unwrap();
+        assert_eq!(authors.iter().nth(2).unwrap().surname(), r"Sm{\'\i}th");
+        assert_eq!(authors.to_bibtex_names(), names);
+        assert_eq!(names.parse::<Authors>().unwrap().iter().nth(3).unwrap().surname(), "van Dyke");
+    }
+
+    #[test]
+    fn test_pages() {
+        let pages: Pages = "12--34, 40".parse().unwrap();
+        assert_eq!(pages.first_page(), Some(12));
+        assert_eq!(pages.last_page(), Some(40));
+        assert_eq!(pages.to_string(), "12--34, 40");
+        assert!("e1234".parse::<Pages>().is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! Decoding of the LaTeX markup BibTeX values are written in, such as
+//! `M{\"u}ller` or `G\'erard`, into plain Unicode
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Decodes accents, special letters, escaped characters and the `--` and
+/// `---` dashes, dropping the braces and any other commands, e.g. `\emph{x}`
+/// becomes `x`
+pub fn decode(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut decoded = String::with_capacity(s.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i = decode_command(&chars, i + 1, &mut decoded),
+            '{' | '}' | '$' => i += 1,
+            '~' => {
+                decoded.push(' ');
+                i += 1;
+            }
+            '-' if chars.get(i + 1) == Some(&'-') => {
+                let em = chars.get(i + 2) == Some(&'-');
+                decoded.push(if em { '\u{2014}' } else { '\u{2013}' });
+                i += if em { 3 } else { 2 };
+            }
+            c => {
+                decoded.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    // combine the accents with the letters they were put on
+    decoded.nfc().collect()
+}
+
+/// Decodes like [`decode`], but keeps the braces that protect words from a
+/// change of case, e.g. `{NASA}`, dropping only those around special
+/// characters such as `{\"u}`
+pub fn decode_protected(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut decoded = String::with_capacity(s.len());
+    let mut plain = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                // keep a command and its braced argument together, e.g. `\emph{x}`
+                plain.push('\\');
+                i += 1;
+                if let Some(&c) = chars.get(i) {
+                    plain.push(c);
+                    i += 1;
+                    while c.is_ascii_alphabetic() && chars.get(i).is_some_and(char::is_ascii_alphabetic) {
+                        plain.push(chars[i]);
+                        i += 1;
+                    }
+                }
+                if chars.get(i) == Some(&'{') {
+                    let end = group_end(&chars, i);
+                    plain.extend(&chars[i..end]);
+                    i = end;
+                }
+            }
+            '{' => {
+                let end = group_end(&chars, i);
+                // an unclosed group runs to the end of the value
+                let close = if end > i + 1 && chars[end - 1] == '}' { end - 1 } else { end };
+                let inner: String = chars[i + 1..close].iter().collect();
+                decoded.push_str(&decode(&plain));
+                plain.clear();
+                if inner.starts_with('\\') {
+                    decoded.push_str(&decode(&inner));
+                } else {
+                    decoded.push('{');
+                    decoded.push_str(&decode(&inner));
+                    decoded.push('}');
+                }
+                i = end;
+            }
+            c => {
+                plain.push(c);
+                i += 1;
+            }
+        }
+    }
+    decoded.push_str(&decode(&plain));
+    decoded
+}
+
+/// Where the brace group opening at `start` ends, just past its closing
+/// brace or at the end of the input
+fn group_end(chars: &[char], start: usize) -> usize {
+    let mut depth = 0;
+    for (i, &c) in chars.iter().enumerate().skip(start) {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    chars.len()
+}
+
+/// Escapes the characters that BibTeX and LaTeX treat specially in plain
+/// text, e.g. `Fluid & Structure` becomes `Fluid \& Structure`
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Decodes the command after a backslash at `i`, returning where decoding
+/// should carry on from
+fn decode_command(chars: &[char], mut i: usize, decoded: &mut String) -> usize {
+    let Some(&first) = chars.get(i) else {
+        return i;
+    };
+
+    if let Some(accent) = symbol_accent(first) {
+        let (base, next) = argument(chars, i + 1);
+        decoded.push_str(&base);
+        decoded.push(accent);
+        return next;
+    }
+    if !first.is_ascii_alphabetic() {
+        // an escaped character such as \& or \%
+        decoded.push(first);
+        return i + 1;
+    }
+
+    let start = i;
+    while chars.get(i).is_some_and(char::is_ascii_alphabetic) {
+        i += 1;
+    }
+    let name: String = chars[start..i].iter().collect();
+    // a space after a command word only ends the word
+    if chars.get(i) == Some(&' ') {
+        i += 1;
+    }
+
+    if let Some(accent) = letter_accent(&name) {
+        let (base, next) = argument(chars, i);
+        decoded.push_str(&base);
+        decoded.push(accent);
+        return next;
+    }
+    if let Some(letter) = special_letter(&name) {
+        decoded.push_str(letter);
+    }
+    i
+}
+
+/// The letter an accent is put on, either braced or a single character,
+/// along with where it ends
+fn argument(chars: &[char], mut i: usize) -> (String, usize) {
+    while chars.get(i) == Some(&' ') {
+        i += 1;
+    }
+    let mut base = String::new();
+    match chars.get(i) {
+        Some('{') => {
+            let end = chars[i..].iter().position(|&c| c == '}').map_or(chars.len(), |end| i + end);
+            base = decode(&chars[i + 1..end].iter().collect::<String>());
+            i = end + 1;
+        }
+        Some('\\') => i = decode_command(chars, i + 1, &mut base),
+        Some(&c) => {
+            base.push(c);
+            i += 1;
+        }
+        None => {}
+    }
+    (base, i)
+}
+
+/// The combining character of an accent written with a symbol, e.g. `\'`
+fn symbol_accent(c: char) -> Option<char> {
+    match c {
+        '\'' => Some('\u{301}'),
+        '`'  => Some('\u{300}'),
+        '^'  => Some('\u{302}'),
+        '"'  => Some('\u{308}'),
+        '~'  => Some('\u{303}'),
+        '='  => Some('\u{304}'),
+        '.'  => Some('\u{307}'),
+        _ => None,
+    }
+}
+
+/// The combining character of an accent written with a letter, e.g. `\c`
+fn letter_accent(name: &str) -> Option<char> {
+    match name {
+        "u" => Some('\u{306}'),
+        "v" => Some('\u{30C}'),
+        "H" => Some('\u{30B}'),
+        "c" => Some('\u{327}'),
+        "k" => Some('\u{328}'),
+        "r" => Some('\u{30A}'),
+        "d" => Some('\u{323}'),
+        "b" => Some('\u{331}'),
+        _ => None,
+    }
+}
+
+fn special_letter(name: &str) -> Option<&'static str> {
+    match name {
+        "ss" => Some("ß"),
+        "o"  => Some("ø"),
+        "O"  => Some("Ø"),
+        "ae" => Some("æ"),
+        "AE" => Some("Æ"),
+        "oe" => Some("œ"),
+        "OE" => Some("Œ"),
+        "aa" => Some("å"),
+        "AA" => Some("Å"),
+        "l"  => Some("ł"),
+        "L"  => Some("Ł"),
+        // the dotless i and j only exist to carry accents
+        "i"  => Some("i"),
+        "j"  => Some("j"),
+        "TeX" => Some("TeX"),
+        "LaTeX" => Some("LaTeX"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(r#"M{\"u}ller"#), "Müller");
+        assert_eq!(decode(r#"Schr\"odinger"#), "Schrödinger");
+        assert_eq!(decode(r"G{\'e}rard \& Stra{\ss}e"), "Gérard & Straße");
+        assert_eq!(decode(r"Fran\c{c}ois {\o}rsted Dvo\v r\'ak"), "François ørsted Dvořák");
+        assert_eq!(decode(r#"na\"{\i}ve {\L}ukasiewicz"#), "naïve Łukasiewicz");
+        assert_eq!(decode(r"An \emph{Adjoint} {CFD} Method for $k$-$\epsilon$"), "An Adjoint CFD Method for k-");
+        assert_eq!(decode("Navier--Stokes --- 12--34"), "Navier\u{2013}Stokes \u{2014} 12\u{2013}34");
+    }
+
+    #[test]
+    fn test_decode_protected() {
+        assert_eq!(decode_protected(r#"M{\"u}ller on {CFD} and \emph{Navier}--{S}tokes"#), "Müller on {CFD} and Navier\u{2013}{S}tokes");
+        assert_eq!(decode_protected(r#"Schr\"{o}dinger's {\"U}ber {{NASA}} \{x\}"#), "Schrödinger's Über {NASA} {x}");
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("Fluid & Structure: 50% of k_t {sic}"), r"Fluid \& Structure: 50\% of k\_t \{sic\}");
+        assert_eq!(decode(&escape("Fluid & Structure_1")), "Fluid & Structure_1");
+    }
+}
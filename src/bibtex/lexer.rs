@@ -37,6 +37,12 @@ pub struct Tokens {
     stream: Vec<Token>, 
 }
 
+impl Default for Tokens {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Tokens {
     pub fn new() -> Self {
         Tokens { stream: Vec::new() }
@@ -61,7 +67,7 @@ impl<'a> Lexer<'a> {
     pub fn tokenize(&mut self) -> Result<Tokens, ParseError> {
         let mut tokens = Tokens::new();
         while let Some(token) = self.next_token()? {
-            if token.is_eof() {
+            if token.kind.is_eof() {
                 tokens.push(token);
                 break;
             }
@@ -84,8 +90,53 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    fn next_token() -> Result<Option<Token>, ParseError> {
+    fn next_token(&mut self) -> Result<Option<Token>, ParseError> {
+        self.skip_whitespace();
+        let (line, col) = (self.line, self.col);
+
+        let kind = match self.input.peek() {
+            None => TokenKind::Eof,
+            Some('%') => {
+                while let Some(&c) = self.input.peek() {
+                    if c == '\n' {
+                        break;
+                    }
+                    self.advance();
+                }
+                TokenKind::Comment
+            }
+            Some(&c) if Self::is_punctuation(c) => {
+                self.advance();
+                match c {
+                    '@' => TokenKind::At,
+                    '{' => TokenKind::OpenBrace,
+                    '}' => TokenKind::CloseBrace,
+                    '(' => TokenKind::OpenParen,
+                    ')' => TokenKind::CloseParen,
+                    ',' => TokenKind::Comma,
+                    '=' => TokenKind::Equals,
+                    '#' => TokenKind::Hash,
+                    _   => TokenKind::Quote,
+                }
+            }
+            Some(_) => {
+                let mut ident = String::new();
+                while let Some(&c) = self.input.peek() {
+                    if c.is_whitespace() || Self::is_punctuation(c) || c == '%' {
+                        break;
+                    }
+                    ident.push(c);
+                    self.advance();
+                }
+                TokenKind::Ident(ident)
+            }
+        };
+
+        Ok(Some(Token { kind, line, col }))
+    }
 
+    fn is_punctuation(c: char) -> bool {
+        matches!(c, '@' | '{' | '}' | '(' | ')' | ',' | '=' | '#' | '"')
     }
 
     fn skip_whitespace(&mut self) {
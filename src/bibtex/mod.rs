@@ -2,7 +2,9 @@
 
 pub mod parser;
 pub mod entry;
+pub mod error;
+pub mod fields;
+pub mod latex;
+pub mod rewrite;
 
-mod error;
-mod fields;
-mod lexer;
\ No newline at end of file
+mod lexer;
@@ -1,11 +1,21 @@
-use std::collections::HashMap;
-use std::ops::Range
-
-
-use super::error::ParseError;
-use super::entry::BibTeXEntry;
-use super::fields::{Author, Authors};
-
+use std::ops::Range;
+use std::str::FromStr;
+
+use super::error::{Diagnostic, ParseError};
+use super::entry::{BibTeXEntry, BibTeXEntryKind};
+use super::fields::BibTeXField;
+
+/// Where a field of an entry is written in the input
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSpan {
+    /// The field's name as written
+    pub key: String,
+    /// The byte offset of the field's name
+    pub start: usize,
+    /// The byte range of the field's value, braces, quotes and `#`
+    /// concatenations included
+    pub value: Range<usize>,
+}
 
 pub struct BibTeXParser<'a> {
     /// The raw UTF-8 input slice from a BibTeX file
@@ -24,23 +34,91 @@ impl<'a> BibTeXParser<'a> {
 
     /// Parses the entire input slice
     pub fn parse(&mut self) -> Result<Vec<BibTeXEntry>, ParseError> {
+        let spanned = self.parse_spanned()?;
+        Ok(spanned.into_iter().map(|(entry, _)| entry).collect())
+    }
+
+    /// Parses the entire input slice, keeping the byte range each entry
+    /// spans in the input, from its `@` to its closing brace
+    pub fn parse_spanned(&mut self) -> Result<Vec<(BibTeXEntry, Range<usize>)>, ParseError> {
         self.consume_whitespace();
         
         if self.peek().is_none() {
             return Err(ParseError::EmptyBibliography);
         }
         
-        let mut entries: Vec<BibTeXEntry> = Vec::new();
+        let mut entries = Vec::new();
 
         while self.peek().is_some() {
-            let entry = self.parse_entry()?;
-            entries.push(entry);
+            if !self.skip_special_block()? {
+                let start = self.cursor;
+                let entry = self.parse_entry()?;
+                entries.push((entry, start..self.cursor));
+            }
             self.consume_whitespace();
         }
 
         Ok(entries)
     }
 
+    /// Parses the entire input slice like [`Self::parse_spanned`], but skips
+    /// over any entry that fails to parse instead of giving up on the rest
+    pub fn parse_lenient(&mut self) -> (Vec<(BibTeXEntry, Range<usize>)>, Vec<Diagnostic>) {
+        let mut entries = Vec::new();
+        let mut diagnostics = Vec::new();
+        self.consume_whitespace();
+
+        while self.peek().is_some() {
+            let start = self.cursor;
+            let parsed = match self.skip_special_block() {
+                Ok(true) => Ok(None),
+                Ok(false) => self.parse_entry().map(Some),
+                Err(e) => Err(e),
+            };
+            match parsed {
+                Ok(Some(entry)) => entries.push((entry, start..self.cursor)),
+                Ok(None) => {}
+                Err(error) => {
+                    diagnostics.push(Diagnostic { line: self.line_of(start), error });
+                    self.skip_to_next_entry(start);
+                }
+            }
+            self.consume_whitespace();
+        }
+
+        (entries, diagnostics)
+    }
+
+    /// Parses the entry the input starts with, keeping where each of its
+    /// fields is written, so that one field can be changed without touching
+    /// the rest of the entry
+    pub fn parse_fields_spanned(&mut self) -> Result<(BibTeXEntry, Vec<FieldSpan>), ParseError> {
+        self.consume_whitespace();
+        self.parse_entry_spanned()
+    }
+
+    /// The line of a byte offset, counting from 1
+    fn line_of(&self, offset: usize) -> usize {
+        self.input[..offset].iter().filter(|&&byte| byte == b'\n').count() + 1
+    }
+
+    /// Moves the cursor past `start` to the next `@` that begins a line, which
+    /// is where the next entry most likely starts
+    fn skip_to_next_entry(&mut self, start: usize) {
+        self.cursor = start + 1;
+        while let Some(byte) = self.peek() {
+            let line_start = self.input[..self.cursor]
+                .iter()
+                .rev()
+                .take_while(|&&byte| byte != b'\n')
+                .all(u8::is_ascii_whitespace);
+            if byte == b'@' && line_start {
+                return;
+            }
+            self.advance();
+        }
+    }
+
     /// Peeks at the byte the cursor is currently pointing at 
     fn peek(&self) -> Option<u8> {
         self.input.get(self.cursor).copied()
@@ -53,15 +131,22 @@ impl<'a> BibTeXParser<'a> {
 
     /// Parces a BibTeX entry
     fn parse_entry(&mut self) -> Result<BibTeXEntry, ParseError> {
+        self.parse_entry_spanned().map(|(entry, _)| entry)
+    }
+
+    /// Parses a BibTeX entry along with where each of its fields is written
+    fn parse_entry_spanned(&mut self) -> Result<(BibTeXEntry, Vec<FieldSpan>), ParseError> {
         self.consume_char(b'@')?; // jump to the next entry
         let entry_kind_str = self.consume_identifier();
-        let entry_kind = BibTeXEntryKind::from_str(&entry_kind_str);
+        let entry_kind = BibTeXEntryKind::from_str(&entry_kind_str)
+            .unwrap_or(BibTeXEntryKind::Unknown);
 
         self.consume_char(b'{')?; // jump inside the braces
         let citekey = self.consume_identifier();
         self.consume_char(b',')?;
 
-        let mut fields = HashMap::new();
+        let mut entry = BibTeXEntry::new(entry_kind, citekey);
+        let mut spans = Vec::new();
         loop {
             self.consume_whitespace();
             if self.peek() == Some(b'}') {
@@ -69,64 +154,80 @@ impl<'a> BibTeXParser<'a> {
                 break;
             }
 
-            let (key, value) = self.parse_field()?;
-            fields.insert(key, value);
+            let (field, span) = self.parse_field()?;
+            entry.add_field(field);
+            spans.push(span);
 
             self.consume_whitespace();
             if self.peek() == Some(b',') {
                 self.advance();
             } else if self.peek() != Some(b'}') {
-                let recieved = self.peek().map(|b| b as char).unwrap_or(' ');
-                return Err(ParseError::UnexpectedCharacter { expected: '}', recieved })
+                let Some(received) = self.peek() else {
+                    return Err(ParseError::UnexpectedEOF);
+                };
+                return Err(ParseError::UnexpectedCharacter { expected: '}', received: received as char })
             }
         }
 
         self.consume_char(b'}')?;
 
-        let entry = BibTeXEntry { 
-            kind: entry_kind, 
-            citekey, 
-            fields, 
-        };
-        Ok(entry)
+        Ok((entry, spans))
     }
 
-    /// Parses a BibTeX field
-    fn parse_field(&mut self) -> Result<(String, String), ParseError> {
-        let key = self.consume_identifier();
-        self.consume_char(b'=')?;
-        
-        let value = match key.to_lowercase().as_str() {
-            "author" => self.parse_authors(),
-            "doi" => self.parse_doi(), 
-            "pages" => self.parse_pages(), 
-            "day" => self.parse_day(), 
-            "month" => self.parse_month(), 
-            "year" => self.parse_year(), 
-        }?;
-
-        Ok((key, value))
-    }
+    /// Skips over `@comment`, `@string` and `@preamble` blocks, returning 
+    /// whether one was skipped
+    fn skip_special_block(&mut self) -> Result<bool, ParseError> {
+        let start = self.cursor;
+        self.consume_char(b'@')?;
+        let kind = self.consume_identifier().to_lowercase();
+
+        if !matches!(kind.as_str(), "comment" | "string" | "preamble") {
+            self.cursor = start;
+            return Ok(false);
+        }
 
-    /// Parses a the value of some field 
-    fn parse_value(&mut self) -> Result<String, ParseError> {
         self.consume_whitespace();
-        match self.peek() {
-            Some(b'{') => self.consume_braced_string(),
-            Some(b'"') => self.consume_quoted_string(), 
-            Some(c) if c.is_ascii_digit() => Ok(self.consume_identifier()), 
-            _ => Err(ParseError::InvalidValue), 
+        if self.peek() == Some(b'{') {
+            self.consume_braced_string()?;
         }
+        Ok(true)
     }
 
-    /// Parses the author/s
-    fn parse_authors(&mut self) -> Result<Authors, ParseError> {
-        todo!()
+    /// Parses a BibTeX field
+    fn parse_field(&mut self) -> Result<(BibTeXField, FieldSpan), ParseError> {
+        let start = self.cursor;
+        let key = self.consume_identifier();
+        self.consume_char(b'=')?;
+        self.consume_whitespace();
+        let value_start = self.cursor;
+        let (value, value_end) = self.parse_value()?;
+
+        let span = FieldSpan { key: key.clone(), start, value: value_start..value_end };
+        Ok((BibTeXField::from_key_value(&key, &value), span))
     }
 
-    /// Parses the date
-    fn parse_date(&mut self) -> Result<Date, ParseError> {
-        todo!()
+    /// Parses a the value of some field, joining any `#` concatenations, and
+    /// the byte offset the value ends at
+    fn parse_value(&mut self) -> Result<(String, usize), ParseError> {
+        let mut value = String::new();
+        loop {
+            self.consume_whitespace();
+            let part = match self.peek() {
+                Some(b'{') => self.consume_braced_string()?,
+                Some(b'"') => self.consume_quoted_string()?, 
+                Some(c) if c.is_ascii_alphanumeric() => self.consume_identifier(), 
+                _ => return Err(ParseError::InvalidValue), 
+            };
+            value.push_str(&part);
+            let end = self.cursor;
+
+            self.consume_whitespace();
+            if self.peek() == Some(b'#') {
+                self.advance();
+            } else {
+                return Ok((value, end));
+            }
+        }
     }
 
     /// Consume a braced string
@@ -185,9 +286,9 @@ impl<'a> BibTeXParser<'a> {
                 Ok(())
             }
             Some(other_byte) => {
-                return Err(ParseError::UnexpectedCharacter { 
+                Err(ParseError::UnexpectedCharacter { 
                     expected: expected as char, 
-                    recieved: other_byte as char,  
+                    received: other_byte as char,  
                 })
             }
             None => Err(ParseError::UnexpectedEOF)
@@ -256,10 +357,10 @@ mod tests {
 
         assert_eq!(entry.kind, BibTeXEntryKind::Article);
         assert_eq!(entry.citekey, "test_key");
-        assert_eq!(entry.fields.get("author").unwrap(), "Author, A.");
-        assert_eq!(entry.fields.get("title").unwrap(), "A Test Title");
-        assert_eq!(entry.fields.get("year").unwrap(), "2025");
-        assert_eq!(entry.fields.get("journal").unwrap(), "Journal of Tests");
+        assert_eq!(entry.author().unwrap().to_bibtex_names(), "Author, A.");
+        assert_eq!(entry.title().unwrap(), "A Test Title");
+        assert_eq!(entry.year(), Some(&2025));
+        assert_eq!(entry.journal().unwrap(), "Journal of Tests");
     }
 
     #[test]
@@ -276,7 +377,39 @@ mod tests {
         assert_eq!(result[0].kind, BibTeXEntryKind::Article);
         assert_eq!(result[1].citekey, "key2");
         assert_eq!(result[1].kind, BibTeXEntryKind::Book);
-        assert_eq!(result[1].fields.get("author").unwrap(), "Author B");
+        assert_eq!(result[1].author().unwrap().to_string(), "Author B");
+    }
+
+    #[test]
+    fn test_spans() {
+        let input = "@comment{x}\n@article{key1, title = {A}}\n\n@book{key2, title = \"B\"}\n";
+        let result = BibTeXParser::new(input).parse_spanned().unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(&input[result[0].1.clone()], "@article{key1, title = {A}}");
+        assert_eq!(&input[result[1].1.clone()], "@book{key2, title = \"B\"}");
+    }
+
+    #[test]
+    fn test_field_spans() {
+        let input = "@article{key1,\n  title = {A {B}},\n  journal=aiaa # \" J\" % note\n}";
+        let (entry, spans) = BibTeXParser::new(input).parse_fields_spanned().unwrap();
+
+        assert_eq!(entry.citekey, "key1");
+        assert_eq!(spans.iter().map(|span| span.key.as_str()).collect::<Vec<_>>(), ["title", "journal"]);
+        assert_eq!(&input[spans[0].start..spans[0].value.end], "title = {A {B}}");
+        assert_eq!(&input[spans[1].value.clone()], "aiaa # \" J\"");
+    }
+
+    #[test]
+    fn test_lenient() {
+        let input = "@article{key1, title = {A}}\n@article{broken,\n  title = {B\n\n@book{key2, title = {C}}\n";
+        assert!(BibTeXParser::new(input).parse().is_err());
+
+        let (entries, diagnostics) = BibTeXParser::new(input).parse_lenient();
+        let citekeys: Vec<&str> = entries.iter().map(|(entry, _)| entry.citekey.as_str()).collect();
+        assert_eq!(citekeys, ["key1", "key2"]);
+        assert_eq!(diagnostics, [Diagnostic { line: 2, error: ParseError::UnexpectedEOF }]);
     }
 
     #[test]
@@ -291,6 +424,28 @@ mod tests {
         
         assert_eq!(result.len(), 1);
         let entry = &result[0];
-        assert_eq!(entry.fields.get("title").unwrap(), "A Title with {Nested Braces} is Cool");
+        assert_eq!(entry.title().unwrap(), "A Title with {Nested Braces} is Cool");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_write_round_trip() {
+        let input = r#"
+            @inproceedings{Kenway2019,
+                author = {Kenway, Gaetan K. W. and van der Waals, Jr, Johannes},
+                title = {Effective {Adjoint} Approaches},
+                booktitle = {AIAA Aviation Forum},
+                pages = {1--12},
+                month = oct,
+                year = 2019,
+                keywords = {adjoint, cfd}
+            }
+        "#;
+        let entries = BibTeXParser::new(input).parse().unwrap();
+        let written = entries[0].to_string();
+        assert!(written.starts_with("@inproceedings{Kenway2019,\n  author = {Kenway, Gaetan K. W. and"));
+        assert!(written.contains("  month = oct,\n"));
+
+        let reparsed = BibTeXParser::new(&written).parse().unwrap();
+        assert_eq!(reparsed, entries);
+    }
+}
@@ -0,0 +1,101 @@
+//! Writing changed fields back into a BibTeX source. Only the text of the
+//! changed fields is replaced, so comments, `@string` macros and the
+//! formatting of every entry are kept as they were
+
+use std::collections::HashMap;
+
+use super::parser::BibTeXParser;
+
+/// The source with the field `key` of each entry in `values` set to the
+/// value given for its citekey, and how many entries were changed. An entry
+/// without the field has it added after its last field. Entries that are not
+/// in the source are left out
+pub fn set_field(contents: &str, key: &str, values: &HashMap<String, String>) -> (String, usize) {
+    let (entries, _) = BibTeXParser::new(contents).parse_lenient();
+
+    let mut rewritten = String::with_capacity(contents.len());
+    let mut replaced = 0;
+    let mut end = 0;
+    for (entry, span) in entries {
+        let Some(value) = values.get(&entry.citekey) else {
+            continue;
+        };
+        let text = &contents[span.clone()];
+        let Ok((_, fields)) = BibTeXParser::new(text).parse_fields_spanned() else {
+            continue;
+        };
+        rewritten.push_str(&contents[end..span.start]);
+        match (fields.iter().find(|field| field.key.eq_ignore_ascii_case(key)), fields.last()) {
+            (Some(field), _) => {
+                rewritten.push_str(&text[..field.value.start]);
+                rewritten.push_str(&format!("{{{value}}}"));
+                rewritten.push_str(&text[field.value.end..]);
+            }
+            (None, Some(last)) => {
+                // the new field goes on a line of its own when the fields
+                // already have one each
+                let line = &text[text[..last.start].rfind('\n').map_or(0, |i| i + 1)..last.start];
+                let separator = if line.trim().is_empty() { format!(",\n{line}") } else { ", ".to_string() };
+                rewritten.push_str(&text[..last.value.end]);
+                rewritten.push_str(&format!("{separator}{key} = {{{value}}}"));
+                rewritten.push_str(&text[last.value.end..]);
+            }
+            (None, None) => {
+                let closing = text.len() - 1;
+                rewritten.push_str(text[..closing].trim_end());
+                rewritten.push_str(&format!("\n  {key} = {{{value}}}\n}}"));
+            }
+        }
+        end = span.end;
+        replaced += 1;
+    }
+    rewritten.push_str(&contents[end..]);
+    (rewritten, replaced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(values: &[(&str, &str)]) -> HashMap<String, String> {
+        values.iter().map(|(citekey, value)| (citekey.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_set_field() {
+        let input = concat!(
+            "% the reading list\n",
+            "@string{aiaa = {AIAA Journal}}\n\n",
+            "@article{Kenway2019,\n    title = {Effective Adjoint Approaches},\n    file = {old.pdf}\n}\n\n",
+            "@book{Muller2012,   title={Turbulence}   }\n",
+            "@misc{Empty2020,}\n",
+        );
+        let values = files(&[("Kenway2019", "2019/Kenway2019.pdf"), ("Muller2012", "2012/Muller2012.pdf"), ("Empty2020", "e.pdf")]);
+        let (rewritten, replaced) = set_field(input, "file", &values);
+        assert_eq!(replaced, 3);
+        assert_eq!(rewritten, concat!(
+            "% the reading list\n",
+            "@string{aiaa = {AIAA Journal}}\n\n",
+            "@article{Kenway2019,\n    title = {Effective Adjoint Approaches},\n    file = {2019/Kenway2019.pdf}\n}\n\n",
+            "@book{Muller2012,   title={Turbulence}, file = {2012/Muller2012.pdf}   }\n",
+            "@misc{Empty2020,\n  file = {e.pdf}\n}\n",
+        ));
+    }
+
+    #[test]
+    fn test_set_field_keeps_the_rest() {
+        let input = concat!(
+            "@string{aiaaj = {AIAA Journal}}\n\n",
+            "@TechReport{NASA2020,\n",
+            "  author={{NASA Langley Research Center}},\n",
+            "  title = \"Wind Tunnel Tests of the {CRM}\", % revised\n",
+            "  journal=aiaaj,\n",
+            "  year = 2020,\n",
+            "}\n",
+            "@article{Other2021, author = {Doe, J.}, journal = aiaaj}\n",
+        );
+        let (rewritten, replaced) = set_field(input, "file", &files(&[("NASA2020", "papers/NASA2020.pdf")]));
+        assert_eq!(replaced, 1);
+        assert_eq!(rewritten, input.replace("  year = 2020,\n", "  year = 2020,\n  file = {papers/NASA2020.pdf},\n"));
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+
+use litrev::bibtex::entry::BibTeXEntry;
+use litrev::bibtex::fields::BibTeXField;
+use litrev::bibtex::parser::BibTeXParser;
+use litrev::formats;
+use litrev::pdf::{self, draft};
+
+use super::current_project;
+use super::import::append;
+use super::note::edit;
+
+#[derive(Args)]
+pub struct AddArgs {
+    /// The PDF to draft the entry from, using its metadata and the text of
+    /// its first pages
+    #[arg(long = "from-pdf", value_name = "PDF", required = true)]
+    from_pdf: PathBuf,
+    /// The bibliography to add the entry to, defaults to the project's first
+    /// one
+    #[arg(long)]
+    into: Option<PathBuf>,
+    /// Adds the draft without asking to review it first
+    #[arg(long, short)]
+    yes: bool,
+}
+
+/// Drafts an entry for a PDF and, once it has been reviewed, adds it to a
+/// bibliography of the project
+pub fn run(args: AddArgs) -> Result<(), Box<dyn Error>> {
+    let mut project = current_project()?;
+    let path = std::path::absolute(&args.from_pdf)?;
+    let metadata = pdf::read(&path).map_err(|e| format!("{}: {e}", args.from_pdf.display()))?;
+
+    let existing = project.entries()?;
+    if let Some(doi) = metadata.dois.first() {
+        let known = existing.iter().find(|entry| entry.doi().is_some_and(|known| pdf::normalise_doi(known) == *doi));
+        if let Some(known) = known {
+            return Err(format!("{} is already in the library as {}", args.from_pdf.display(), known.citekey).into());
+        }
+    }
+
+    let mut entry = draft::draft(&metadata);
+    let citekeys: HashSet<String> = existing.iter().map(|entry| entry.citekey.clone()).collect();
+    entry.citekey = formats::unique_citekey(&entry.citekey, &citekeys);
+    let file = path.strip_prefix(project.root()).unwrap_or(&path);
+    entry.add_field(BibTeXField::from_key_value("file", &file.to_string_lossy()));
+
+    loop {
+        println!("{entry}");
+        let missing: Vec<&str> = [("author", entry.author().is_none()), ("title", entry.title().is_none()), ("year", entry.year().is_none())]
+            .into_iter()
+            .filter_map(|(field, missing)| missing.then_some(field))
+            .collect();
+        if !missing.is_empty() {
+            eprintln!("warning: no {} found, fill them in before citing", missing.join(", "));
+        }
+
+        if args.yes {
+            break;
+        }
+        if !io::stdin().is_terminal() {
+            eprintln!("Not added, review the draft and run again with --yes to add it");
+            return Ok(());
+        }
+        match ask("Add it? [y]es, [e]dit, [n]o ")?.as_str() {
+            "y" | "yes" => break,
+            "e" | "edit" => entry = edit_draft(&entry)?,
+            _ => {
+                println!("Not added");
+                return Ok(());
+            }
+        }
+    }
+
+    let citekey = entry.citekey.clone();
+    let target = append(&mut project, args.into, &[entry])?;
+    println!("Added {citekey} to {}", target.strip_prefix(project.root()).unwrap_or(&target).display());
+    Ok(())
+}
+
+fn ask(question: &str) -> io::Result<String> {
+    let mut err = io::stderr().lock();
+    write!(err, "{question}")?;
+    err.flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().to_lowercase())
+}
+
+/// Opens the draft in $EDITOR, returning it as edited
+fn edit_draft(entry: &BibTeXEntry) -> Result<BibTeXEntry, Box<dyn Error>> {
+    let path = std::env::temp_dir().join(format!("litrev-{}-{}.bib", std::process::id(), entry.citekey));
+    fs::write(&path, entry.to_string())?;
+    let edited = edit(&path).and_then(|()| parse_draft(&path));
+    let _ = fs::remove_file(&path);
+    edited
+}
+
+fn parse_draft(path: &Path) -> Result<BibTeXEntry, Box<dyn Error>> {
+    let mut entries = BibTeXParser::new(&fs::read_to_string(path)?).parse()
+        .map_err(|e| format!("the edited draft is not valid BibTeX: {e}"))?;
+    match entries.len() {
+        1 => Ok(entries.remove(0)),
+        found => Err(format!("the edited draft should have one entry, but it has {found}").into()),
+    }
+}
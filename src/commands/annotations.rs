@@ -0,0 +1,46 @@
+use std::error::Error;
+use std::fs;
+
+use clap::Args;
+use clap_complete::engine::ArgValueCompleter;
+
+use litrev::notes::annotations;
+use litrev::pdf::annotations::read_annotations;
+
+use super::{complete, current_project, lookup, note, open};
+
+#[derive(Args)]
+pub struct AnnotationsArgs {
+    /// The citekey of the entry whose PDF to read
+    #[arg(add = ArgValueCompleter::new(complete::citekeys))]
+    citekey: String,
+}
+
+/// Adds the annotations in an entry's PDF to its note, leaving those
+/// already there as they are
+pub fn run(args: AnnotationsArgs) -> Result<(), Box<dyn Error>> {
+    let project = current_project()?;
+    let entry = lookup(&project, &args.citekey)?;
+    let pdfs = open::find_pdfs(&project, &entry)?;
+    let Some(pdf) = pdfs.first() else {
+        return Err(format!("{} has no PDF to read annotations from, see `litrev link`", entry.citekey).into());
+    };
+    let shown = pdf.strip_prefix(project.root()).unwrap_or(pdf).display();
+    let found = read_annotations(pdf).map_err(|e| format!("{shown}: {e}"))?;
+    if found.is_empty() {
+        println!("{shown} has no highlights, underlines, notes or free text");
+        return Ok(());
+    }
+
+    let path = note::create(&project, &entry)?;
+    let (merged, added) = annotations::merge(&fs::read_to_string(&path)?, &found);
+    if added > 0 {
+        fs::write(&path, merged)?;
+    }
+    let note = path.strip_prefix(project.root()).unwrap_or(&path).display();
+    match added {
+        0 => println!("The {} annotations in {shown} are already in {note}", found.len()),
+        added => println!("Added {added} of the {} annotations in {shown} to {note}", found.len()),
+    }
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use std::env;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use clap_complete::engine::ArgValueCompleter;
+
+use litrev::project::Project;
+use litrev::style::csl::{CslProcessor, CslStyle};
+use litrev::style::{Markup, ReferenceFormatter, Style};
+
+use super::export::load_entries;
+use super::{complete, find_entry, UsageError};
+
+#[derive(Args)]
+pub struct CiteArgs {
+    /// The citekeys of the entries to format
+    #[arg(required = true, add = ArgValueCompleter::new(complete::citekeys))]
+    citekeys: Vec<String>,
+    /// The citation style: apa, ieee, chicago, harvard or aiaa, defaults to
+    /// the project's default style
+    #[arg(long)]
+    style: Option<Style>,
+    /// A CSL style file to format with instead of a built-in style
+    #[arg(long, conflicts_with = "style")]
+    csl: Option<PathBuf>,
+    /// Writes an in-text citation of the entries instead of references,
+    /// which needs a CSL style
+    #[arg(long)]
+    in_text: bool,
+    /// The markup to write: text, markdown or html
+    #[arg(long, default_value = "text")]
+    markup: Markup,
+    /// The bibliography files to look the citekeys up in, defaults to those
+    /// of the current project
+    #[arg(long = "bib")]
+    bibs: Vec<PathBuf>,
+}
+
+pub fn run(args: CiteArgs) -> Result<(), Box<dyn Error>> {
+    let CiteArgs { citekeys, style, csl, in_text, markup, bibs } = args;
+    // the project is only needed for whatever was not given
+    let project = match Project::discover(&env::current_dir()?) {
+        Ok(project) => Some(project),
+        Err(e) if bibs.is_empty() => return Err(e.into()),
+        Err(_) => None,
+    };
+    let (style, csl) = match (style, csl, &project) {
+        (None, None, Some(project)) => default_style(project)?,
+        (style, csl, _) => (style.unwrap_or(Style::Apa), csl),
+    };
+    if in_text && csl.is_none() {
+        return Err(UsageError("in-text citations need a CSL style, pass one with --csl").into());
+    }
+
+    let entries = match &project {
+        Some(project) if bibs.is_empty() => project.entries()?,
+        _ => load_entries(&bibs)?,
+    };
+    let cited = citekeys
+        .iter()
+        .map(|citekey| find_entry(&entries, citekey))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let Some(path) = csl {
+        let style = CslStyle::from_file(&path)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        let processor = CslProcessor::new(&style, &cited)?;
+        if in_text {
+            let citekeys: Vec<&str> = citekeys.iter().map(String::as_str).collect();
+            println!("{}", processor.citation(&citekeys)?.render(markup));
+        } else {
+            for reference in processor.bibliography()? {
+                println!("{}", reference.render(markup));
+            }
+        }
+        return Ok(());
+    }
+
+    let formatter = ReferenceFormatter::new(style, markup);
+    for reference in formatter.format_bibliography(&cited) {
+        println!("{reference}");
+    }
+    Ok(())
+}
+
+/// The project's default style, which is either the name of a built-in
+/// style or the path to a `.csl` file
+fn default_style(project: &Project) -> Result<(Style, Option<PathBuf>), Box<dyn Error>> {
+    let default = &project.config().default_style;
+    if default.ends_with(".csl") {
+        Ok((Style::Apa, Some(project.resolve(Path::new(default)))))
+    } else {
+        Ok((default.parse()?, None))
+    }
+}
@@ -0,0 +1,48 @@
+use std::error::Error;
+
+use clap::Args;
+use clap_complete::engine::ArgValueCompleter;
+
+use litrev::bibtex::entry::BibTeXEntry;
+use litrev::output::{self, OutputFormat};
+
+use super::{complete, current_project, lookup, with_stdout};
+
+#[derive(Args)]
+pub struct CitesArgs {
+    /// The citekey of the entry
+    #[arg(add = ArgValueCompleter::new(complete::citekeys))]
+    citekey: String,
+}
+
+/// Lists the entries the entry's PDF cites
+pub fn run(args: CitesArgs, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    list(args, format, true)
+}
+
+/// Lists the entries whose PDFs cite the entry
+pub fn cited_by(args: CitesArgs, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    list(args, format, false)
+}
+
+fn list(args: CitesArgs, format: OutputFormat, cites: bool) -> Result<(), Box<dyn Error>> {
+    let project = current_project()?;
+    let entry = lookup(&project, &args.citekey)?;
+    let store = project.store()?;
+    let citations = store.citations()?;
+    if citations.is_empty() {
+        eprintln!("No citations have been found yet, run `litrev link` to read them from the linked PDFs");
+    }
+
+    let citekeys: Vec<&str> = citations
+        .iter()
+        .filter_map(|citation| match cites {
+            true => (citation.citing == entry.citekey).then_some(citation.cited.as_str()),
+            false => (citation.cited == entry.citekey).then_some(citation.citing.as_str()),
+        })
+        .collect();
+    let entries: Vec<BibTeXEntry> = store.entries()?.into_iter().map(|stored| stored.entry).collect();
+    let listed: Vec<&BibTeXEntry> = entries.iter().filter(|entry| citekeys.contains(&entry.citekey.as_str())).collect();
+    let tags = project.tags()?;
+    with_stdout(|out| output::write_entries(out, &listed, &tags, format))
+}
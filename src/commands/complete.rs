@@ -0,0 +1,65 @@
+//! Dynamic shell completion, enabled by sourcing `COMPLETE=<shell> litrev`.
+//! The completers run on every <TAB>, so they read the project's index
+//! rather than parsing the bibliographies
+
+use std::env;
+use std::ffi::OsStr;
+
+use clap_complete::engine::CompletionCandidate;
+
+use litrev::project::Project;
+
+/// Citekeys starting with what has been typed so far, ignoring case, with
+/// the title of each entry as its description
+pub fn citekeys(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(project) = project() else {
+        return Vec::new();
+    };
+    let current = current.to_string_lossy().to_lowercase();
+    project
+        .citekeys()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(citekey, _)| citekey.to_lowercase().starts_with(&current))
+        .map(|(citekey, title)| {
+            let help = (!title.is_empty()).then(|| title.into());
+            CompletionCandidate::new(citekey).help(help)
+        })
+        .collect()
+}
+
+/// Tags in use, with how many entries have each of them
+pub fn tags(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(tags) = project().and_then(|project| project.tags().ok()) else {
+        return Vec::new();
+    };
+    let current = current.to_string_lossy().to_lowercase();
+    tags.counts()
+        .into_iter()
+        .filter(|(tag, _)| tag.starts_with(&current))
+        .map(|(tag, count)| {
+            let help = format!("{count} {}", if count == 1 { "entry" } else { "entries" });
+            CompletionCandidate::new(tag).help(Some(help.into()))
+        })
+        .collect()
+}
+
+/// Saved searches, with the query of each
+pub fn saved_searches(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(searches) = project().and_then(|project| project.saved_searches().ok()) else {
+        return Vec::new();
+    };
+    let current = current.to_string_lossy();
+    searches
+        .iter()
+        .filter(|(name, _)| name.starts_with(current.as_ref()))
+        .map(|(name, search)| {
+            let help = (!search.query.is_empty()).then(|| search.query.clone().into());
+            CompletionCandidate::new(name).help(help)
+        })
+        .collect()
+}
+
+fn project() -> Option<Project> {
+    Project::discover(&env::current_dir().ok()?).ok()
+}
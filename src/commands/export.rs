@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use clap_complete::engine::ArgValueCompleter;
+
+use litrev::bibtex::entry::BibTeXEntry;
+use litrev::formats::{self, Format};
+use litrev::output::OutputFormat;
+
+use super::{complete, current_project, search, UnsupportedFormat, UsageError};
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// The format to write: bibtex, ris or hayagriva. `--format` takes the
+    /// same values
+    #[arg(long, value_enum)]
+    to: Option<ExportFormat>,
+    /// Where to write the output, defaults to stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Only exports the entries a saved search finds now, so that the
+    /// output can be regenerated as the library changes
+    #[arg(long, value_name = "NAME", conflicts_with = "files", add = ArgValueCompleter::new(complete::saved_searches))]
+    group: Option<String>,
+    /// The bibliography files to convert, in any format litrev can import,
+    /// defaults to those of the current project
+    files: Vec<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    #[value(name = "bibtex", alias = "bib")]
+    BibTeX,
+    Ris,
+    Hayagriva,
+}
+
+impl From<ExportFormat> for Format {
+    fn from(format: ExportFormat) -> Self {
+        match format {
+            ExportFormat::BibTeX    => Format::BibTeX,
+            ExportFormat::Ris       => Format::Ris,
+            ExportFormat::Hayagriva => Format::Hayagriva,
+        }
+    }
+}
+
+pub fn run(args: ExportArgs, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let to = match (args.to, format) {
+        (Some(to), _)                   => to.into(),
+        (None, OutputFormat::BibTeX)    => Format::BibTeX,
+        (None, OutputFormat::Ris)       => Format::Ris,
+        (None, OutputFormat::Hayagriva) => Format::Hayagriva,
+        // the default, so most likely no format was given at all
+        (None, OutputFormat::Table) => {
+            return Err(UsageError("export needs a format to write, pass --to or --format bibtex, ris or hayagriva").into());
+        }
+        (None, format) => return Err(UnsupportedFormat { command: "export", format }.into()),
+    };
+    let entries = match &args.group {
+        Some(name) => search::group(&current_project()?, name)?,
+        None if args.files.is_empty() => current_project()?.entries()?,
+        None => load_entries(&args.files)?,
+    };
+    let exported = formats::export(&entries, to)?;
+    match args.output {
+        Some(path) => fs::write(path, exported)?,
+        None => print!("{exported}"),
+    }
+    Ok(())
+}
+
+/// Reads and imports every file, whatever format it is in
+pub fn load_entries(files: &[PathBuf]) -> Result<Vec<BibTeXEntry>, Box<dyn Error>> {
+    let mut entries = Vec::new();
+    for file in files {
+        let contents = fs::read_to_string(file)
+            .map_err(|e| format!("{}: {e}", file.display()))?;
+        let imported = formats::import(&contents, &HashSet::new())
+            .map_err(|e| format!("{}: {e}", file.display()))?;
+        entries.extend(imported);
+    }
+    Ok(entries)
+}
@@ -0,0 +1,264 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Subcommand};
+use serde::Serialize;
+
+use litrev::bibtex::rewrite;
+use litrev::files::dedupe::{self, Duplicates};
+use litrev::files::organize;
+use litrev::link::{file_field_paths, LinkStatus, Links};
+use litrev::output::{OutputFormat, SCHEMA_VERSION};
+use litrev::project::Project;
+
+use super::{current_project, local_pdfs, with_stdout, UnsupportedFormat};
+
+#[derive(Args)]
+pub struct FilesArgs {
+    #[command(subcommand)]
+    command: FilesCommand,
+}
+
+#[derive(Subcommand)]
+enum FilesCommand {
+    /// Groups the PDFs that are copies of each other, by their contents or
+    /// their text
+    Dedupe,
+    /// Moves the linked PDFs to paths made from their entries, see
+    /// `organize` in .litrev/config.toml
+    Organize(OrganizeArgs),
+}
+
+#[derive(Args)]
+struct OrganizeArgs {
+    /// Lists what would be moved and which file fields would change,
+    /// without changing anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+pub fn run(args: FilesArgs, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let project = current_project()?;
+    match args.command {
+        FilesCommand::Dedupe         => dedupe(&project, format),
+        FilesCommand::Organize(args) => organize(&project, args),
+    }
+}
+
+/// A group of copies as it appears in JSON output
+#[derive(Serialize)]
+struct DuplicatesRecord<'a> {
+    schema_version: u32,
+    similarity: f64,
+    files: Vec<FileRecord<'a>>,
+}
+
+#[derive(Serialize)]
+struct FileRecord<'a> {
+    path: &'a Path,
+    hash: &'a str,
+    /// The entries the file is linked to
+    citekeys: Vec<&'a str>,
+}
+
+/// Lists the groups of PDFs that are copies of each other, with the entries
+/// each copy is linked to
+fn dedupe(project: &Project, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    if matches!(format, OutputFormat::Csv | OutputFormat::BibTeX | OutputFormat::Ris | OutputFormat::Hayagriva) {
+        return Err(UnsupportedFormat { command: "files dedupe", format }.into());
+    }
+    let links = project.links()?;
+    let (pdfs, texts) = local_pdfs(project, &links)?;
+    let found = dedupe::duplicates(&pdfs, &texts);
+    let records: Vec<DuplicatesRecord> = found.iter().map(|group| record(project, &links, group)).collect();
+
+    with_stdout(|out| match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, &records)?;
+            writeln!(out)
+        }
+        OutputFormat::Jsonl => {
+            for record in &records {
+                serde_json::to_writer(&mut *out, record)?;
+                writeln!(out)?;
+            }
+            Ok(())
+        }
+        _ => {
+            if records.is_empty() {
+                return writeln!(out, "No two of the {} PDFs are copies of each other", pdfs.len());
+            }
+            for record in &records {
+                let alike = match record.similarity {
+                    1.0 => String::from("identical"),
+                    similarity => format!("{:.0}% of their text alike", 100.0 * similarity),
+                };
+                writeln!(out, "{} copies, {alike}", record.files.len())?;
+                for file in &record.files {
+                    write!(out, "  {}  {}", &file.hash[..8], file.path.display())?;
+                    if !file.citekeys.is_empty() {
+                        write!(out, "  ({})", file.citekeys.join(", "))?;
+                    }
+                    writeln!(out)?;
+                }
+            }
+            writeln!(out, "\nCopies with the same hash have the same contents")
+        }
+    })
+}
+
+fn record<'a>(project: &Project, links: &'a Links, group: &Duplicates<'a>) -> DuplicatesRecord<'a> {
+    let files = group
+        .copies
+        .iter()
+        .flatten()
+        .map(|pdf| FileRecord {
+            path: pdf.path.strip_prefix(project.root()).unwrap_or(&pdf.path),
+            hash: &pdf.hash,
+            citekeys: links
+                .iter()
+                .filter(|link| link.path == pdf.path && link.status != LinkStatus::Rejected && !link.needs_review())
+                .map(|link| link.citekey.as_str())
+                .collect(),
+        })
+        .collect();
+    DuplicatesRecord { schema_version: SCHEMA_VERSION, similarity: group.similarity, files }
+}
+
+/// A PDF to be filed under its entry
+struct Move {
+    citekey: String,
+    from: PathBuf,
+    to: PathBuf,
+}
+
+/// Moves each entry's best linked PDF to the path the `organize` template
+/// gives it in the first PDF directory, then points the links and the
+/// entries' `file` fields at the new paths
+fn organize(project: &Project, args: OrganizeArgs) -> Result<(), Box<dyn Error>> {
+    let Some(dir) = project.pdf_dirs().into_iter().next() else {
+        return Err("there is no PDF directory to organize PDFs into, add one to pdf_dirs in .litrev/config.toml".into());
+    };
+    let template = &project.config().organize;
+    let store = project.store()?;
+    let stored = store.entries()?;
+    let mut links = project.links()?;
+
+    let mut moves: Vec<Move> = Vec::new();
+    let mut targets = HashSet::new();
+    for entry in stored.iter().map(|stored| &stored.entry) {
+        let linked = links.of(&entry.citekey);
+        let Some(link) = linked.first().filter(|link| !link.needs_review() && link.path.is_file()) else {
+            continue;
+        };
+        let to = dir.join(organize::expand(template, entry)?);
+        // a PDF linked to several entries is filed under the one it is
+        // most likely the paper of
+        let owned = links.best_for(&link.path).is_some_and(|best| best.citekey == entry.citekey);
+        if linked.iter().any(|link| link.path == to) || !owned {
+            continue;
+        }
+        if to.exists() || !targets.insert(to.clone()) {
+            eprintln!(
+                "warning: {} is not moved, {} is already taken",
+                relative(project, &link.path).display(),
+                relative(project, &to).display(),
+            );
+            continue;
+        }
+        moves.push(Move { citekey: entry.citekey.clone(), from: link.path.clone(), to });
+    }
+
+    let mut moved = Vec::new();
+    let mut failure = None;
+    for planned in &moves {
+        let verb = if args.dry_run { "Would move" } else { "Moved" };
+        if !args.dry_run {
+            if let Err(e) = move_file(&planned.from, &planned.to) {
+                failure = Some(format!("{}: {e}", relative(project, &planned.from).display()));
+                break;
+            }
+        }
+        println!("{verb} {} to {}", relative(project, &planned.from).display(), relative(project, &planned.to).display());
+        moved.push(planned);
+    }
+
+    // the `file` fields of entries that were filed, or that point at a
+    // PDF that moved
+    let mut changed: HashMap<PathBuf, HashMap<String, String>> = HashMap::new();
+    for stored in stored {
+        let old = stored.entry.non_standard_field("file");
+        let mut value = old.cloned();
+        for planned in &moved {
+            let points_at = value.as_deref().is_some_and(|value| {
+                file_field_paths(value).iter().any(|path| project.resolve(path) == planned.from)
+            });
+            if planned.citekey == stored.entry.citekey || points_at {
+                let to = relative(project, &planned.to).to_string_lossy();
+                value = Some(organize::moved_file_field(value.as_deref(), &planned.from, &to, |path| project.resolve(path)));
+            }
+        }
+        let Some(value) = value.filter(|value| Some(value) != old) else {
+            continue;
+        };
+        if stored.span.is_none() {
+            eprintln!(
+                "warning: the file field of {} is not updated, only BibTeX bibliographies are rewritten",
+                stored.entry.citekey,
+            );
+            continue;
+        }
+        let verb = if args.dry_run { "Would change" } else { "Changed" };
+        match old {
+            Some(old) => println!("{verb} the file field of {} from {{{old}}} to {{{value}}}", stored.entry.citekey),
+            None => println!("{verb} the file field of {} to {{{value}}}", stored.entry.citekey),
+        }
+        changed.entry(stored.source).or_default().insert(stored.entry.citekey, value);
+    }
+    let mut updated = 0;
+    for (source, values) in &changed {
+        let contents = fs::read_to_string(source).map_err(|e| format!("{}: {e}", source.display()))?;
+        let (rewritten, replaced) = rewrite::set_field(&contents, "file", values);
+        if !args.dry_run {
+            fs::write(source, rewritten).map_err(|e| format!("{}: {e}", source.display()))?;
+        }
+        updated += replaced;
+    }
+
+    if !args.dry_run {
+        let moved: HashMap<&Path, &Path> = moved.iter().map(|planned| (planned.from.as_path(), planned.to.as_path())).collect();
+        links.map_paths(|path| moved.get(path).map_or_else(|| path.to_path_buf(), |to| to.to_path_buf()));
+        project.save_links(&links)?;
+    }
+
+    let pdfs = if moved.len() == 1 { "PDF" } else { "PDFs" };
+    let fields = if updated == 1 { "file field" } else { "file fields" };
+    match (args.dry_run, moved.len()) {
+        (_, 0) if failure.is_none() => println!("Every linked PDF is already where `{template}` files it"),
+        (true, count) => println!("Would move {count} {pdfs} and update {updated} {fields}, run without --dry-run to do so"),
+        (false, count) => println!("Moved {count} {pdfs} and updated {updated} {fields}"),
+    }
+    match failure {
+        Some(failure) => Err(failure.into()),
+        None => Ok(()),
+    }
+}
+
+/// Moves a file, copying it across filesystems if it cannot be renamed
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)
+}
+
+fn relative<'a>(project: &Project, path: &'a Path) -> &'a Path {
+    path.strip_prefix(project.root()).unwrap_or(path)
+}
@@ -0,0 +1,106 @@
+use std::error::Error;
+use std::io::Write;
+use std::path::Path;
+
+use clap::Args;
+use clap_complete::engine::ArgValueCompleter;
+use serde::Serialize;
+
+use litrev::link::LinkStatus;
+use litrev::output::{OutputFormat, SCHEMA_VERSION};
+use litrev::references::{self, Evidence};
+
+use super::{complete, current_project, local_pdfs, lookup, with_stdout, UnsupportedFormat};
+
+#[derive(Args)]
+pub struct FindRefsArgs {
+    /// The citekey of the entry to find citations of
+    #[arg(add = ArgValueCompleter::new(complete::citekeys))]
+    citekey: String,
+}
+
+/// A reference to the entry as it appears in JSON output
+#[derive(Serialize)]
+struct CitationRecord<'a> {
+    schema_version: u32,
+    /// The entry the citing PDF is linked to, if any
+    citekey: Option<&'a str>,
+    path: &'a Path,
+    page: u32,
+    evidence: Evidence,
+    reference: &'a str,
+}
+
+/// Lists the local PDFs whose reference lists cite an entry, with the
+/// references that cite it
+pub fn run(args: FindRefsArgs, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    if matches!(format, OutputFormat::Csv | OutputFormat::BibTeX | OutputFormat::Ris | OutputFormat::Hayagriva) {
+        return Err(UnsupportedFormat { command: "find-refs", format }.into());
+    }
+    let project = current_project()?;
+    let entry = lookup(&project, &args.citekey)?;
+    let links = project.links()?;
+    let (pdfs, texts) = local_pdfs(&project, &links)?;
+
+    // the entry's own PDFs are not citing it, wherever copies of them are
+    let own: Vec<&str> = links
+        .of(&entry.citekey)
+        .into_iter()
+        .filter_map(|link| pdfs.iter().find(|pdf| pdf.path == link.path))
+        .map(|pdf| pdf.hash.as_str())
+        .collect();
+    let mut citations = Vec::new();
+    for pdf in pdfs.iter().filter(|pdf| !own.contains(&pdf.hash.as_str())) {
+        let Some(pages) = texts.get(&pdf.hash) else {
+            continue;
+        };
+        let citing = links
+            .iter()
+            .filter(|link| link.path == pdf.path && link.status != LinkStatus::Rejected)
+            .max_by_key(|link| link.status == LinkStatus::Confirmed)
+            .map(|link| link.citekey.as_str());
+        for reference in references::reference_list(pages) {
+            if let Some(evidence) = references::evidence(&entry, &reference.text) {
+                citations.push((citing, pdf.path.as_path(), reference, evidence));
+            }
+        }
+    }
+    citations.sort_by(|a, b| a.3.cmp(&b.3).then(a.1.cmp(b.1)));
+
+    let records: Vec<CitationRecord> = citations
+        .iter()
+        .map(|(citing, path, reference, evidence)| CitationRecord {
+            schema_version: SCHEMA_VERSION,
+            citekey: *citing,
+            path: path.strip_prefix(project.root()).unwrap_or(path),
+            page: reference.page,
+            evidence: *evidence,
+            reference: &reference.text,
+        })
+        .collect();
+
+    with_stdout(|out| match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, &records)?;
+            writeln!(out)
+        }
+        OutputFormat::Jsonl => {
+            for record in &records {
+                serde_json::to_writer(&mut *out, record)?;
+                writeln!(out)?;
+            }
+            Ok(())
+        }
+        _ => {
+            if records.is_empty() {
+                return writeln!(out, "No local PDF cites {} in its reference list", entry.citekey);
+            }
+            for record in &records {
+                let citing = record.citekey.map(|citekey| format!("{citekey}  ")).unwrap_or_default();
+                writeln!(out, "{citing}{} p. {}  (by {})", record.path.display(), record.page, record.evidence)?;
+                writeln!(out, "  {}", record.reference)?;
+            }
+            Ok(())
+        }
+    })
+}
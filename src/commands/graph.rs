@@ -0,0 +1,60 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+
+use litrev::output::SCHEMA_VERSION;
+use litrev::references::graph::Graph;
+
+use super::current_project;
+
+#[derive(Args)]
+pub struct GraphArgs {
+    /// The format to write
+    #[arg(long, value_enum, default_value = "dot")]
+    to: GraphFormat,
+    /// Where to write the graph, defaults to stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GraphFormat {
+    Dot,
+    #[value(name = "graphml")]
+    GraphMl,
+    Json,
+}
+
+/// The graph as it appears in JSON output
+#[derive(serde::Serialize)]
+struct GraphRecord<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    graph: &'a Graph,
+}
+
+/// Writes the citations between entries as a graph, with the entries that
+/// cite or are cited as its nodes
+pub fn run(args: GraphArgs) -> Result<(), Box<dyn Error>> {
+    let project = current_project()?;
+    let store = project.store()?;
+    let entries: Vec<_> = store.entries()?.into_iter().map(|stored| stored.entry).collect();
+    let citations = store.citations()?;
+    if citations.is_empty() {
+        eprintln!("No citations have been found yet, run `litrev link` to read them from the linked PDFs");
+    }
+
+    let graph = Graph::new(&entries, &citations);
+    let written = match args.to {
+        GraphFormat::Dot     => graph.to_dot(),
+        GraphFormat::GraphMl => graph.to_graphml(),
+        GraphFormat::Json    => serde_json::to_string_pretty(&GraphRecord { schema_version: SCHEMA_VERSION, graph: &graph })? + "\n",
+    };
+    match args.output {
+        Some(path) => fs::write(path, written)?,
+        None => print!("{written}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Args;
+
+use litrev::bibtex::entry::BibTeXEntry;
+use litrev::formats;
+use litrev::project::Project;
+
+use super::current_project;
+
+/// Where entries are imported into when the project has no bibliography yet
+const DEFAULT_BIB: &str = "references.bib";
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// The files to import, in any format litrev can read
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// The bibliography to add the entries to, defaults to the project's
+    /// first one
+    #[arg(long)]
+    into: Option<PathBuf>,
+}
+
+/// Appends the entries of each file to a bibliography of the project,
+/// skipping any whose DOI is already in the library and renaming clashing
+/// citekeys
+pub fn run(args: ImportArgs) -> Result<(), Box<dyn Error>> {
+    let mut project = current_project()?;
+    let existing = project.entries()?;
+    let mut citekeys: HashSet<String> = existing.iter().map(|entry| entry.citekey.clone()).collect();
+    let mut dois: HashSet<String> = existing.iter().filter_map(|entry| entry.doi()).map(|doi| normalise_doi(doi)).collect();
+
+    let mut imported = Vec::new();
+    let mut skipped = 0;
+    for file in &args.files {
+        let contents = fs::read_to_string(file)
+            .map_err(|e| format!("{}: {e}", file.display()))?;
+        let entries = formats::import(&contents, &citekeys)
+            .map_err(|e| format!("{}: {e}", file.display()))?;
+        for entry in entries {
+            if let Some(doi) = entry.doi().map(|doi| normalise_doi(doi)) {
+                if !dois.insert(doi) {
+                    skipped += 1;
+                    continue;
+                }
+            }
+            citekeys.insert(entry.citekey.clone());
+            imported.push(entry);
+        }
+    }
+
+    let target = append(&mut project, args.into, &imported)?;
+    println!("Imported {} entries into {}", imported.len(), target.display());
+    if skipped > 0 {
+        println!("Skipped {skipped} already in the library");
+    }
+    Ok(())
+}
+
+/// Appends entries to a bibliography, `into` or else the project's first,
+/// and starts tracking it if it is new to the project. Returns the path of
+/// the bibliography
+pub fn append(project: &mut Project, into: Option<PathBuf>, entries: &[BibTeXEntry]) -> Result<PathBuf, Box<dyn Error>> {
+    let target = match into {
+        Some(path) => std::path::absolute(path)?,
+        None => project.bib_files().into_iter().next().unwrap_or_else(|| project.resolve(DEFAULT_BIB.as_ref())),
+    };
+    let mut bib = OpenOptions::new().create(true).append(true).open(&target)
+        .map_err(|e| format!("{}: {e}", target.display()))?;
+    for entry in entries {
+        write!(bib, "\n{entry}")?;
+    }
+
+    // start tracking the bibliography if it is new to the project
+    if !project.bib_files().contains(&target) {
+        let tracked = target.strip_prefix(project.root()).map_or_else(|_| target.clone(), PathBuf::from);
+        project.config_mut().bib.push(tracked);
+        project.save_config()?;
+    }
+    Ok(target)
+}
+
+fn normalise_doi(doi: &str) -> String {
+    doi.trim().to_lowercase()
+}
@@ -0,0 +1,40 @@
+use std::error::Error;
+
+use clap::Args;
+
+use litrev::project::Project;
+use litrev::store::IndexSummary;
+
+use super::current_project;
+
+#[derive(Args)]
+pub struct IndexArgs {
+    /// Deletes the index and indexes every bibliography from scratch
+    #[arg(long)]
+    rebuild: bool,
+}
+
+/// Re-indexes whichever of the project's bibliographies have changed
+pub fn run(args: IndexArgs) -> Result<(), Box<dyn Error>> {
+    let project = current_project()?;
+    let summary = if args.rebuild { project.rebuild_store()? } else { project.update_store()? };
+    report_diagnostics(&project, &summary);
+
+    if args.rebuild {
+        println!("Indexed {} entries from {} files", summary.added, summary.files);
+    } else if summary.is_empty() {
+        println!("The index is up to date");
+    } else {
+        println!("Re-indexed {} of {} files: {summary}", summary.files, project.bib_files().len());
+    }
+    Ok(())
+}
+
+/// Warns about each entry that failed to parse, e.g.
+/// `warning: refs.bib:12: unexpected eof, skipped`
+pub fn report_diagnostics(project: &Project, summary: &IndexSummary) {
+    for (path, diagnostic) in &summary.diagnostics {
+        let path = path.strip_prefix(project.root()).unwrap_or(path);
+        eprintln!("warning: {}:{}: {}, skipped", path.display(), diagnostic.line, diagnostic.error);
+    }
+}
@@ -0,0 +1,77 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use clap::Args;
+
+use litrev::project::{self, Config, Project};
+use litrev::style::Style;
+
+#[derive(Args)]
+pub struct InitArgs {
+    /// Creates the user-level library used outside of any project instead
+    #[arg(long)]
+    global: bool,
+    /// The bibliography files to track, defaults to every .bib file in the
+    /// directory
+    #[arg(long = "bib")]
+    bibs: Vec<PathBuf>,
+    /// The directories to look for PDFs in
+    #[arg(long = "pdf-dir")]
+    pdf_dirs: Vec<PathBuf>,
+    /// The default citation style: apa, ieee, chicago, harvard, aiaa or a
+    /// .csl file
+    #[arg(long, default_value = "apa", value_parser = parse_style)]
+    style: String,
+}
+
+pub fn run(args: InitArgs) -> Result<(), Box<dyn Error>> {
+    let root = if args.global { project::global_root()? } else { env::current_dir()? };
+    fs::create_dir_all(&root)?;
+    // the paths are given relative to where litrev runs, which is not the
+    // root of the global library
+    let (bibs, pdf_dirs) = if args.global {
+        (absolute(args.bibs)?, absolute(args.pdf_dirs)?)
+    } else {
+        (args.bibs, args.pdf_dirs)
+    };
+    let bib = if bibs.is_empty() { find_bib_files(&root)? } else { bibs };
+    let config = Config { bib, pdf_dirs, default_style: args.style, ..Config::default() };
+    let project = Project::init(&root, config)?;
+
+    println!("Initialised litrev project in {}", project.dir().display());
+    for bib in &project.config().bib {
+        println!("  tracking {}", bib.display());
+    }
+    Ok(())
+}
+
+/// A built-in style name or the path of a `.csl` file
+fn parse_style(style: &str) -> Result<String, String> {
+    if !style.ends_with(".csl") {
+        style.parse::<Style>()?;
+    }
+    Ok(style.to_string())
+}
+
+/// The paths made absolute against the current directory, following any
+/// symlinks in those that exist
+fn absolute(paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    paths
+        .into_iter()
+        .map(|path| Ok(fs::canonicalize(&path).or_else(|_| std::path::absolute(&path))?))
+        .collect()
+}
+
+/// Every `.bib` file directly inside a directory, relative to it
+fn find_bib_files(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut found = Vec::new();
+    for file in fs::read_dir(dir)? {
+        let path = file?.path();
+        if path.extension().is_some_and(|extension| extension == "bib") {
+            found.extend(path.file_name().map(PathBuf::from));
+        }
+    }
+    found.sort();
+    Ok(found)
+}
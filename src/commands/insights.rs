@@ -0,0 +1,33 @@
+use std::error::Error;
+
+use clap::Args;
+
+use litrev::insights::Insights;
+use litrev::output::OutputFormat;
+
+use super::{current_project, with_stdout, UnsupportedFormat};
+
+#[derive(Args)]
+pub struct InsightsArgs {
+    /// How many of the top authors and keywords to show
+    #[arg(long, default_value_t = 10)]
+    limit: usize,
+}
+
+pub fn run(args: InsightsArgs, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let project = current_project()?;
+    let insights = Insights::new(&project.entries()?, args.limit);
+    match format {
+        OutputFormat::Table => with_stdout(|out| insights.write_text(out)),
+        OutputFormat::Json | OutputFormat::Jsonl => {
+            let json = if format == OutputFormat::Json {
+                serde_json::to_string_pretty(&insights)?
+            } else {
+                serde_json::to_string(&insights)?
+            };
+            println!("{json}");
+            Ok(())
+        }
+        format => Err(UnsupportedFormat { command: "insights", format }.into()),
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::HashSet;
+use std::env;
+use std::error::Error;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use serde::Serialize;
+
+use litrev::link::{self, Link, LinkStatus, Signal, REVIEW_BELOW};
+use litrev::output::{OutputFormat, SCHEMA_VERSION};
+use litrev::project::Project;
+use litrev::references::Resolver;
+use litrev::store::{Citation, StoredPdf};
+
+use super::{current_project, lookup, with_stdout, UnsupportedFormat};
+
+#[derive(Args)]
+pub struct LinkArgs {
+    /// Lists the links too uncertain to trust without a look, instead of
+    /// linking
+    #[arg(long, conflicts_with_all = ["confirm", "reject"])]
+    review: bool,
+    /// Confirms the links of an entry, or links it to the given PDF, so the
+    /// link is kept from then on
+    #[arg(long, num_args = 1..=2, value_names = ["CITEKEY", "PDF"], conflicts_with = "reject")]
+    confirm: Vec<String>,
+    /// Rejects the links of an entry, or only its link to the given PDF, so
+    /// the two are never linked again
+    #[arg(long, num_args = 1..=2, value_names = ["CITEKEY", "PDF"])]
+    reject: Vec<String>,
+}
+
+/// Links the project's entries to the PDFs in its PDF directories, or
+/// reviews the links
+pub fn run(args: LinkArgs, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let project = current_project()?;
+    if args.review {
+        return review(&project, format);
+    }
+    if let Some((status, decision)) = [(LinkStatus::Confirmed, &args.confirm), (LinkStatus::Rejected, &args.reject)]
+        .into_iter()
+        .find(|(_, decision)| !decision.is_empty())
+    {
+        return decide(&project, status, decision);
+    }
+    update(&project)
+}
+
+/// Reads the new and changed PDFs, links them to the entries and finds the
+/// citations in the reference lists of the linked ones
+pub fn update(project: &Project) -> Result<(), Box<dyn Error>> {
+    let mut store = project.store()?;
+    let entries: Vec<_> = store.entries()?.into_iter().map(|stored| stored.entry).collect();
+    let pdfs = store.pdfs(&project.pdf_files())?;
+    for pdf in &pdfs {
+        if let Some(error) = &pdf.error {
+            eprintln!("warning: {}: {error}, it can only be linked by its name", relative(project, &pdf.path).display());
+        }
+    }
+
+    let mut links = project.links()?;
+    links.update(link::link(&entries, &pdfs, |path| project.resolve(path)));
+    project.save_links(&links)?;
+
+    // the reference lists of the linked PDFs give the citations between
+    // entries, for `litrev cites` and `litrev graph`
+    let linked: Vec<StoredPdf> = pdfs
+        .iter()
+        .filter(|pdf| links.iter().any(|link| link.path == pdf.path && link.status != LinkStatus::Rejected))
+        .cloned()
+        .collect();
+    let texts = store.texts(&linked)?;
+    let resolver = Resolver::new(&entries);
+    let mut citations = Vec::new();
+    for pdf in &linked {
+        // a PDF is the paper of its best link, not of the entries whose DOI
+        // only turns up in its reference list
+        let citing = links
+            .best_for(&pdf.path)
+            .and_then(|link| entries.iter().find(|entry| entry.citekey == link.citekey));
+        if let (Some(entry), Some(pages)) = (citing, texts.get(&pdf.hash)) {
+            citations.extend(resolver.citations(entry, pages));
+        }
+    }
+    // an entry linked to several copies of its PDF cites each entry once
+    let mut seen = HashSet::new();
+    citations.retain(|citation: &Citation| seen.insert((citation.citing.clone(), citation.cited.clone())));
+    store.set_citations(&citations)?;
+
+    let linked = entries.iter().filter(|entry| !links.of(&entry.citekey).is_empty()).count();
+    let to_review = links.to_review().count();
+    print!("Linked {linked} of {} entries to {} PDFs", entries.len(), pdfs.len());
+    if to_review > 0 {
+        print!(", {to_review} {} review, see `litrev link --review`", if to_review == 1 { "needs" } else { "need" });
+    }
+    println!();
+    if !citations.is_empty() {
+        println!("Found {} citations between entries in their reference lists, see `litrev cites`", citations.len());
+    }
+    Ok(())
+}
+
+/// A link as it appears in JSON output
+#[derive(Serialize)]
+struct LinkRecord<'a> {
+    schema_version: u32,
+    citekey: &'a str,
+    path: &'a Path,
+    signal: Signal,
+    confidence: f64,
+    status: LinkStatus,
+}
+
+/// Lists the automatic links below [`REVIEW_BELOW`], the least confident
+/// first
+fn review(project: &Project, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    if matches!(format, OutputFormat::Csv | OutputFormat::BibTeX | OutputFormat::Ris | OutputFormat::Hayagriva) {
+        return Err(UnsupportedFormat { command: "link --review", format }.into());
+    }
+    let links = project.links()?;
+    let mut to_review: Vec<&Link> = links.to_review().collect();
+    to_review.sort_by(|a, b| a.confidence.total_cmp(&b.confidence).then(a.citekey.cmp(&b.citekey)));
+    let records: Vec<LinkRecord> = to_review
+        .iter()
+        .map(|link| LinkRecord {
+            schema_version: SCHEMA_VERSION,
+            citekey: &link.citekey,
+            path: relative(project, &link.path),
+            signal: link.signal,
+            confidence: link.confidence,
+            status: link.status,
+        })
+        .collect();
+
+    with_stdout(|out| match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, &records)?;
+            writeln!(out)
+        }
+        OutputFormat::Jsonl => {
+            for record in &records {
+                serde_json::to_writer(&mut *out, record)?;
+                writeln!(out)?;
+            }
+            Ok(())
+        }
+        _ => {
+            if records.is_empty() {
+                return writeln!(out, "Every link is at least {REVIEW_BELOW} confident, nothing to review");
+            }
+            let width = records.iter().map(|record| record.citekey.chars().count()).max().unwrap_or(0);
+            for record in &records {
+                writeln!(
+                    out,
+                    "{:.2}  {:width$}  {}  (by {})",
+                    record.confidence,
+                    record.citekey,
+                    record.path.display(),
+                    record.signal,
+                )?;
+            }
+            writeln!(out, "\nKeep a link with `litrev link --confirm CITEKEY PDF`, or drop it with --reject")
+        }
+    })
+}
+
+/// Confirms or rejects the links of an entry, or only its link to a PDF
+fn decide(project: &Project, status: LinkStatus, decision: &[String]) -> Result<(), Box<dyn Error>> {
+    let citekey = &decision[0];
+    lookup(project, citekey)?;
+    let path = match decision.get(1) {
+        Some(path) => Some(absolute(Path::new(path))?),
+        None => None,
+    };
+
+    let mut links = project.links()?;
+    let changed = match status {
+        LinkStatus::Rejected => links.reject(citekey, path.as_deref())?,
+        _ => links.confirm(citekey, path.as_deref())?,
+    };
+    project.save_links(&links)?;
+    let verb = if status == LinkStatus::Rejected { "Rejected" } else { "Confirmed" };
+    println!("{verb} {changed} {} of {citekey}", if changed == 1 { "link" } else { "links" });
+    Ok(())
+}
+
+/// A PDF given on the command line as the path the linker would have found
+/// it under
+fn absolute(path: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let path = env::current_dir()?.join(path);
+    if !path.is_file() {
+        return Err(format!("{}: no such PDF", path.display()).into());
+    }
+    Ok(path.canonicalize()?)
+}
+
+fn relative<'a>(project: &Project, path: &'a Path) -> &'a Path {
+    path.strip_prefix(project.root()).unwrap_or(path)
+}
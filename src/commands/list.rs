@@ -0,0 +1,38 @@
+use std::error::Error;
+
+use clap::Args;
+use clap_complete::engine::ArgValueCompleter;
+
+use litrev::bibtex::entry::BibTeXEntry;
+use litrev::output::{self, OutputFormat};
+
+use super::{complete, current_project, search, with_stdout};
+
+#[derive(Args)]
+pub struct ListArgs {
+    /// Only lists entries with this tag
+    #[arg(long, add = ArgValueCompleter::new(complete::tags))]
+    tag: Option<String>,
+    /// Only lists entries of this kind, e.g. article
+    #[arg(long)]
+    kind: Option<String>,
+    /// Only lists the entries a saved search finds, see `search --save`
+    #[arg(long, value_name = "NAME", add = ArgValueCompleter::new(complete::saved_searches))]
+    group: Option<String>,
+}
+
+pub fn run(args: ListArgs, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let project = current_project()?;
+    let entries = match &args.group {
+        Some(name) => search::group(&project, name)?,
+        None => project.entries()?,
+    };
+    let tags = project.tags()?;
+
+    let listed: Vec<&BibTeXEntry> = entries
+        .iter()
+        .filter(|entry| args.tag.as_ref().is_none_or(|tag| tags.has(&entry.citekey, tag)))
+        .filter(|entry| args.kind.as_ref().is_none_or(|kind| entry.kind.to_string().eq_ignore_ascii_case(kind)))
+        .collect();
+    with_stdout(|out| output::write_entries(out, &listed, &tags, format))
+}
@@ -0,0 +1,142 @@
+//! The subcommands of the `litrev` binary, one module each
+
+pub mod add;
+pub mod annotations;
+pub mod cite;
+pub mod cites;
+pub mod complete;
+pub mod export;
+pub mod files;
+pub mod find_refs;
+pub mod graph;
+pub mod import;
+pub mod index;
+pub mod init;
+pub mod insights;
+pub mod link;
+pub mod list;
+pub mod note;
+pub mod open;
+pub mod search;
+pub mod show;
+pub mod tag;
+pub mod watch;
+
+use std::env;
+use std::error::Error;
+use std::io::{self, Write};
+
+use litrev::bibtex::entry::BibTeXEntry;
+use litrev::link::Links;
+use litrev::output::OutputFormat;
+use litrev::project::Project;
+use litrev::search::fuzzy;
+use litrev::store::{StoredPdf, Texts};
+
+/// The most citekeys suggested for one that is not in the library
+const MAX_SUGGESTIONS: usize = 3;
+
+/// A citekey that is not in the library, which exits with its own code
+#[derive(Debug)]
+pub struct UnknownCitekey {
+    pub citekey: String, 
+    /// The citekeys in the library it may have been a typo of
+    pub suggestions: Vec<String>, 
+}
+
+impl UnknownCitekey {
+    /// The error for a citekey, suggesting the closest of the known
+    /// citekeys
+    pub fn new<'a>(citekey: &str, known: impl IntoIterator<Item = &'a str>) -> Self {
+        let suggestions = fuzzy::suggestions(citekey, known, MAX_SUGGESTIONS);
+        Self { citekey: citekey.to_string(), suggestions: suggestions.into_iter().map(String::from).collect() }
+    }
+}
+
+impl std::fmt::Display for UnknownCitekey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no entry with citekey '{}'", self.citekey)?;
+        let quoted: Vec<String> = self.suggestions.iter().map(|citekey| format!("'{citekey}'")).collect();
+        match quoted.split_last() {
+            None => Ok(()),
+            Some((last, [])) => write!(f, ", did you mean {last}?"),
+            Some((last, rest)) => write!(f, ", did you mean {} or {last}?", rest.join(", ")),
+        }
+    }
+}
+
+impl Error for UnknownCitekey {}
+
+/// A `--format` the command cannot write, e.g. insights as BibTeX
+#[derive(Debug)]
+pub struct UnsupportedFormat {
+    pub command: &'static str, 
+    pub format: OutputFormat, 
+}
+
+impl std::fmt::Display for UnsupportedFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} cannot be written as {}", self.command, self.format)
+    }
+}
+
+impl Error for UnsupportedFormat {}
+
+/// Arguments that do not go together, which exits like those clap rejects
+#[derive(Debug)]
+pub struct UsageError(pub &'static str);
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for UsageError {}
+
+/// The project enclosing the current directory, or else the global library
+pub fn current_project() -> Result<Project, Box<dyn Error>> {
+    Ok(Project::discover(&env::current_dir()?)?)
+}
+
+/// Looks an entry up in the project's index
+pub fn lookup(project: &Project, citekey: &str) -> Result<BibTeXEntry, Box<dyn Error>> {
+    let store = project.store()?;
+    match store.entry(citekey)? {
+        Some(stored) => Ok(stored.entry),
+        None => {
+            let citekeys = store.citekeys()?;
+            Err(UnknownCitekey::new(citekey, citekeys.iter().map(|(citekey, _)| citekey.as_str())).into())
+        }
+    }
+}
+
+pub fn find_entry<'a>(entries: &'a [BibTeXEntry], citekey: &str) -> Result<&'a BibTeXEntry, UnknownCitekey> {
+    entries
+        .iter()
+        .find(|entry| entry.citekey == citekey)
+        .ok_or_else(|| UnknownCitekey::new(citekey, entries.iter().map(|entry| entry.citekey.as_str())))
+}
+
+/// Every local PDF, those in the PDF directories and any linked from
+/// elsewhere, along with the text of each by the hash of its contents. PDFs
+/// whose text is not cached yet are read first
+pub fn local_pdfs(project: &Project, links: &Links) -> Result<(Vec<StoredPdf>, Texts), Box<dyn Error>> {
+    let mut paths = project.pdf_files();
+    paths.extend(links.iter().map(|link| link.path.clone()).filter(|path| path.is_file()));
+    paths.sort();
+    paths.dedup();
+
+    let mut store = project.store()?;
+    let pdfs = store.pdfs(&paths)?;
+    let texts = store.texts(&pdfs)?;
+    Ok((pdfs, texts))
+}
+
+/// Runs `write` against a locked stdout, flushing at the end
+pub fn with_stdout(write: impl FnOnce(&mut io::StdoutLock) -> io::Result<()>) -> Result<(), Box<dyn Error>> {
+    let mut out = io::stdout().lock();
+    write(&mut out)?;
+    out.flush()?;
+    Ok(())
+}
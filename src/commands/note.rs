@@ -0,0 +1,73 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fs};
+
+use clap::Args;
+use clap_complete::engine::ArgValueCompleter;
+
+use litrev::bibtex::entry::BibTeXEntry;
+use litrev::notes::template;
+use litrev::project::Project;
+
+use super::{complete, current_project, lookup};
+
+#[derive(Args)]
+pub struct NoteArgs {
+    /// The citekey of the entry to take notes on
+    #[arg(add = ArgValueCompleter::new(complete::citekeys))]
+    citekey: String,
+    /// Prints the path of the note instead of opening it
+    #[arg(long)]
+    path: bool,
+}
+
+pub fn run(args: NoteArgs) -> Result<(), Box<dyn Error>> {
+    let project = current_project()?;
+    let entry = lookup(&project, &args.citekey)?;
+
+    let path = create(&project, &entry)?;
+    if args.path {
+        println!("{}", path.display());
+        return Ok(());
+    }
+    edit(&path)
+}
+
+/// The path of the note on an entry, making the note from the project's
+/// note template if there is none yet
+pub fn create(project: &Project, entry: &BibTeXEntry) -> Result<PathBuf, Box<dyn Error>> {
+    let path = project.notes_dir().join(format!("{}.md", entry.citekey));
+    if !path.exists() {
+        let template = match &project.config().note_template {
+            Some(template) => {
+                let template = project.resolve(template);
+                fs::read_to_string(&template).map_err(|e| format!("{}: {e}", template.display()))?
+            }
+            None => template::DEFAULT.to_string(),
+        };
+        let note = template::render(&template, entry)?;
+        fs::create_dir_all(project.notes_dir())?;
+        fs::write(&path, note)?;
+    }
+    Ok(path)
+}
+
+/// Opens a file in `$VISUAL` or `$EDITOR`, waiting for the editor to exit
+pub fn edit(path: &Path) -> Result<(), Box<dyn Error>> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| String::from("vi"));
+    // the editor may come with arguments of its own, e.g. `code --wait`
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or("$EDITOR is empty")?;
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|e| format!("could not run {program}: {e}"))?;
+    if !status.success() {
+        return Err(format!("{program} exited with {status}").into());
+    }
+    Ok(())
+}
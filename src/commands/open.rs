@@ -0,0 +1,123 @@
+use std::error::Error;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use clap_complete::engine::ArgValueCompleter;
+
+use litrev::bibtex::entry::BibTeXEntry;
+use litrev::link::file_field_paths;
+use litrev::project::Project;
+
+use super::{complete, current_project, lookup};
+
+#[derive(Args)]
+pub struct OpenArgs {
+    /// The citekey of the entry to open
+    #[arg(add = ArgValueCompleter::new(complete::citekeys))]
+    citekey: String,
+    /// Prints the path of the PDF, or the URL, instead of opening it. Every
+    /// PDF is printed when several are linked, the most likely first
+    #[arg(long)]
+    print: bool,
+}
+
+/// Opens the entry's PDF, or else its DOI or URL in the browser
+pub fn run(args: OpenArgs) -> Result<(), Box<dyn Error>> {
+    let project = current_project()?;
+    let entry = lookup(&project, &args.citekey)?;
+
+    let pdfs = find_pdfs(&project, &entry)?;
+    if !pdfs.is_empty() {
+        if args.print {
+            for pdf in &pdfs {
+                println!("{}", pdf.display());
+            }
+            return Ok(());
+        }
+        let pdf = pick(&project, &pdfs)?;
+        opener::open(pdf).map_err(|e| format!("{}: {e}", pdf.display()))?;
+        return Ok(());
+    }
+
+    let url = entry
+        .doi()
+        .map(|doi| doi_url(doi.trim()))
+        .or_else(|| entry.non_standard_field("url").map(|url| url.trim().to_string()))
+        .ok_or_else(|| format!("{} has no PDF, DOI or URL to open", entry.citekey))?;
+    if args.print {
+        println!("{url}");
+        return Ok(());
+    }
+    opener::open_browser(&url).map_err(|e| format!("{url}: {e}"))?;
+    Ok(())
+}
+
+/// The PDFs linked to the entry by `litrev link`, the most likely first. If
+/// none are, the entry's `file` field or `<citekey>.pdf` in one of the
+/// project's PDF directories, which need no linking
+pub fn find_pdfs(project: &Project, entry: &BibTeXEntry) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let links = project.links()?;
+    let linked: Vec<PathBuf> = links
+        .of(&entry.citekey)
+        .into_iter()
+        .map(|link| link.path.clone())
+        .filter(|path| path.is_file())
+        .collect();
+    if !linked.is_empty() {
+        return Ok(linked);
+    }
+
+    let in_field = entry
+        .non_standard_field("file")
+        .into_iter()
+        .flat_map(|value| file_field_paths(value))
+        .map(|path| project.resolve(&path));
+    let named = project
+        .pdf_dirs()
+        .into_iter()
+        .map(|dir| dir.join(format!("{}.pdf", entry.citekey)));
+    let mut pdfs: Vec<PathBuf> = Vec::new();
+    for path in in_field.chain(named).filter(|path| path.is_file()) {
+        if !pdfs.contains(&path) {
+            pdfs.push(path);
+        }
+    }
+    Ok(pdfs)
+}
+
+/// Asks which PDF to open when there are several and someone is there to
+/// answer, or else takes the most likely
+fn pick<'a>(project: &Project, pdfs: &'a [PathBuf]) -> Result<&'a Path, Box<dyn Error>> {
+    if pdfs.len() == 1 || !io::stdin().is_terminal() {
+        return Ok(&pdfs[0]);
+    }
+    let mut err = io::stderr().lock();
+    for (i, pdf) in pdfs.iter().enumerate() {
+        writeln!(err, "{}) {}", i + 1, pdf.strip_prefix(project.root()).unwrap_or(pdf).display())?;
+    }
+    write!(err, "Open which PDF? [1] ")?;
+    err.flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    let answer = answer.trim();
+    if answer.is_empty() {
+        return Ok(&pdfs[0]);
+    }
+    answer
+        .parse::<usize>()
+        .ok()
+        .and_then(|choice| pdfs.get(choice.checked_sub(1)?))
+        .map(PathBuf::as_path)
+        .ok_or_else(|| format!("expected a number from 1 to {}, but found {answer}", pdfs.len()).into())
+}
+
+/// The URL a DOI resolves at, which the `doi` field may already be
+fn doi_url(doi: &str) -> String {
+    if doi.starts_with("http://") || doi.starts_with("https://") {
+        return doi.to_string();
+    }
+    let doi = doi.strip_prefix("doi:").unwrap_or(doi);
+    format!("https://doi.org/{doi}")
+}
@@ -0,0 +1,414 @@
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::io::{self, IsTerminal, Write};
+use std::ops::Range;
+
+use clap::Args;
+use clap_complete::engine::ArgValueCompleter;
+use serde::Serialize;
+
+use litrev::bibtex::entry::BibTeXEntry;
+use litrev::output::{self, OutputFormat, SCHEMA_VERSION};
+use litrev::project::{Project, SavedSearch};
+use litrev::search::fulltext::{self, Snippet};
+use litrev::search::rank::{self, Part, Score};
+use litrev::search::pattern::{FieldMatch, FieldPattern};
+use litrev::search::{self, fuzzy, FullText, Query, QueryParser, TextPage};
+use litrev::store::Notes;
+use litrev::style::case::strip_braces;
+
+use super::{complete, current_project, local_pdfs, with_stdout, UnsupportedFormat};
+
+#[derive(Args)]
+#[command(after_help = QUERY_HELP)]
+pub struct SearchArgs {
+    /// The query, see below. Words must all appear in the title, authors,
+    /// abstract or keywords
+    #[arg(required_unless_present_any = ["author", "year", "patterns", "saved", "forget"])]
+    query: Vec<String>,
+    /// Only matches entries with an author whose name contains this, the same
+    /// as `author:"..."`
+    #[arg(long)]
+    author: Option<String>,
+    /// Only matches entries from this year or range of years, e.g. 2015..2020
+    #[arg(long)]
+    year: Option<String>,
+    /// Only matches entries with a field matching a regular expression, e.g.
+    /// 'note=arXiv:\d{4}\.\d{5}' or 'title=^On the'. Any field can be
+    /// searched, and the matches are highlighted
+    #[arg(long = "regex", value_name = "FIELD=PATTERN")]
+    patterns: Vec<FieldPattern>,
+    /// Tolerates typos in every word, as if each were followed by `~`
+    #[arg(long)]
+    fuzzy: bool,
+    /// Shows how relevant each result is and which of its words made it so,
+    /// instead of the results themselves
+    #[arg(long)]
+    explain: bool,
+    /// Saves the search under a name, to run again with `--saved NAME` or
+    /// to use as a group with `list --group` and `export --group`
+    #[arg(long, value_name = "NAME", conflicts_with = "saved")]
+    save: Option<String>,
+    /// Runs a saved search, or lists them all if no name is given
+    #[arg(
+        long,
+        value_name = "NAME",
+        num_args = 0..=1,
+        conflicts_with_all = ["query", "author", "year", "patterns", "fuzzy"],
+        add = ArgValueCompleter::new(complete::saved_searches),
+    )]
+    saved: Option<Option<String>>,
+    /// Deletes a saved search
+    #[arg(long, value_name = "NAME", exclusive = true, add = ArgValueCompleter::new(complete::saved_searches))]
+    forget: Option<String>,
+}
+
+const QUERY_HELP: &str = r#"Queries:
+  adjoint "shape optimisation"   words and phrases, in the title, authors, abstract or keywords
+  author:kenway title:"adjoint method"
+                                 words and phrases in a field, e.g. author, editor, title,
+                                 abstract, keywords, journal, booktitle, note, doi or citekey
+  year:2019 year:2015..2020 year:2015..
+  fulltext:"lyapunov exponent"   words and phrases in the text of the entry's linked PDFs,
+                                 shown with the pages they are on
+  note:"shock boundary layer"    words and phrases in the entry's note field or its notes,
+                                 see `litrev note`
+  kind:article tag:cfd
+  cfd AND (rans OR les) NOT kind:book
+  author:kenwey~ "ajoint method"~
+                                 words and phrases with a few typos in them, which any
+                                 query tolerates when nothing matches it exactly
+
+Case and accents are ignored, so `muller` finds M{\"u}ller. Results are ordered by
+relevance, with words in the title counting for more than those in the keywords,
+authors or abstract"#;
+
+pub fn run(args: SearchArgs, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let project = current_project()?;
+    if let Some(name) = &args.forget {
+        let mut searches = project.saved_searches()?;
+        searches.remove(name).ok_or_else(|| unknown_search(&project, name))?;
+        project.save_searches(&searches)?;
+        println!("Deleted the saved search '{name}'");
+        return Ok(());
+    }
+    let saved = match &args.saved {
+        Some(None) => return list_saved(&project, format),
+        Some(Some(name)) => Some(saved_search(&project, name)?),
+        None => None,
+    };
+    if args.explain && !matches!(format, OutputFormat::Table | OutputFormat::Json | OutputFormat::Jsonl) {
+        return Err(UnsupportedFormat { command: "search --explain", format }.into());
+    }
+
+    let search = saved.unwrap_or_else(|| to_saved(&args));
+    let (query, patterns) = compile(&search)?;
+    if let Some(name) = &args.save {
+        let mut searches = project.saved_searches()?;
+        searches.insert(name, search)?;
+        project.save_searches(&searches)?;
+        eprintln!("Saved the search as '{name}'");
+    }
+
+    let mut store = project.store()?;
+    let entries: Vec<BibTeXEntry> = store.entries()?.into_iter().map(|stored| stored.entry).collect();
+    let tags = project.tags()?;
+    let full_text = match &query {
+        Some(query) if fulltext::uses_full_text(query) => full_text(&project, &entries)?,
+        _ => FullText::new(),
+    };
+    let notes = match &query {
+        Some(query) if search::uses_notes(query) => store.notes(&project.notes_dir())?,
+        _ => Notes::new(),
+    };
+
+    let mut found: Vec<&BibTeXEntry> = match &query {
+        Some(query) => search::search(&entries, &tags, &full_text, &notes, query),
+        None => entries.iter().collect(),
+    };
+    // with no exact matches the search tolerated typos, and so does the
+    // ranking then
+    let query = match query {
+        Some(query) if found.first().is_some_and(|&entry| !search::is_match(entry, &tags, &full_text, &notes, &query)) => {
+            eprintln!("Nothing matches exactly, showing the entries a few typos away");
+            Some(query.into_fuzzy())
+        }
+        query => query,
+    };
+    found.retain(|entry| patterns.iter().all(|pattern| pattern.is_match(entry)));
+    let scores = match &query {
+        Some(query) if rank::is_ranked(query) => {
+            let scores = rank::rank(&store, query)?;
+            // the sort is stable, so equally relevant entries stay in
+            // library order
+            found.sort_by(|a, b| total(&scores, b).total_cmp(&total(&scores, a)));
+            scores
+        }
+        _ => HashMap::new(),
+    };
+
+    if args.explain {
+        return with_stdout(|out| write_explanation(out, &found, &scores, format));
+    }
+    if (!patterns.is_empty() || !full_text.is_empty()) && format == OutputFormat::Table {
+        let highlight = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+        let snippets: HashMap<&str, Vec<Snippet>> = match &query {
+            Some(query) => found
+                .iter()
+                .filter_map(|entry| Some((entry.citekey.as_str(), fulltext::snippets(full_text.get(&entry.citekey)?, query))))
+                .collect(),
+            None => HashMap::new(),
+        };
+        return with_stdout(|out| write_matches(out, &found, &patterns, &snippets, highlight));
+    }
+    with_stdout(|out| output::write_entries(out, &found, &tags, format))
+}
+
+/// Writes each entry with the values its patterns matched and the snippets
+/// of its PDFs' text, like grep
+fn write_matches(
+    out: &mut impl Write,
+    found: &[&BibTeXEntry],
+    patterns: &[FieldPattern],
+    snippets: &HashMap<&str, Vec<Snippet>>,
+    highlight: bool,
+) -> io::Result<()> {
+    for entry in found {
+        writeln!(out, "{} ({})", entry.citekey, entry.kind)?;
+        let matches: Vec<FieldMatch> = patterns.iter().flat_map(|pattern| pattern.find(entry)).collect();
+        let width = matches.iter().map(|found| found.field.len()).max().unwrap_or(0);
+        for found in &matches {
+            writeln!(out, "  {:width$}  {}", found.field, highlighted(&found.value, &found.spans, highlight))?;
+        }
+        let snippets = snippets.get(entry.citekey.as_str()).map_or(&[][..], Vec::as_slice);
+        for snippet in snippets.iter().take(SNIPPETS) {
+            let file = snippet.path.file_name().unwrap_or_default().to_string_lossy();
+            writeln!(out, "  {file} p. {}  {}", snippet.page, highlighted(&snippet.text, &snippet.spans, highlight))?;
+        }
+        if snippets.len() > SNIPPETS {
+            writeln!(out, "  and {} more pages", snippets.len() - SNIPPETS)?;
+        }
+    }
+    Ok(())
+}
+
+/// How many snippets of its PDFs' text are shown for each entry
+const SNIPPETS: usize = 3;
+
+/// The value with its matching spans in bold red, as grep shows them
+fn highlighted(value: &str, spans: &[Range<usize>], highlight: bool) -> String {
+    if !highlight {
+        return value.to_string();
+    }
+    let mut highlighted = String::new();
+    let mut end = 0;
+    for span in spans {
+        highlighted.push_str(&value[end..span.start]);
+        highlighted.push_str(&format!("\x1b[1;31m{}\x1b[0m", &value[span.clone()]));
+        end = span.end;
+    }
+    highlighted.push_str(&value[end..]);
+    highlighted
+}
+
+/// The text of the PDFs linked to each entry, reading any PDF whose text is
+/// not cached yet
+pub fn full_text(project: &Project, entries: &[BibTeXEntry]) -> Result<FullText, Box<dyn Error>> {
+    let links = project.links()?;
+    let (pdfs, texts) = local_pdfs(project, &links)?;
+    let mut full_text = FullText::new();
+    for entry in entries {
+        let pages: Vec<TextPage> = links
+            .of(&entry.citekey)
+            .into_iter()
+            .filter_map(|link| pdfs.iter().find(|pdf| pdf.path == link.path))
+            .filter_map(|pdf| Some((&pdf.path, texts.get(&pdf.hash)?)))
+            .flat_map(|(path, pages)| {
+                pages.iter().map(|page| TextPage { path: path.clone(), number: page.number, text: page.text.clone() })
+            })
+            .collect();
+        if !pages.is_empty() {
+            full_text.insert(entry.citekey.clone(), pages);
+        }
+    }
+    Ok(full_text)
+}
+
+fn total(scores: &HashMap<String, Score>, entry: &BibTeXEntry) -> f64 {
+    scores.get(&entry.citekey).map_or(0.0, |score| score.total)
+}
+
+/// The score of a result as it appears in JSON output
+#[derive(Serialize)]
+struct ExplanationRecord<'a> {
+    schema_version: u32,
+    citekey: &'a str,
+    score: f64,
+    parts: &'a [Part],
+}
+
+fn write_explanation(
+    out: &mut impl Write,
+    found: &[&BibTeXEntry],
+    scores: &HashMap<String, Score>,
+    format: OutputFormat,
+) -> io::Result<()> {
+    let no_score = Score::default();
+    let records: Vec<ExplanationRecord> = found
+        .iter()
+        .map(|entry| {
+            let score = scores.get(&entry.citekey).unwrap_or(&no_score);
+            ExplanationRecord { schema_version: SCHEMA_VERSION, citekey: &entry.citekey, score: score.total, parts: &score.parts }
+        })
+        .collect();
+
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, &records)?;
+            writeln!(out)
+        }
+        OutputFormat::Jsonl => {
+            for record in &records {
+                serde_json::to_writer(&mut *out, record)?;
+                writeln!(out)?;
+            }
+            Ok(())
+        }
+        _ => {
+            for (entry, record) in found.iter().zip(&records) {
+                let title = entry.title().map_or_else(String::new, |title| strip_braces(title));
+                writeln!(out, "{}  {:.3}  {title}", record.citekey, record.score)?;
+                for part in record.parts {
+                    writeln!(
+                        out,
+                        "  {:.3}  {} in {}, {} of {} words",
+                        part.score, part.term, part.field, part.frequency, part.length,
+                    )?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// The search given on the command line, with the `--author` and `--year`
+/// filters written into the query
+fn to_saved(args: &SearchArgs) -> SavedSearch {
+    let mut parts = Vec::new();
+    if !args.query.is_empty() {
+        parts.push(args.query.join(" "));
+    }
+    if let Some(author) = &args.author {
+        parts.push(format!("author:\"{}\"", author.replace('"', "")));
+    }
+    if let Some(year) = &args.year {
+        parts.push(format!("year:{year}"));
+    }
+    // each part is bracketed so that an OR in the query stays within it
+    let query = match parts.as_slice() {
+        [one] => one.clone(),
+        parts => parts.iter().map(|part| format!("({part})")).collect::<Vec<_>>().join(" "),
+    };
+    SavedSearch {
+        query,
+        regex: args.patterns.iter().map(FieldPattern::to_string).collect(),
+        fuzzy: args.fuzzy,
+    }
+}
+
+/// The query of a search, if it has more to search by than patterns, and
+/// its patterns
+fn compile(search: &SavedSearch) -> Result<(Option<Query>, Vec<FieldPattern>), Box<dyn Error>> {
+    let query = if search.query.trim().is_empty() {
+        None
+    } else {
+        let query = QueryParser::new(&search.query).parse()?;
+        Some(if search.fuzzy { query.into_fuzzy() } else { query })
+    };
+    let patterns = search.regex.iter().map(|pattern| pattern.parse()).collect::<Result<_, _>>()?;
+    Ok((query, patterns))
+}
+
+fn saved_search(project: &Project, name: &str) -> Result<SavedSearch, Box<dyn Error>> {
+    match project.saved_searches()?.get(name) {
+        Some(search) => Ok(search.clone()),
+        None => Err(unknown_search(project, name)),
+    }
+}
+
+fn unknown_search(project: &Project, name: &str) -> Box<dyn Error> {
+    let searches = project.saved_searches().unwrap_or_default();
+    match fuzzy::suggestions(name, searches.iter().map(|(name, _)| name), 1).first() {
+        Some(suggestion) => format!("no saved search named '{name}', did you mean '{suggestion}'?").into(),
+        None => format!("no saved search named '{name}'").into(),
+    }
+}
+
+/// The entries a saved search finds now, in library order, for using it as
+/// a group
+pub fn group(project: &Project, name: &str) -> Result<Vec<BibTeXEntry>, Box<dyn Error>> {
+    let (query, patterns) = compile(&saved_search(project, name)?)?;
+    let tags = project.tags()?;
+    let entries = project.entries()?;
+    let full_text = match &query {
+        Some(query) if fulltext::uses_full_text(query) => full_text(project, &entries)?,
+        _ => FullText::new(),
+    };
+    let notes = match &query {
+        Some(query) if search::uses_notes(query) => project.store()?.notes(&project.notes_dir())?,
+        _ => Notes::new(),
+    };
+    Ok(entries
+        .into_iter()
+        .filter(|entry| query.as_ref().is_none_or(|query| search::is_match(entry, &tags, &full_text, &notes, query)))
+        .filter(|entry| patterns.iter().all(|pattern| pattern.is_match(entry)))
+        .collect())
+}
+
+/// A saved search as it appears in JSON output
+#[derive(Serialize)]
+struct SavedRecord<'a> {
+    schema_version: u32,
+    name: &'a str,
+    #[serde(flatten)]
+    search: &'a SavedSearch,
+    /// How many entries the search finds now
+    entries: usize,
+}
+
+fn list_saved(project: &Project, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let searches = project.saved_searches()?;
+    let mut records = Vec::new();
+    for (name, search) in searches.iter() {
+        let entries = group(project, name)?.len();
+        records.push(SavedRecord { schema_version: SCHEMA_VERSION, name, search, entries });
+    }
+
+    with_stdout(|out| match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, &records)?;
+            writeln!(out)
+        }
+        OutputFormat::Jsonl => {
+            for record in &records {
+                serde_json::to_writer(&mut *out, record)?;
+                writeln!(out)?;
+            }
+            Ok(())
+        }
+        _ => {
+            let width = records.iter().map(|record| record.name.chars().count()).max().unwrap_or(0);
+            for record in &records {
+                let mut search = record.search.regex.iter().map(|pattern| format!("--regex '{pattern}'")).collect::<Vec<_>>();
+                search.insert(0, record.search.query.clone());
+                if record.search.fuzzy {
+                    search.push(String::from("--fuzzy"));
+                }
+                let count = format!("({} {})", record.entries, if record.entries == 1 { "entry" } else { "entries" });
+                writeln!(out, "{:width$}  {}  {count}", record.name, search.join(" ").trim())?;
+            }
+            Ok(())
+        }
+    })
+}
@@ -0,0 +1,22 @@
+use std::error::Error;
+
+use clap::Args;
+use clap_complete::engine::ArgValueCompleter;
+
+use litrev::output::{self, OutputFormat};
+
+use super::{complete, current_project, lookup, with_stdout};
+
+#[derive(Args)]
+pub struct ShowArgs {
+    /// The citekey of the entry to show
+    #[arg(add = ArgValueCompleter::new(complete::citekeys))]
+    citekey: String,
+}
+
+pub fn run(args: ShowArgs, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let project = current_project()?;
+    let entry = lookup(&project, &args.citekey)?;
+    let tags = project.tags()?;
+    with_stdout(|out| output::write_entry(out, &entry, &tags, format))
+}
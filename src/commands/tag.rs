@@ -0,0 +1,47 @@
+use std::error::Error;
+
+use clap::Args;
+use clap_complete::engine::ArgValueCompleter;
+
+use super::{complete, current_project, lookup, UsageError};
+
+#[derive(Args)]
+pub struct TagArgs {
+    /// The citekey of the entry to tag, lists every tag in use if omitted
+    #[arg(add = ArgValueCompleter::new(complete::citekeys))]
+    citekey: Option<String>,
+    /// Tags to add, separated by commas
+    #[arg(long, value_delimiter = ',', add = ArgValueCompleter::new(complete::tags))]
+    add: Vec<String>,
+    /// Tags to remove, separated by commas
+    #[arg(long, value_delimiter = ',', add = ArgValueCompleter::new(complete::tags))]
+    remove: Vec<String>,
+}
+
+pub fn run(args: TagArgs) -> Result<(), Box<dyn Error>> {
+    let project = current_project()?;
+    let mut tags = project.tags()?;
+
+    let Some(citekey) = args.citekey else {
+        if !args.add.is_empty() || !args.remove.is_empty() {
+            return Err(UsageError("a citekey is needed to add or remove tags").into());
+        }
+        for (tag, count) in tags.counts() {
+            println!("{tag} ({count})");
+        }
+        return Ok(());
+    };
+
+    lookup(&project, &citekey)?;
+    for tag in &args.add {
+        tags.add(&citekey, tag);
+    }
+    for tag in &args.remove {
+        tags.remove(&citekey, tag);
+    }
+    if !args.add.is_empty() || !args.remove.is_empty() {
+        project.save_tags(&tags)?;
+    }
+    println!("{citekey}: {}", tags.of(&citekey).join(", "));
+    Ok(())
+}
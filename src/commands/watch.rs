@@ -0,0 +1,160 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
+
+use clap::Args;
+use notify_debouncer_mini::notify::{RecursiveMode, Watcher};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult};
+
+use litrev::project::{self, Project};
+
+use super::index::report_diagnostics;
+use super::{current_project, link};
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// How long to wait for a burst of saves to settle before re-indexing,
+    /// in milliseconds
+    #[arg(long, default_value_t = 500)]
+    debounce: u64,
+}
+
+enum Message {
+    Changed(DebounceEventResult),
+    Interrupted,
+}
+
+/// Re-indexes the project's bibliographies whenever they change, and links
+/// the PDFs added to or changed in its PDF directories, until interrupted
+pub fn run(args: WatchArgs) -> Result<(), Box<dyn Error>> {
+    let mut project = current_project()?;
+    let (sender, receiver) = mpsc::channel();
+    let interrupt = sender.clone();
+    // the handler only asks the loop to stop, so an update in progress is
+    // always finished before exiting
+    ctrlc::set_handler(move || {
+        let _ = interrupt.send(Message::Interrupted);
+    })?;
+
+    update(&project);
+    loop {
+        let sender = sender.clone();
+        let mut debouncer = new_debouncer(Duration::from_millis(args.debounce), move |result| {
+            let _ = sender.send(Message::Changed(result));
+        })?;
+        watch(&project, debouncer.watcher())?;
+        println!(
+            "Watching {} bibliographies and {} PDF directories, press Ctrl-C to stop",
+            project.bib_files().len(),
+            project.pdf_dirs().len(),
+        );
+
+        let config = project.dir().join(project::CONFIG_FILE);
+        let bibs = project.bib_files();
+        let pdf_dirs = project.pdf_dirs();
+        // reading a PDF to link it is an event too, so a PDF only counts as
+        // changed when its modification time does
+        let mut modified: HashMap<PathBuf, Option<SystemTime>> = project
+            .pdf_files()
+            .into_iter()
+            .map(|path| (path.clone(), modified_time(&path)))
+            .collect();
+        loop {
+            let events = match receiver.recv()? {
+                Message::Interrupted => {
+                    println!("Stopped watching");
+                    return Ok(());
+                }
+                Message::Changed(Err(e)) => {
+                    eprintln!("error: {e}");
+                    continue;
+                }
+                Message::Changed(Ok(events)) => events,
+            };
+            let changed: HashSet<PathBuf> = events.into_iter().map(|event| event.path).collect();
+
+            if changed.contains(&config) {
+                println!("The config changed, reloading the project");
+                break;
+            }
+            if changed.iter().any(|path| bibs.contains(path)) {
+                update(&project);
+            }
+            let pdfs: Vec<&PathBuf> = changed
+                .iter()
+                .filter(|path| is_pdf(path) && pdf_dirs.iter().any(|dir| path.starts_with(dir)))
+                .filter(|path| {
+                    let time = modified_time(path);
+                    modified.insert(path.to_path_buf(), time) != Some(time)
+                })
+                .collect();
+            for path in &pdfs {
+                let change = if path.exists() { "changed" } else { "removed" };
+                println!("{} {change}", relative(&project, path).display());
+            }
+            if !pdfs.is_empty() {
+                if let Err(e) = link::update(&project) {
+                    eprintln!("error: {e}");
+                }
+            }
+        }
+
+        drop(debouncer);
+        project = Project::open(project.root())?;
+        update(&project);
+    }
+}
+
+/// Watches the directories holding the bibliographies, so that editors which
+/// save by replacing the file are still noticed, along with the PDF
+/// directories and the config
+fn watch(project: &Project, watcher: &mut dyn Watcher) -> Result<(), Box<dyn Error>> {
+    let mut dirs: Vec<PathBuf> = project
+        .bib_files()
+        .iter()
+        .filter_map(|bib| bib.parent().map(Path::to_path_buf))
+        .collect();
+    dirs.push(project.dir());
+    dirs.sort();
+    dirs.dedup();
+    for dir in dirs {
+        watcher.watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("{}: {e}", dir.display()))?;
+    }
+
+    for dir in project.pdf_dirs() {
+        if let Err(e) = watcher.watch(&dir, RecursiveMode::Recursive) {
+            eprintln!("warning: not watching {}: {e}", dir.display());
+        }
+    }
+    Ok(())
+}
+
+/// Brings the index up to date, logging what changed and any entries that
+/// failed to parse
+fn update(project: &Project) {
+    match project.update_store() {
+        Ok(summary) => {
+            report_diagnostics(project, &summary);
+            if !summary.is_empty() {
+                println!("Re-indexed {} files: {summary}", summary.files);
+            }
+        }
+        Err(e) => eprintln!("error: {e}"),
+    }
+}
+
+/// When a file was last modified, or `None` if it is gone
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|metadata| metadata.modified()).ok()
+}
+
+fn is_pdf(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"))
+}
+
+fn relative<'a>(project: &Project, path: &'a Path) -> &'a Path {
+    path.strip_prefix(project.root()).unwrap_or(path)
+}
@@ -0,0 +1,166 @@
+//! Finding copies of the same PDF. Files with the same contents are copies,
+//! and so are files whose text is near enough the same, e.g. a paper
+//! downloaded twice with a different cover page or watermark
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use crate::search::text;
+use crate::store::{StoredPdf, Texts};
+
+/// How many words in a row make up each piece two texts are compared by
+const SHINGLE: usize = 5;
+
+/// The share of pieces two texts must have in common to be copies
+pub const NEAR_IDENTICAL: f64 = 0.9;
+
+/// Texts shorter than this are too short to tell apart, e.g. a scan with
+/// only a cover page of text
+const MIN_WORDS: usize = 50;
+
+/// Copies of the same PDF
+#[derive(Debug, PartialEq)]
+pub struct Duplicates<'a> {
+    /// The copies, grouped by their contents
+    pub copies: Vec<Vec<&'a StoredPdf>>,
+    /// How alike the text of the copies is, from 0 to 1. Copies with the
+    /// same contents are 1 alike
+    pub similarity: f64,
+}
+
+/// The groups of copies among some PDFs, by the path of their first copy.
+/// `texts` has the text of each PDF by hash, see [`crate::store::Store::texts`]
+pub fn duplicates<'a>(pdfs: &'a [StoredPdf], texts: &Texts) -> Vec<Duplicates<'a>> {
+    let mut by_hash: Vec<Vec<&StoredPdf>> = Vec::new();
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    for pdf in pdfs {
+        match positions.get(pdf.hash.as_str()) {
+            Some(&i) => by_hash[i].push(pdf),
+            None => {
+                positions.insert(&pdf.hash, by_hash.len());
+                by_hash.push(vec![pdf]);
+            }
+        }
+    }
+
+    let shingles: Vec<Option<HashSet<u64>>> = by_hash
+        .iter()
+        .map(|copies| texts.get(&copies[0].hash).and_then(|pages| shingles(pages.iter().map(|page| page.text.as_str()))))
+        .collect();
+    // each group of identical copies joins the first it is near-identical to
+    let mut parents: Vec<usize> = (0..by_hash.len()).collect();
+    let mut similarities = vec![1.0; by_hash.len()];
+    for i in 0..by_hash.len() {
+        for j in i + 1..by_hash.len() {
+            let (Some(a), Some(b)) = (&shingles[i], &shingles[j]) else {
+                continue;
+            };
+            let similarity = jaccard(a, b);
+            if similarity >= NEAR_IDENTICAL {
+                let (root_i, root_j) = (root(&parents, i), root(&parents, j));
+                if root_i != root_j {
+                    parents[root_j] = root_i;
+                    similarities[root_i] = f64::min(similarities[root_i], f64::min(similarities[root_j], similarity));
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<Duplicates> = Vec::new();
+    let mut group_of: HashMap<usize, usize> = HashMap::new();
+    for (i, copies) in by_hash.into_iter().enumerate() {
+        let root = root(&parents, i);
+        match group_of.get(&root) {
+            Some(&group) => groups[group].copies.push(copies),
+            None => {
+                group_of.insert(root, groups.len());
+                groups.push(Duplicates { copies: vec![copies], similarity: similarities[root] });
+            }
+        }
+    }
+    groups.retain(|group| group.copies.iter().map(Vec::len).sum::<usize>() > 1);
+    groups
+}
+
+fn root(parents: &[usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        i = parents[i];
+    }
+    i
+}
+
+/// The hashes of every run of [`SHINGLE`] words in a text, if it is long
+/// enough to compare
+fn shingles<'a>(pages: impl Iterator<Item = &'a str>) -> Option<HashSet<u64>> {
+    let words: Vec<String> = pages.flat_map(text::words).collect();
+    if words.len() < MIN_WORDS {
+        return None;
+    }
+    let shingles = words
+        .windows(SHINGLE)
+        .map(|window| {
+            let mut hasher = DefaultHasher::new();
+            window.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect();
+    Some(shingles)
+}
+
+/// The share of the pieces of either text that both have
+fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    // sets this different in size cannot be near-identical
+    let (smaller, larger) = if a.len() < b.len() { (a, b) } else { (b, a) };
+    if (smaller.len() as f64) < NEAR_IDENTICAL * larger.len() as f64 {
+        return smaller.len() as f64 / larger.len() as f64;
+    }
+    let common = smaller.intersection(larger).count();
+    common as f64 / (a.len() + b.len() - common) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::PageText;
+
+    fn pdf(path: &str, hash: &str) -> StoredPdf {
+        StoredPdf {
+            path: path.into(),
+            hash: String::from(hash),
+            title: None,
+            author: None,
+            dois: Vec::new(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let body: Vec<String> = (0..200).map(|i| format!("word{i}")).collect();
+        let body = body.join(" ");
+        let pdfs = [
+            pdf("a.pdf", "1"),
+            pdf("b.pdf", "2"),
+            pdf("c.pdf", "1"),
+            pdf("d.pdf", "3"),
+            pdf("e.pdf", "4"),
+        ];
+        let mut texts = Texts::new();
+        let page = |text: String| vec![PageText { number: 1, text }];
+        texts.insert(String::from("1"), page(body.clone()));
+        // the same paper with a watermark on its page
+        texts.insert(String::from("2"), page(format!("{body} downloaded from the library")));
+        texts.insert(String::from("3"), page(body.replace("word1", "other")));
+
+        let found = duplicates(&pdfs, &texts);
+        assert_eq!(found.len(), 1);
+        let paths: Vec<Vec<&str>> = found[0]
+            .copies
+            .iter()
+            .map(|copies| copies.iter().map(|pdf| pdf.path.to_str().unwrap()).collect())
+            .collect();
+        assert_eq!(paths, [vec!["a.pdf", "c.pdf"], vec!["b.pdf"]]);
+        assert!(found[0].similarity > NEAR_IDENTICAL && found[0].similarity < 1.0);
+    }
+}
@@ -0,0 +1,6 @@
+//! Keeping the PDFs of a library in order: finding the copies of a PDF saved
+//! under different names, and filing PDFs under paths made from their
+//! entries
+
+pub mod dedupe;
+pub mod organize;
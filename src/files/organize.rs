@@ -0,0 +1,137 @@
+//! Filing PDFs under paths made from their entries with a template, e.g.
+//! `{year}/{citekey}.pdf`, and keeping the entries' `file` fields in step
+
+use std::path::{Path, PathBuf};
+
+use crate::bibtex::entry::BibTeXEntry;
+use crate::bibtex::latex;
+use crate::link::file_field_paths;
+
+/// What a value missing from an entry is filed under
+const UNKNOWN: &str = "unknown";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateError {
+    /// A `{` with no `}` after it
+    Unclosed,
+    Unknown(String),
+}
+
+impl std::error::Error for TemplateError {}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unclosed => write!(f, "the organize template has a `{{` with no `}}`"),
+            Self::Unknown(name) => write!(
+                f,
+                "the organize template has an unknown placeholder `{{{name}}}`, expected {{citekey}}, {{year}}, {{author}} or {{title}}"
+            ),
+        }
+    }
+}
+
+/// The path of an entry's PDF under a template, with `{citekey}`, `{year}`,
+/// `{author}` (the first author's surname) and `{title}` filled in
+pub fn expand(template: &str, entry: &BibTeXEntry) -> Result<PathBuf, TemplateError> {
+    let mut path = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        path.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            return Err(TemplateError::Unclosed);
+        };
+        let name = &rest[start + 1..start + end];
+        let value = match name {
+            "citekey" => Some(entry.citekey.clone()),
+            "year" => entry.year().map(u16::to_string),
+            "author" => entry.author().and_then(|authors| authors.first()).map(|author| latex::decode(author.surname())),
+            "title" => entry.title().map(|title| latex::decode(title)),
+            name => return Err(TemplateError::Unknown(name.to_string())),
+        };
+        path.push_str(&value.map(|value| sanitise(&value)).filter(|value| !value.is_empty()).unwrap_or(UNKNOWN.to_string()));
+        rest = &rest[start + end + 1..];
+    }
+    path.push_str(rest);
+    Ok(PathBuf::from(path))
+}
+
+/// A value fit to be part of a file name on any platform
+fn sanitise(value: &str) -> String {
+    let value: String = value
+        .chars()
+        .filter(|c| !c.is_control() && !matches!(c, '{' | '}' | '*' | '?' | '"' | '<' | '>' | '|'))
+        .map(|c| if matches!(c, '/' | '\\' | ':') { '-' } else { c })
+        .collect();
+    value.split_whitespace().collect::<Vec<_>>().join(" ").trim_matches('.').to_string()
+}
+
+/// An entry's `file` field once its PDF has moved from `from` to `to`,
+/// which is written into the field as given. Other files in the field are
+/// kept if they still exist, and JabRef's `description:path:type` form is
+/// kept where it is used. `resolve` turns the field's paths into paths that
+/// can be compared with `from`
+pub fn moved_file_field(value: Option<&str>, from: &Path, to: &str, resolve: impl Fn(&Path) -> PathBuf) -> String {
+    let mut files = Vec::new();
+    let mut replaced = false;
+    let mut jabref = false;
+    for file in value.unwrap_or_default().split(';').filter(|file| !file.trim().is_empty()) {
+        let parts: Vec<&str> = file.split(':').collect();
+        jabref |= parts.len() >= 3;
+        let Some(path) = file_field_paths(file).into_iter().next().map(|path| resolve(&path)) else {
+            continue;
+        };
+        if path == from {
+            files.push(match parts.as_slice() {
+                [description, .., kind] if parts.len() >= 3 => format!("{description}:{to}:{kind}"),
+                _ => to.to_string(),
+            });
+            replaced = true;
+        } else if path.is_file() {
+            files.push(file.to_string());
+        }
+    }
+    if !replaced {
+        files.push(if jabref { format!(":{to}:PDF") } else { to.to_string() });
+    }
+    files.join(";")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibtex::parser::BibTeXParser;
+
+    #[test]
+    fn test_expand() {
+        let entries = BibTeXParser::new(r#"
+            @article{Muller2012,
+                author = {M{\"u}ller, Hans and Smith, Jane},
+                title = {Turbulence: {RANS}/{LES} Modelling},
+                year = {2012}
+            }
+        "#).parse().unwrap();
+        let entry = &entries[0];
+        assert_eq!(expand("{year}/{citekey}.pdf", entry).unwrap(), PathBuf::from("2012/Muller2012.pdf"));
+        assert_eq!(expand("{author}/{year} - {title}.pdf", entry).unwrap(), PathBuf::from("Müller/2012 - Turbulence- RANS-LES Modelling.pdf"));
+        assert_eq!(expand("{citekey", entry), Err(TemplateError::Unclosed));
+        assert_eq!(expand("{journal}.pdf", entry), Err(TemplateError::Unknown(String::from("journal"))));
+
+        let undated = BibTeXParser::new("@misc{Anon, title = {Notes}}").parse().unwrap();
+        assert_eq!(expand("{year}/{citekey}.pdf", &undated[0]).unwrap(), PathBuf::from("unknown/Anon.pdf"));
+    }
+
+    #[test]
+    fn test_moved_file_field() {
+        let resolve = |path: &Path| Path::new("/project").join(path);
+        let from = Path::new("/project/papers/old.pdf");
+        assert_eq!(moved_file_field(None, from, "papers/2012/A.pdf", resolve), "papers/2012/A.pdf");
+        assert_eq!(moved_file_field(Some("papers/old.pdf"), from, "papers/2012/A.pdf", resolve), "papers/2012/A.pdf");
+        assert_eq!(
+            moved_file_field(Some("Full text:papers/old.pdf:PDF"), from, "papers/2012/A.pdf", resolve),
+            "Full text:papers/2012/A.pdf:PDF"
+        );
+        // a stale path is dropped, in the form the field was written in
+        assert_eq!(moved_file_field(Some(":gone.pdf:PDF"), from, "papers/2012/A.pdf", resolve), ":papers/2012/A.pdf:PDF");
+    }
+}
@@ -0,0 +1,311 @@
+//! Reading of EndNote XML exports (`File > Export > XML`)
+
+use std::str::FromStr;
+
+use roxmltree::{Document, Node};
+
+use crate::bibtex::entry::{BibTeXEntry, BibTeXEntryKind};
+use crate::bibtex::fields::{Author, Authors, BibTeXField, Month};
+
+use super::error::FormatError;
+use super::strip_doi_prefix;
+
+pub struct EndNoteXmlParser<'a> {
+    /// The raw XML of the export
+    input: &'a str,
+}
+
+impl<'a> EndNoteXmlParser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        EndNoteXmlParser { input: input.trim_start_matches('\u{feff}') }
+    }
+
+    /// Parses every `<record>` in the export into an entry, leaving the
+    /// citekeys empty to be generated later
+    pub fn parse(&mut self) -> Result<Vec<BibTeXEntry>, FormatError> {
+        let document = Document::parse(self.input)?;
+        let entries = document
+            .descendants()
+            .filter(|node| node.has_tag_name("record"))
+            .map(record_to_entry)
+            .collect();
+
+        Ok(entries)
+    }
+}
+
+/// The text of an element, which EndNote usually wraps in one or more
+/// `<style>` elements
+fn text_of(node: Node) -> String {
+    let text: String = node
+        .descendants()
+        .filter(Node::is_text)
+        .filter_map(|n| n.text())
+        .collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The text of the first descendant found along `path`
+fn find_text(record: Node, path: &[&str]) -> Option<String> {
+    let mut node = record;
+    for name in path {
+        node = node.children().find(|child| child.has_tag_name(*name))?;
+    }
+    Some(text_of(node)).filter(|text| !text.is_empty())
+}
+
+/// The text of every element named `leaf` below the element at `path`
+fn find_all_text(record: Node, path: &[&str], leaf: &str) -> Vec<String> {
+    let mut node = record;
+    for name in path {
+        match node.children().find(|child| child.has_tag_name(*name)) {
+            Some(child) => node = child,
+            None => return Vec::new(),
+        }
+    }
+    node.descendants()
+        .filter(|n| n.has_tag_name(leaf))
+        .map(text_of)
+        .filter(|text| !text.is_empty())
+        .collect()
+}
+
+fn record_to_entry(record: Node) -> BibTeXEntry {
+    let kind = kind_from_record(record);
+    let mut entry = BibTeXEntry::new(kind, String::new());
+
+    let names = |role: &str| -> Option<Authors> {
+        let authors: Vec<Author> = find_all_text(record, &["contributors", role], "author")
+            .iter()
+            .filter_map(|name| Author::from_str(name).ok())
+            .collect();
+        (!authors.is_empty()).then(|| Authors::new(authors))
+    };
+    if let Some(authors) = names("authors") {
+        entry.add_field(BibTeXField::Author(authors));
+    }
+    if let Some(editors) = names("secondary-authors") {
+        entry.add_field(BibTeXField::Editor(editors));
+    }
+
+    if let Some(title) = find_text(record, &["titles", "title"]) {
+        entry.add_field(BibTeXField::Title(title));
+    }
+
+    let secondary_title = find_text(record, &["titles", "secondary-title"]);
+    match entry.kind {
+        BibTeXEntryKind::Article => {
+            let journal = find_text(record, &["periodical", "full-title"]).or(secondary_title);
+            if let Some(journal) = journal {
+                entry.add_field(BibTeXField::Journal(journal));
+            }
+        }
+        _ => {
+            if let Some(book_title) = secondary_title {
+                entry.add_field(BibTeXField::BookTitle(book_title));
+            }
+        }
+    }
+
+    let simple_fields = [
+        ("volume", "volume"),
+        ("number", "number"),
+        ("pages", "pages"),
+        ("edition", "edition"),
+        ("pub-location", "address"),
+    ];
+    for (tag, key) in simple_fields {
+        if let Some(value) = find_text(record, &[tag]) {
+            entry.add_field(BibTeXField::from_key_value(key, &value));
+        }
+    }
+
+    if let Some(publisher) = find_text(record, &["publisher"]) {
+        let field = match entry.kind {
+            BibTeXEntryKind::PhdThesis | BibTeXEntryKind::MasterThesis => BibTeXField::School(publisher),
+            BibTeXEntryKind::TechReport => BibTeXField::Institution(publisher),
+            _ => BibTeXField::Publisher(publisher),
+        };
+        entry.add_field(field);
+    }
+
+    if let Some(year) = find_text(record, &["dates", "year"]).and_then(|y| y.parse().ok()) {
+        entry.add_field(BibTeXField::Year(year));
+    }
+    // pub-dates holds free text such as "Mar" or "Mar 15"
+    let month = find_text(record, &["dates", "pub-dates", "date"])
+        .and_then(|date| date.split_whitespace().next().map(str::to_string))
+        .and_then(|month| Month::from_str(&month).ok());
+    if let Some(month) = month {
+        entry.add_field(BibTeXField::Month(month as u8 + 1));
+    }
+
+    if let Some(doi) = find_text(record, &["electronic-resource-num"]) {
+        entry.add_field(BibTeXField::Doi(strip_doi_prefix(&doi).to_string()));
+    }
+    if let Some(notes) = find_text(record, &["notes"]) {
+        entry.add_field(BibTeXField::Note(notes));
+    }
+
+    let mut non_standard = vec![
+        ("abstract", find_text(record, &["abstract"])),
+        ("pmid", pmid(record)),
+        ("url", find_all_text(record, &["urls", "related-urls"], "url").into_iter().next()),
+        ("file", find_all_text(record, &["urls", "pdf-urls"], "url").into_iter().next()),
+        ("language", find_text(record, &["language"])),
+    ];
+    let isbn_key = if entry.kind == BibTeXEntryKind::Article { "issn" } else { "isbn" };
+    non_standard.push((isbn_key, find_text(record, &["isbn"])));
+
+    for (key, value) in non_standard {
+        if let Some(value) = value {
+            entry.add_field(BibTeXField::NonStandard { key: key.to_string(), value });
+        }
+    }
+
+    let keywords = find_all_text(record, &["keywords"], "keyword");
+    if !keywords.is_empty() {
+        entry.add_field(BibTeXField::NonStandard {
+            key: String::from("keywords"),
+            value: keywords.join(", "),
+        });
+    }
+
+    entry
+}
+
+/// Records that came from PubMed keep their PMID in the accession number
+fn pmid(record: Node) -> Option<String> {
+    find_text(record, &["accession-num"])
+        .map(|num| num.trim_start_matches("PMID:").trim().to_string())
+        .filter(|num| !num.is_empty() && num.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn kind_from_record(record: Node) -> BibTeXEntryKind {
+    let ref_type = record.children().find(|child| child.has_tag_name("ref-type"));
+    let name = ref_type
+        .and_then(|node| node.attribute("name"))
+        .map(str::to_lowercase);
+    let number = ref_type.map(text_of);
+
+    let name = match (name.as_deref(), number.as_deref()) {
+        (Some(name), _) => name.to_string(),
+        (None, Some("17")) => String::from("journal article"),
+        (None, Some("6")) => String::from("book"),
+        (None, Some("5")) => String::from("book section"),
+        (None, Some("10")) => String::from("conference proceedings"),
+        (None, Some("47")) => String::from("conference paper"),
+        (None, Some("32")) => String::from("thesis"),
+        (None, Some("27")) => String::from("report"),
+        (None, Some("34")) => String::from("unpublished work"),
+        _ => String::new(),
+    };
+
+    match name.as_str() {
+        "journal article" | "magazine article" | "newspaper article"
+        | "electronic article"                         => BibTeXEntryKind::Article,
+        "book" | "edited book" | "electronic book"     => BibTeXEntryKind::Book,
+        "book section" | "electronic book section"     => BibTeXEntryKind::InCollection,
+        "conference paper"                             => BibTeXEntryKind::InProceedings,
+        // EndNote files most conference papers under "Conference Proceedings"
+        "conference proceedings" if has_authors(record) => BibTeXEntryKind::InProceedings,
+        "conference proceedings"                       => BibTeXEntryKind::Proceedings,
+        "report" | "government document"               => BibTeXEntryKind::TechReport,
+        "pamphlet"                                     => BibTeXEntryKind::Booklet,
+        "standard" | "computer program"                => BibTeXEntryKind::Manual,
+        "unpublished work" | "manuscript"              => BibTeXEntryKind::Unpublished,
+        "thesis" => {
+            let work_type = find_text(record, &["work-type"]).unwrap_or_default().to_lowercase();
+            if work_type.contains("master") || work_type.contains("msc") {
+                BibTeXEntryKind::MasterThesis
+            } else {
+                BibTeXEntryKind::PhdThesis
+            }
+        }
+        _ => BibTeXEntryKind::Misc,
+    }
+}
+
+fn has_authors(record: Node) -> bool {
+    !find_all_text(record, &["contributors", "authors"], "author").is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<xml><records>
+<record>
+  <database name="Library.enl" path="Library.enl">Library.enl</database>
+  <ref-type name="Journal Article">17</ref-type>
+  <contributors><authors>
+    <author><style face="normal" font="default" size="100%">Smith, John A.</style></author>
+    <author><style face="normal" font="default" size="100%">Doe, Jane</style></author>
+  </authors></contributors>
+  <titles>
+    <title><style face="normal" font="default" size="100%">Adenosine signalling in </style><style face="italic" font="default" size="100%">vivo</style></title>
+    <secondary-title><style face="normal" font="default" size="100%">J Biol Chem</style></secondary-title>
+  </titles>
+  <periodical><full-title><style face="normal" font="default" size="100%">The Journal of biological chemistry</style></full-title></periodical>
+  <pages><style face="normal" font="default" size="100%">4012-4025</style></pages>
+  <volume><style face="normal" font="default" size="100%">294</style></volume>
+  <number><style face="normal" font="default" size="100%">11</style></number>
+  <keywords>
+    <keyword><style face="normal" font="default" size="100%">Humans</style></keyword>
+    <keyword><style face="normal" font="default" size="100%">Adenosine/metabolism</style></keyword>
+  </keywords>
+  <dates><year><style face="normal" font="default" size="100%">2019</style></year>
+    <pub-dates><date><style face="normal" font="default" size="100%">Mar 15</style></date></pub-dates></dates>
+  <accession-num><style face="normal" font="default" size="100%">30670592</style></accession-num>
+  <abstract><style face="normal" font="default" size="100%">Adenosine is an
+    important signalling molecule.</style></abstract>
+  <electronic-resource-num><style face="normal" font="default" size="100%">10.1074/jbc.RA118.006</style></electronic-resource-num>
+</record>
+<record>
+  <ref-type name="Thesis">32</ref-type>
+  <contributors><authors><author>Roe, Richard</author></authors></contributors>
+  <titles><title>Hypersonic boundary layers</title></titles>
+  <dates><year>2021</year></dates>
+  <publisher>University of Oxford</publisher>
+  <work-type>MSc thesis</work-type>
+</record>
+</records></xml>"#;
+
+    #[test]
+    fn test_journal_article() {
+        let entries = EndNoteXmlParser::new(SAMPLE).parse().unwrap();
+        assert_eq!(entries.len(), 2);
+        let entry = &entries[0];
+
+        assert_eq!(entry.kind, BibTeXEntryKind::Article);
+        assert_eq!(entry.author().unwrap().to_bibtex_names(), "Smith, John A. and Doe, Jane");
+        assert_eq!(entry.title().unwrap(), "Adenosine signalling in vivo");
+        assert_eq!(entry.journal().unwrap(), "The Journal of biological chemistry");
+        assert_eq!(entry.volume(), Some(&294));
+        assert_eq!(entry.number(), Some(&11));
+        assert_eq!(entry.pages().unwrap().to_string(), "4012--4025");
+        assert_eq!(entry.year(), Some(&2019));
+        assert_eq!(entry.month(), Some(&3));
+        assert_eq!(entry.doi().unwrap(), "10.1074/jbc.RA118.006");
+        assert_eq!(entry.non_standard_field("pmid").unwrap(), "30670592");
+        assert_eq!(entry.non_standard_field("abstract").unwrap(), "Adenosine is an important signalling molecule.");
+        assert_eq!(entry.non_standard_field("keywords").unwrap(), "Humans, Adenosine/metabolism");
+    }
+
+    #[test]
+    fn test_thesis() {
+        let entries = EndNoteXmlParser::new(SAMPLE).parse().unwrap();
+        let entry = &entries[1];
+
+        assert_eq!(entry.kind, BibTeXEntryKind::MasterThesis);
+        assert_eq!(entry.school().unwrap(), "University of Oxford");
+        assert_eq!(entry.publisher(), None);
+    }
+
+    #[test]
+    fn test_invalid_xml() {
+        let result = EndNoteXmlParser::new("<xml><records><record></records>").parse();
+        assert!(matches!(result, Err(FormatError::Xml(_))));
+    }
+}
@@ -0,0 +1,58 @@
+use crate::bibtex::error::ParseError;
+
+use super::Format;
+
+#[derive(Debug, PartialEq)]
+pub enum FormatError {
+    UnrecognisedFormat, 
+    MissingTag { line: usize, tag: &'static str }, 
+    Xml(String), 
+    Yaml(String), 
+    UnsupportedExport(Format), 
+    BibTeX(ParseError), 
+}
+
+impl std::error::Error for FormatError {}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnrecognisedFormat => {
+                write!(f, "could not recognise the format of the given file")
+            }
+            Self::MissingTag { line, tag } => {
+                write!(f, "line {}: expected a '{}' tag to start the record", line, tag)
+            }
+            Self::Xml(e) => {
+                write!(f, "invalid xml: {}", e)
+            }
+            Self::Yaml(e) => {
+                write!(f, "invalid yaml: {}", e)
+            }
+            Self::UnsupportedExport(format) => {
+                write!(f, "exporting to {} is not supported", format)
+            }
+            Self::BibTeX(e) => {
+                write!(f, "{}", e)
+            }
+        }
+    }
+}
+
+impl From<roxmltree::Error> for FormatError {
+    fn from(e: roxmltree::Error) -> Self {
+        Self::Xml(e.to_string())
+    }
+}
+
+impl From<serde_yaml::Error> for FormatError {
+    fn from(e: serde_yaml::Error) -> Self {
+        Self::Yaml(e.to_string())
+    }
+}
+
+impl From<ParseError> for FormatError {
+    fn from(e: ParseError) -> Self {
+        Self::BibTeX(e)
+    }
+}
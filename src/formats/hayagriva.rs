@@ -0,0 +1,556 @@
+//! Conversion to and from Hayagriva YAML, the bibliography format used by
+//! Typst. Hayagriva nests the containing work of an entry as its `parent`,
+//! so a paper in a journal is an `article` whose parent is a `periodical`
+
+use std::str::FromStr;
+
+use serde_yaml::{Mapping, Value};
+
+use crate::bibtex::entry::{BibTeXEntry, BibTeXEntryKind};
+use crate::bibtex::fields::{Author, Authors, BibTeXField};
+
+use super::error::FormatError;
+use super::strip_doi_prefix;
+
+/// Whether the line looks like the key opening a Hayagriva entry, e.g.
+/// `kenway2019:`
+pub fn is_record_start(line: &str) -> bool {
+    let line = line.trim_end();
+    !line.starts_with([' ', '\t', '#', '-'])
+        && line.ends_with(':')
+        && !line[..line.len() - 1].contains([':', ' '])
+}
+
+pub struct HayagrivaParser<'a> {
+    /// The raw YAML of the bibliography
+    input: &'a str,
+}
+
+impl<'a> HayagrivaParser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        HayagrivaParser { input: input.trim_start_matches('\u{feff}') }
+    }
+
+    /// Parses every top level item of the YAML mapping into an entry, using
+    /// the item's key as the citekey
+    pub fn parse(&mut self) -> Result<Vec<BibTeXEntry>, FormatError> {
+        let document: Mapping = serde_yaml::from_str(self.input)?;
+        let entries = document
+            .iter()
+            .filter_map(|(key, item)| Some((scalar_to_string(key)?, item.as_mapping()?)))
+            .map(|(citekey, item)| item_to_entry(citekey, item))
+            .collect();
+
+        Ok(entries)
+    }
+}
+
+/// Reads a string or numeric YAML scalar as a string
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        // formattable strings can also be given as `{ value: ..., verbatim: true }`
+        Value::Mapping(m) => m.get("value").and_then(scalar_to_string),
+        _ => None,
+    }
+}
+
+fn get_string(item: &Mapping, key: &str) -> Option<String> {
+    item.get(key).and_then(scalar_to_string).filter(|s| !s.is_empty())
+}
+
+/// Items can have a single parent or a list of them, and we only care for
+/// the first
+fn get_parent(item: &Mapping) -> Option<&Mapping> {
+    match item.get("parent")? {
+        Value::Sequence(parents) => parents.first()?.as_mapping(),
+        parent => parent.as_mapping(),
+    }
+}
+
+/// Hayagriva types are case insensitive
+fn get_type(item: &Mapping) -> Option<String> {
+    get_string(item, "type").map(|t| t.to_lowercase())
+}
+
+fn get_persons(item: &Mapping, key: &str) -> Option<Authors> {
+    let persons = match item.get(key)? {
+        Value::Sequence(persons) => persons.iter().filter_map(person_to_author).collect(),
+        person => person_to_author(person).into_iter().collect::<Vec<_>>(),
+    };
+    (!persons.is_empty()).then(|| Authors::new(persons))
+}
+
+/// Persons are either "Prefix Name, Given, Suffix" or a mapping of the
+/// separate parts
+fn person_to_author(person: &Value) -> Option<Author> {
+    match person {
+        Value::String(name) => {
+            let parts: Vec<&str> = name.split(',').map(str::trim).collect();
+            // hayagriva puts the suffix last rather than in the middle like BibTeX
+            let bibtex_name = match parts.as_slice() {
+                [name, given, suffix] => format!("{name}, {suffix}, {given}"),
+                [name, given] => format!("{name}, {given}"),
+                _ => format!("{{{name}}}"),
+            };
+            Author::from_str(&bibtex_name).ok()
+        }
+        Value::Mapping(parts) => {
+            let name = get_string(parts, "name")?;
+            let given = get_string(parts, "given-name").unwrap_or_default();
+            let mut author = Author::new(&given, &name);
+            if let Some(prefix) = get_string(parts, "prefix") {
+                author = author.with_prefix(&prefix);
+            }
+            if let Some(suffix) = get_string(parts, "suffix") {
+                author = author.with_suffix(&suffix);
+            }
+            Some(author)
+        }
+        _ => None,
+    }
+}
+
+fn kind_from_types(item_type: Option<&str>, parent_type: Option<&str>, genre: &str) -> BibTeXEntryKind {
+    match (item_type.unwrap_or("misc"), parent_type) {
+        ("article", Some("proceedings" | "conference")) => BibTeXEntryKind::InProceedings,
+        ("article", _)                                  => BibTeXEntryKind::Article,
+        ("chapter" | "anthos", _)                       => BibTeXEntryKind::InCollection,
+        ("book" | "anthology", _)                       => BibTeXEntryKind::Book,
+        ("proceedings", _)                              => BibTeXEntryKind::Proceedings,
+        ("report", _)                                   => BibTeXEntryKind::TechReport,
+        ("reference", _)                                => BibTeXEntryKind::Manual,
+        ("manuscript", _)                               => BibTeXEntryKind::Unpublished,
+        ("thesis", _) if genre.contains("master")       => BibTeXEntryKind::MasterThesis,
+        ("thesis", _)                                   => BibTeXEntryKind::PhdThesis,
+        _                                               => BibTeXEntryKind::Misc,
+    }
+}
+
+fn item_to_entry(citekey: String, item: &Mapping) -> BibTeXEntry {
+    let parent = get_parent(item);
+    let genre = get_string(item, "genre").unwrap_or_default().to_lowercase();
+    let kind = kind_from_types(get_type(item).as_deref(), parent.and_then(get_type).as_deref(), &genre);
+    let mut entry = BibTeXEntry::new(kind, citekey);
+
+    // most fields can be on either the item itself or its parent
+    let lookup = |key: &str| get_string(item, key).or_else(|| parent.and_then(|p| get_string(p, key)));
+
+    if let Some(authors) = get_persons(item, "author") {
+        entry.add_field(BibTeXField::Author(authors));
+    }
+    if let Some(editors) = get_persons(item, "editor").or_else(|| parent.and_then(|p| get_persons(p, "editor"))) {
+        entry.add_field(BibTeXField::Editor(editors));
+    }
+    if let Some(title) = get_string(item, "title") {
+        entry.add_field(BibTeXField::Title(title));
+    }
+    if let Some(parent_title) = parent.and_then(|p| get_string(p, "title")) {
+        let field = match entry.kind {
+            BibTeXEntryKind::Article => BibTeXField::Journal(parent_title),
+            _ => BibTeXField::BookTitle(parent_title),
+        };
+        entry.add_field(field);
+    }
+
+    for (key, bibtex_key) in [("volume", "volume"), ("issue", "number"), ("edition", "edition"), ("chapter", "chapter")] {
+        if let Some(value) = lookup(key) {
+            entry.add_field(BibTeXField::from_key_value(bibtex_key, &value));
+        }
+    }
+    if let Some(pages) = get_string(item, "page-range") {
+        entry.add_field(BibTeXField::from_key_value("pages", &pages));
+    }
+
+    let publisher = item.get("publisher").or_else(|| parent.and_then(|p| p.get("publisher")));
+    if let Some(publisher) = publisher {
+        let name = scalar_to_string(publisher)
+            .or_else(|| publisher.as_mapping().and_then(|p| get_string(p, "name")));
+        if let Some(name) = name {
+            entry.add_field(BibTeXField::Publisher(name));
+        }
+    }
+    // the location is on the publisher if there is one, else on its own
+    let location = publisher
+        .and_then(Value::as_mapping)
+        .and_then(|p| get_string(p, "location"))
+        .or_else(|| lookup("location"));
+    if let Some(location) = location {
+        entry.add_field(BibTeXField::Address(location));
+    }
+    if let Some(organization) = lookup("organization") {
+        let field = match entry.kind {
+            BibTeXEntryKind::PhdThesis | BibTeXEntryKind::MasterThesis => BibTeXField::School(organization),
+            BibTeXEntryKind::TechReport => BibTeXField::Institution(organization),
+            _ => BibTeXField::Organization(organization),
+        };
+        entry.add_field(field);
+    }
+
+    if let Some(date) = get_string(item, "date") {
+        let mut parts = date.split('-');
+        if let Some(year) = parts.next().and_then(|y| y.parse().ok()) {
+            entry.add_field(BibTeXField::Year(year));
+        }
+        if let Some(month) = parts.next().and_then(|m| m.parse().ok()) {
+            entry.add_field(BibTeXField::Month(month));
+        }
+        if let Some(day) = parts.next().and_then(|d| d.parse().ok()) {
+            entry.add_field(BibTeXField::Day(day));
+        }
+    }
+
+    // the serial number is either a plain report number or a mapping of schemes
+    let serial_numbers = item.get("serial-number").and_then(Value::as_mapping);
+    let serial = |key: &str| serial_numbers.and_then(|s| get_string(s, key));
+    let report_number = serial("serial").or_else(|| item.get("serial-number").and_then(scalar_to_string));
+    if let Some(number) = report_number.filter(|_| entry.kind == BibTeXEntryKind::TechReport) {
+        entry.add_field(BibTeXField::from_key_value("number", &number));
+    }
+    if let Some(doi) = serial("doi") {
+        entry.add_field(BibTeXField::Doi(strip_doi_prefix(&doi).to_string()));
+    }
+    if let Some(note) = get_string(item, "note") {
+        entry.add_field(BibTeXField::Note(note));
+    }
+
+    let non_standard = [
+        ("url", get_string(item, "url")),
+        ("abstract", get_string(item, "abstract")),
+        ("isbn", serial("isbn")),
+        ("issn", serial("issn")),
+        ("pmid", serial("pmid")),
+        ("eprint", serial("arxiv")),
+        ("language", get_string(item, "language")),
+    ];
+    for (key, value) in non_standard {
+        if let Some(value) = value {
+            entry.add_field(BibTeXField::NonStandard { key: key.to_string(), value });
+        }
+    }
+
+    entry
+}
+
+/// Writes the entries out as a Hayagriva YAML bibliography
+pub fn write<'a>(entries: impl IntoIterator<Item = &'a BibTeXEntry>) -> Result<String, FormatError> {
+    let mut document = Mapping::new();
+    for entry in entries {
+        document.insert(Value::from(entry.citekey.as_str()), Value::Mapping(entry_to_item(entry)));
+    }
+
+    Ok(serde_yaml::to_string(&document)?)
+}
+
+/// The Hayagriva type of the entry, along with the type of its parent when
+/// the entry is published within another work
+fn types_for_kind(kind: &BibTeXEntryKind) -> (&'static str, Option<&'static str>) {
+    match kind {
+        BibTeXEntryKind::Article       => ("article", Some("periodical")),
+        BibTeXEntryKind::InProceedings
+        | BibTeXEntryKind::Conference  => ("article", Some("proceedings")),
+        BibTeXEntryKind::InCollection
+        | BibTeXEntryKind::Inbook      => ("chapter", Some("book")),
+        BibTeXEntryKind::Book          => ("book", None),
+        BibTeXEntryKind::Proceedings   => ("proceedings", None),
+        BibTeXEntryKind::TechReport    => ("report", None),
+        BibTeXEntryKind::Manual        => ("reference", None),
+        BibTeXEntryKind::Unpublished   => ("manuscript", None),
+        BibTeXEntryKind::PhdThesis
+        | BibTeXEntryKind::MasterThesis => ("thesis", None),
+        BibTeXEntryKind::Booklet
+        | BibTeXEntryKind::Misc
+        | BibTeXEntryKind::Unknown     => ("misc", None),
+    }
+}
+
+fn persons_value(authors: &Authors) -> Value {
+    let persons = authors
+        .iter()
+        .map(|author| {
+            let mut name = match author.prefix() {
+                Some(prefix) => format!("{} {}", prefix, author.surname()),
+                None => author.surname().to_string(),
+            };
+            if !author.forename().is_empty() {
+                name.push_str(", ");
+                name.push_str(author.forename());
+            }
+            if let Some(suffix) = author.suffix() {
+                name.push_str(", ");
+                name.push_str(suffix);
+            }
+            Value::from(name)
+        })
+        .collect();
+    Value::Sequence(persons)
+}
+
+fn entry_to_item(entry: &BibTeXEntry) -> Mapping {
+    let (item_type, parent_type) = types_for_kind(&entry.kind);
+    let mut item = Mapping::new();
+    let mut parent = Mapping::new();
+    if let Some(parent_type) = parent_type {
+        parent.insert("type".into(), parent_type.into());
+    }
+
+    item.insert("type".into(), item_type.into());
+    if let Some(title) = entry.title() {
+        item.insert("title".into(), title.as_str().into());
+    }
+    if let Some(authors) = entry.author() {
+        item.insert("author".into(), persons_value(authors));
+    }
+
+    if let Some(year) = entry.year() {
+        let mut date = format!("{year:04}");
+        if let Some(month) = entry.month() {
+            date.push_str(&format!("-{month:02}"));
+            if let Some(day) = entry.day() {
+                date.push_str(&format!("-{day:02}"));
+            }
+        }
+        item.insert("date".into(), date.into());
+    }
+    if let Some(pages) = entry.pages() {
+        let range = match (pages.first_page(), pages.last_page()) {
+            (Some(first), Some(last)) if first != last => format!("{first}-{last}"),
+            (Some(first), _) => first.to_string(),
+            _ => pages.to_string(),
+        };
+        item.insert("page-range".into(), range.into());
+    }
+
+    // the details of the containing work belong to the parent, if there is one
+    let container = if parent_type.is_some() { &mut parent } else { &mut item };
+    if let Some(parent_title) = entry.journal().or(entry.book_title()) {
+        container.insert("title".into(), parent_title.as_str().into());
+    }
+    if let Some(editors) = entry.editor() {
+        container.insert("editor".into(), persons_value(editors));
+    }
+    if let Some(volume) = entry.volume() {
+        container.insert("volume".into(), (*volume).into());
+    }
+    if let Some(number) = entry.number().filter(|_| entry.kind != BibTeXEntryKind::TechReport) {
+        container.insert("issue".into(), (*number).into());
+    }
+    if let Some(edition) = entry.edition() {
+        container.insert("edition".into(), (*edition).into());
+    }
+    if let Some(publisher) = entry.publisher() {
+        let value = match entry.address() {
+            Some(address) => {
+                let mut publisher_mapping = Mapping::new();
+                publisher_mapping.insert("name".into(), publisher.as_str().into());
+                publisher_mapping.insert("location".into(), address.as_str().into());
+                Value::Mapping(publisher_mapping)
+            }
+            None => publisher.as_str().into(),
+        };
+        container.insert("publisher".into(), value);
+    } else if let Some(address) = entry.address() {
+        container.insert("location".into(), address.as_str().into());
+    }
+    let organization = entry
+        .school()
+        .or(entry.institution())
+        .or(entry.organization());
+    if let Some(organization) = organization {
+        container.insert("organization".into(), organization.as_str().into());
+    }
+
+    if let Some(chapter) = entry.chapter() {
+        item.insert("chapter".into(), (*chapter).into());
+    }
+    match entry.kind {
+        BibTeXEntryKind::PhdThesis => {
+            item.insert("genre".into(), "Doctoral dissertation".into());
+        }
+        BibTeXEntryKind::MasterThesis => {
+            item.insert("genre".into(), "Master's thesis".into());
+        }
+        _ => {}
+    }
+
+    let mut serial_numbers = Mapping::new();
+    if let Some(number) = entry.number().filter(|_| entry.kind == BibTeXEntryKind::TechReport) {
+        serial_numbers.insert("serial".into(), number.to_string().into());
+    }
+    if let Some(doi) = entry.doi() {
+        serial_numbers.insert("doi".into(), doi.as_str().into());
+    }
+    for (key, bibtex_key) in [("isbn", "isbn"), ("issn", "issn"), ("pmid", "pmid"), ("arxiv", "eprint")] {
+        if let Some(value) = entry.non_standard_field(bibtex_key) {
+            serial_numbers.insert(key.into(), value.as_str().into());
+        }
+    }
+    if !serial_numbers.is_empty() {
+        item.insert("serial-number".into(), Value::Mapping(serial_numbers));
+    }
+
+    for key in ["url", "abstract", "language"] {
+        if let Some(value) = entry.non_standard_field(key) {
+            item.insert(key.into(), value.as_str().into());
+        }
+    }
+    if let Some(note) = entry.note() {
+        item.insert("note".into(), note.as_str().into());
+    }
+
+    if parent_type.is_some() {
+        item.insert("parent".into(), Value::Mapping(parent));
+    }
+
+    item
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibtex::parser::BibTeXParser;
+
+    const BIBTEX: &str = r#"
+        @article{kenway2019,
+            author = {Kenway, Gaetan K. W. and Martins, Jr, Joaquim},
+            title = {Effective adjoint approaches for computational fluid dynamics},
+            journal = {Progress in Aerospace Sciences},
+            volume = 110,
+            number = 3,
+            pages = {100542--100560},
+            year = 2019,
+            month = oct,
+            doi = {10.1016/j.paerosci.2019.05.002},
+        }
+        @inproceedings{he2018,
+            author = {He, Ping},
+            title = {An aerodynamic design optimization framework},
+            booktitle = {AIAA Aviation Forum},
+            editor = {Doe, Jane},
+            publisher = {AIAA},
+            address = {Atlanta},
+            year = 2018,
+        }
+        @incollection{roe2020,
+            author = {Roe, Richard},
+            title = {Shock boundary layer interaction},
+            booktitle = {Handbook of Hypersonics},
+            publisher = {Springer},
+            pages = {45--67},
+            year = 2020,
+        }
+        @phdthesis{doe2021,
+            author = {Doe, Jane},
+            title = {Wall-modelled large eddy simulation},
+            school = {Stanford University},
+            address = {Stanford, CA},
+            year = 2021,
+        }
+    "#;
+
+    fn entries() -> Vec<BibTeXEntry> {
+        BibTeXParser::new(BIBTEX).parse().unwrap()
+    }
+
+    #[test]
+    fn test_write_article() {
+        let yaml = write(&entries()).unwrap();
+        let document: Mapping = serde_yaml::from_str(&yaml).unwrap();
+        let item = document.get("kenway2019").unwrap().as_mapping().unwrap();
+
+        assert_eq!(get_string(item, "type").unwrap(), "article");
+        assert_eq!(get_string(item, "date").unwrap(), "2019-10");
+        assert_eq!(get_string(item, "page-range").unwrap(), "100542-100560");
+        let authors = item.get("author").unwrap().as_sequence().unwrap();
+        assert_eq!(authors[1].as_str().unwrap(), "Martins, Joaquim, Jr");
+
+        let parent = get_parent(item).unwrap();
+        assert_eq!(get_string(parent, "type").unwrap(), "periodical");
+        assert_eq!(get_string(parent, "title").unwrap(), "Progress in Aerospace Sciences");
+        assert_eq!(get_string(parent, "volume").unwrap(), "110");
+        assert_eq!(get_string(parent, "issue").unwrap(), "3");
+    }
+
+    #[test]
+    fn test_write_parents() {
+        let yaml = write(&entries()).unwrap();
+        let document: Mapping = serde_yaml::from_str(&yaml).unwrap();
+
+        let paper = document.get("he2018").unwrap().as_mapping().unwrap();
+        let proceedings = get_parent(paper).unwrap();
+        assert_eq!(get_string(proceedings, "type").unwrap(), "proceedings");
+        assert_eq!(get_string(proceedings, "title").unwrap(), "AIAA Aviation Forum");
+        let publisher = proceedings.get("publisher").unwrap().as_mapping().unwrap();
+        assert_eq!(get_string(publisher, "location").unwrap(), "Atlanta");
+
+        let chapter = document.get("roe2020").unwrap().as_mapping().unwrap();
+        assert_eq!(get_string(chapter, "type").unwrap(), "chapter");
+        let book = get_parent(chapter).unwrap();
+        assert_eq!(get_string(book, "type").unwrap(), "book");
+        assert_eq!(get_string(book, "title").unwrap(), "Handbook of Hypersonics");
+        assert_eq!(get_string(book, "publisher").unwrap(), "Springer");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let entries = entries();
+        let yaml = write(&entries).unwrap();
+        let parsed = HayagrivaParser::new(&yaml).parse().unwrap();
+        assert_eq!(parsed.len(), entries.len());
+
+        for (original, parsed) in entries.iter().zip(&parsed) {
+            assert_eq!(original.kind, parsed.kind);
+            assert_eq!(original.citekey, parsed.citekey);
+            assert_eq!(original.author(), parsed.author());
+            assert_eq!(original.title(), parsed.title());
+            assert_eq!(original.journal(), parsed.journal());
+            assert_eq!(original.book_title(), parsed.book_title());
+            assert_eq!(original.editor(), parsed.editor());
+            assert_eq!(original.volume(), parsed.volume());
+            assert_eq!(original.number(), parsed.number());
+            assert_eq!(original.pages(), parsed.pages());
+            assert_eq!(original.year(), parsed.year());
+            assert_eq!(original.month(), parsed.month());
+            assert_eq!(original.publisher(), parsed.publisher());
+            assert_eq!(original.address(), parsed.address());
+            assert_eq!(original.doi(), parsed.doi());
+        }
+    }
+
+    #[test]
+    fn test_parse_handwritten() {
+        let input = "
+harry:
+    type: Book
+    title: Harry Potter and the Order of the Phoenix
+    author: Rowling, J. K.
+    volume: 5
+    date: 2003-06-21
+
+wwdc-network:
+    type: Article
+    author:
+        - name: Mehta
+          given-name: Jiten
+        - Kinnear, Eric
+    title: Boost Performance and Security with Modern Networking
+    date: 2020
+    parent:
+        - type: Conference
+          title: World Wide Developer Conference 2020
+        - type: Video
+";
+        let entries = HayagrivaParser::new(input).parse().unwrap();
+        assert_eq!(entries[0].kind, BibTeXEntryKind::Book);
+        assert_eq!(entries[0].author().unwrap().first().unwrap().surname(), "Rowling");
+        assert_eq!(entries[0].day(), Some(&21));
+
+        assert_eq!(entries[1].citekey, "wwdc-network");
+        assert_eq!(entries[1].kind, BibTeXEntryKind::InProceedings);
+        assert_eq!(entries[1].author().unwrap().to_bibtex_names(), "Mehta, Jiten and Kinnear, Eric");
+        assert_eq!(entries[1].book_title().unwrap(), "World Wide Developer Conference 2020");
+        assert_eq!(entries[1].year(), Some(&2020));
+    }
+}
@@ -0,0 +1,228 @@
+//! Conversion between `BibTeXEntry` and the other bibliography formats we
+//! come across, such as RIS exports from publishers
+
+pub mod endnote;
+pub mod hayagriva;
+pub mod nbib;
+pub mod ris;
+
+mod error;
+
+pub use error::FormatError;
+
+use std::collections::{HashMap, HashSet};
+
+use crate::bibtex::entry::BibTeXEntry;
+use crate::bibtex::parser::BibTeXParser;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    BibTeX, 
+    Ris, 
+    EndNoteXml, 
+    Nbib, 
+    Hayagriva, 
+}
+
+impl Format {
+    /// Sniffs the format of a bibliography from its contents rather than 
+    /// trusting the file extension
+    pub fn detect(contents: &str) -> Option<Self> {
+        let contents = contents.trim_start_matches('\u{feff}').trim_start();
+        if contents.starts_with('<') {
+            return contents.contains("<record").then_some(Self::EndNoteXml);
+        }
+        // a YAML directive, not a BibTeX comment
+        if contents.starts_with("%YAML") {
+            return Some(Self::Hayagriva);
+        }
+        if contents.starts_with(['@', '%']) {
+            return Some(Self::BibTeX);
+        }
+
+        // exports often open with a few lines of header before the first record
+        for line in contents.lines() {
+            if nbib::is_record_start(line) {
+                return Some(Self::Nbib);
+            }
+            if ris::is_record_start(line) {
+                return Some(Self::Ris);
+            }
+            if hayagriva::is_record_start(line) {
+                return Some(Self::Hayagriva);
+            }
+        }
+
+        contents.contains('@').then_some(Self::BibTeX)
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BibTeX     => write!(f, "bibtex"), 
+            Self::Ris        => write!(f, "ris"), 
+            Self::EndNoteXml => write!(f, "endnote-xml"), 
+            Self::Nbib       => write!(f, "nbib"), 
+            Self::Hayagriva  => write!(f, "hayagriva"), 
+        }
+    }
+}
+
+/// Detects the format of `contents` and parses it into entries, giving any
+/// entry without a citekey a generated one. Citekeys that clash with each
+/// other or with `taken` are made unique, see [`unique_citekey`]
+pub fn import(contents: &str, taken: &HashSet<String>) -> Result<Vec<BibTeXEntry>, FormatError> {
+    let mut entries = match Format::detect(contents) {
+        Some(Format::BibTeX) => BibTeXParser::new(contents).parse()?,
+        Some(Format::Ris) => ris::RisParser::new(contents).parse()?,
+        Some(Format::EndNoteXml) => endnote::EndNoteXmlParser::new(contents).parse()?,
+        Some(Format::Nbib) => nbib::NbibParser::new(contents).parse()?,
+        Some(Format::Hayagriva) => hayagriva::HayagrivaParser::new(contents).parse()?,
+        None => return Err(FormatError::UnrecognisedFormat),
+    };
+
+    disambiguate_citekeys(&mut entries, taken);
+    Ok(entries)
+}
+
+/// Writes the entries out in the given format
+pub fn export(entries: &[BibTeXEntry], format: Format) -> Result<String, FormatError> {
+    match format {
+        Format::BibTeX => Ok(entries.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n")),
+        Format::Ris => Ok(ris::write(entries)),
+        Format::Hayagriva => hayagriva::write(entries),
+        _ => Err(FormatError::UnsupportedExport(format)),
+    }
+}
+
+/// Strips any resolver url or `doi:` prefix from a DOI
+fn strip_doi_prefix(doi: &str) -> &str {
+    ["https://doi.org/", "http://doi.org/", "https://dx.doi.org/", "http://dx.doi.org/", "doi:"]
+        .iter()
+        .find_map(|prefix| doi.trim().strip_prefix(prefix))
+        .unwrap_or(doi)
+        .trim()
+}
+
+/// Generates a citekey in the form `Surname2004` from the first author (or 
+/// editor) and year, falling back to the first word of the title
+pub fn generate_citekey(entry: &BibTeXEntry) -> String {
+    let name = entry
+        .author()
+        .or(entry.editor())
+        .and_then(|authors| authors.first())
+        .map(|author| author.surname().to_string())
+        .or_else(|| {
+            entry.title()
+                .and_then(|title| title.split_whitespace().next())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| String::from("anon"));
+
+    let mut citekey: String = name.chars().filter(char::is_ascii_alphanumeric).collect();
+    if citekey.is_empty() {
+        citekey.push_str("anon");
+    }
+    if let Some(year) = entry.year() {
+        citekey.push_str(&year.to_string());
+    }
+    citekey
+}
+
+/// The citekey, or if it is already taken the first free one with `a`,
+/// `b`, ... appended
+pub fn unique_citekey(citekey: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(citekey) {
+        return citekey.to_string();
+    }
+    ('a'..='z')
+        .map(|suffix| format!("{citekey}{suffix}"))
+        .chain((2..).map(|n| format!("{citekey}-{n}")))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_else(|| citekey.to_string())
+}
+
+/// Fills in empty citekeys and renames every entry whose citekey clashes
+/// with another entry's or with one in `taken`, so that `Smith2020` twice
+/// becomes `Smith2020a` and `Smith2020b`
+fn disambiguate_citekeys(entries: &mut [BibTeXEntry], taken: &HashSet<String>) {
+    for entry in entries.iter_mut() {
+        if entry.citekey.is_empty() {
+            entry.citekey = generate_citekey(entry);
+        }
+    }
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for entry in entries.iter() {
+        *counts.entry(entry.citekey.clone()).or_default() += 1;
+    }
+
+    // the new citekeys must not clash with any citekey already there either
+    let mut used: HashSet<String> = taken.iter().chain(counts.keys()).cloned().collect();
+    for entry in entries.iter_mut() {
+        if counts[&entry.citekey] < 2 && !taken.contains(&entry.citekey) {
+            continue;
+        }
+        entry.citekey = unique_citekey(&entry.citekey, &used);
+        used.insert(entry.citekey.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Format::detect("@article{key, title = {A}}"), Some(Format::BibTeX));
+        assert_eq!(Format::detect("Provider: Wiley\n\nTY  - JOUR\nER  - \n"), Some(Format::Ris));
+        assert_eq!(Format::detect("\nPMID- 30670592\nTI  - A title\n"), Some(Format::Nbib));
+        assert_eq!(Format::detect("<?xml version=\"1.0\"?><xml><records><record/></records></xml>"), Some(Format::EndNoteXml));
+        assert_eq!(Format::detect("# my library\nkenway2019:\n  type: article\n"), Some(Format::Hayagriva));
+        assert_eq!(Format::detect("%YAML 1.2\n---\nkenway2019:\n  type: article\n"), Some(Format::Hayagriva));
+        assert_eq!(Format::detect("% my library\n@article{key, title = {A}}"), Some(Format::BibTeX));
+        assert_eq!(Format::detect("just some notes"), None);
+    }
+
+    #[test]
+    fn test_import_yaml_directive() {
+        let entries = import("%YAML 1.2\n---\nkenway2019:\n  type: article\n  title: Adjoint\n", &HashSet::new()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].citekey, "kenway2019");
+    }
+
+    #[test]
+    fn test_import_generates_citekeys() {
+        let input = "PMID- 1\nDP  - 2019\nTI  - First.\nFAU - Smith, John\nTA  - Nature\n\n\
+                     PMID- 2\nDP  - 2019\nTI  - Second.\nFAU - Smith, Jane\nTA  - Science\n\n\
+                     PMID- 3\nDP  - 2020\nTI  - Third.\nFAU - Doe, Jane\nTA  - Cell\n";
+        let entries = import(input, &HashSet::new()).unwrap();
+        let citekeys: Vec<&str> = entries.iter().map(|e| e.citekey.as_str()).collect();
+        assert_eq!(citekeys, ["Smith2019a", "Smith2019b", "Doe2020"]);
+
+        let taken = HashSet::from([String::from("Doe2020"), String::from("Smith2019b")]);
+        let entries = import(input, &taken).unwrap();
+        let citekeys: Vec<&str> = entries.iter().map(|e| e.citekey.as_str()).collect();
+        assert_eq!(citekeys, ["Smith2019a", "Smith2019c", "Doe2020a"]);
+    }
+
+    #[test]
+    fn test_many_clashing_citekeys() {
+        // one of the keys to be made is already in the file
+        let mut input: String = (0..30).map(|n| format!("@article{{Smith2020, title = {{Part {n}}}}}\n")).collect();
+        input.push_str("@article{Smith2020c, title = {Other}}\n");
+        let entries = import(&input, &HashSet::new()).unwrap();
+        let citekeys: HashSet<&str> = entries.iter().map(|e| e.citekey.as_str()).collect();
+        assert_eq!(citekeys.len(), 31);
+        assert_eq!(entries[0].citekey, "Smith2020a");
+        assert_eq!(entries[2].citekey, "Smith2020d");
+        assert_eq!(entries[29].citekey, "Smith2020-6");
+        assert_eq!(entries[30].citekey, "Smith2020c");
+    }
+
+    #[test]
+    fn test_import_unrecognised() {
+        assert_eq!(import("just some notes", &HashSet::new()), Err(FormatError::UnrecognisedFormat));
+    }
+}
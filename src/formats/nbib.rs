@@ -0,0 +1,322 @@
+//! Reading of MEDLINE/PubMed `.nbib` files, the "Citation manager" export
+//! from PubMed
+
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::bibtex::entry::{BibTeXEntry, BibTeXEntryKind};
+use crate::bibtex::fields::{Author, Authors, BibTeXField, Month};
+
+use super::error::FormatError;
+
+/// Matches a `PMID- 30670592` style line. Tags are up to four characters,
+/// left aligned and padded before the dash
+static TAG_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([A-Z]{2,4})\s*- (.*)$").expect("valid nbib tag pattern"));
+
+/// Whether the line opens a new record, i.e. is a `PMID` tag
+pub fn is_record_start(line: &str) -> bool {
+    line.starts_with("PMID-")
+}
+
+/// A single MEDLINE record, i.e. a block of tags separated by a blank line
+#[derive(Debug, Default)]
+struct NbibRecord {
+    tags: Vec<(String, String)>,
+}
+
+impl NbibRecord {
+    /// The first value of any of the given tags, in order of preference
+    fn first(&self, tags: &[&str]) -> Option<&str> {
+        tags.iter().find_map(|tag| {
+            self.tags
+                .iter()
+                .find(|(t, value)| t == tag && !value.is_empty())
+                .map(|(_, value)| value.as_str())
+        })
+    }
+
+    /// Every value of the given tag, in the order they appear
+    fn all<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a str> {
+        self.tags
+            .iter()
+            .filter(move |(t, value)| t == tag && !value.is_empty())
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+pub struct NbibParser<'a> {
+    /// The raw input from an nbib file
+    input: &'a str,
+}
+
+impl<'a> NbibParser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        NbibParser { input: input.trim_start_matches('\u{feff}') }
+    }
+
+    /// Parses every record in the input into an entry, leaving the citekeys
+    /// empty to be generated later
+    pub fn parse(&mut self) -> Result<Vec<BibTeXEntry>, FormatError> {
+        let records = self.parse_records()?;
+        Ok(records.iter().map(record_to_entry).collect())
+    }
+
+    fn parse_records(&self) -> Result<Vec<NbibRecord>, FormatError> {
+        let mut records = Vec::new();
+        let mut current: Option<NbibRecord> = None;
+
+        for (index, line) in self.input.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                records.extend(current.take());
+                continue;
+            }
+
+            // long values wrap onto lines indented by six spaces
+            if line.starts_with(' ') {
+                if let Some((_, value)) = current.as_mut().and_then(|r| r.tags.last_mut()) {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+                continue;
+            }
+
+            let Some(captures) = TAG_LINE.captures(line) else {
+                continue;
+            };
+            let tag = captures[1].to_string();
+            let value = captures[2].trim().to_string();
+
+            match current.as_mut() {
+                Some(_) if tag == "PMID" => {
+                    records.extend(current.take());
+                    current = Some(NbibRecord { tags: vec![(tag, value)] });
+                }
+                Some(record) => record.tags.push((tag, value)),
+                None if tag == "PMID" => current = Some(NbibRecord { tags: vec![(tag, value)] }),
+                None => {
+                    return Err(FormatError::MissingTag { line: index + 1, tag: "PMID" })
+                }
+            }
+        }
+        records.extend(current);
+
+        Ok(records)
+    }
+}
+
+fn record_to_entry(record: &NbibRecord) -> BibTeXEntry {
+    let kind = kind_from_publication_types(record);
+    let mut entry = BibTeXEntry::new(kind, String::new());
+
+    // `FAU` has the full forenames, `AU` only the initials
+    let full_names: Vec<&str> = record.all("FAU").collect();
+    let names = if full_names.is_empty() {
+        record.all("AU").map(initials_to_bibtex_name).collect()
+    } else {
+        full_names.iter().map(|name| name.to_string()).collect::<Vec<_>>()
+    };
+    let authors: Vec<Author> = names
+        .iter()
+        .filter_map(|name| Author::from_str(name).ok())
+        .collect();
+    if !authors.is_empty() {
+        entry.add_field(BibTeXField::Author(Authors::new(authors)));
+    }
+
+    if let Some(title) = record.first(&["TI", "BTI"]) {
+        entry.add_field(BibTeXField::Title(title.trim_end_matches('.').to_string()));
+    }
+    if let Some(journal) = record.first(&["JT", "TA"]) {
+        entry.add_field(BibTeXField::Journal(journal.to_string()));
+    }
+    if let Some(volume) = record.first(&["VI"]) {
+        entry.add_field(BibTeXField::from_key_value("volume", volume));
+    }
+    if let Some(issue) = record.first(&["IP"]) {
+        entry.add_field(BibTeXField::from_key_value("number", issue));
+    }
+    if let Some(pages) = record.first(&["PG"]) {
+        entry.add_field(BibTeXField::from_key_value("pages", &expand_page_range(pages)));
+    }
+    if let Some(publisher) = record.first(&["PB"]) {
+        entry.add_field(BibTeXField::Publisher(publisher.to_string()));
+    }
+
+    // e.g. "2019 Mar 15", "2019 Mar-Apr" or "2019"
+    if let Some(date) = record.first(&["DP"]) {
+        let mut parts = date.split_whitespace();
+        if let Some(year) = parts.next().and_then(|y| y.parse().ok()) {
+            entry.add_field(BibTeXField::Year(year));
+        }
+        let month = parts
+            .next()
+            .and_then(|m| m.split('-').next())
+            .and_then(|m| Month::from_str(m).ok());
+        if let Some(month) = month {
+            entry.add_field(BibTeXField::Month(month as u8 + 1));
+        }
+    }
+
+    let doi = record
+        .all("AID")
+        .chain(record.all("LID"))
+        .find_map(|id| id.strip_suffix("[doi]"));
+    if let Some(doi) = doi {
+        entry.add_field(BibTeXField::Doi(doi.trim().to_string()));
+    }
+
+    let non_standard = [
+        ("abstract", record.first(&["AB"])),
+        ("pmid", record.first(&["PMID"])),
+        ("issn", record.first(&["IS"]).map(|issn| issn.split(' ').next().unwrap_or(issn))),
+        ("language", record.first(&["LA"])),
+    ];
+    for (key, value) in non_standard {
+        if let Some(value) = value {
+            entry.add_field(BibTeXField::NonStandard { key: key.to_string(), value: value.to_string() });
+        }
+    }
+
+    let mut keywords: Vec<String> = Vec::new();
+    for keyword in record.all("MH").map(mesh_descriptor).chain(record.all("OT").map(str::to_string)) {
+        if !keywords.contains(&keyword) {
+            keywords.push(keyword);
+        }
+    }
+    if !keywords.is_empty() {
+        entry.add_field(BibTeXField::NonStandard {
+            key: String::from("keywords"),
+            value: keywords.join(", "),
+        });
+    }
+
+    entry
+}
+
+fn kind_from_publication_types(record: &NbibRecord) -> BibTeXEntryKind {
+    let types: Vec<String> = record.all("PT").map(str::to_lowercase).collect();
+    let chapter_of_book = record.first(&["BTI"]).is_some() && record.first(&["TI"]).is_some();
+    if types.iter().any(|t| t == "book chapter") || chapter_of_book {
+        BibTeXEntryKind::InCollection
+    } else if types.iter().any(|t| t == "book") {
+        BibTeXEntryKind::Book
+    } else if record.first(&["JT", "TA"]).is_some() {
+        BibTeXEntryKind::Article
+    } else {
+        BibTeXEntryKind::Misc
+    }
+}
+
+/// Turns the `AU` form "Smith JA" into "Smith, J. A."
+fn initials_to_bibtex_name(name: &str) -> String {
+    match name.rsplit_once(' ') {
+        Some((surname, initials)) if initials.chars().all(|c| c.is_ascii_uppercase()) => {
+            let initials: Vec<String> = initials.chars().map(|c| format!("{c}.")).collect();
+            format!("{}, {}", surname, initials.join(" "))
+        }
+        _ => format!("{{{}}}", name),
+    }
+}
+
+/// Strips the major topic marker and any subheadings from a MeSH heading,
+/// e.g. "*Adenosine/metabolism" becomes "Adenosine"
+fn mesh_descriptor(heading: &str) -> String {
+    let descriptor = heading.split('/').next().unwrap_or(heading);
+    descriptor.trim_start_matches('*').trim().to_string()
+}
+
+/// MEDLINE abbreviates the end page, e.g. "1123-45" for "1123-1145"
+fn expand_page_range(pages: &str) -> String {
+    let Some((start, end)) = pages.split_once('-') else {
+        return pages.to_string();
+    };
+    let (start, end) = (start.trim(), end.trim());
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    if digits(start) && digits(end) && end.len() < start.len() {
+        let prefix = &start[..start.len() - end.len()];
+        format!("{start}-{prefix}{end}")
+    } else {
+        format!("{start}-{end}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "PMID- 30670592
+OWN - NLM
+STAT- MEDLINE
+DP  - 2019 Mar 15
+TI  - Adenosine signalling in the regulation of cardiac fibroblast
+      proliferation.
+PG  - 4012-25
+LID - 10.1074/jbc.RA118.006 [doi]
+AB  - Adenosine is an important signalling molecule.
+FAU - Smith, John Andrew
+AU  - Smith JA
+FAU - van der Berg, Jan
+AU  - van der Berg J
+LA  - eng
+PT  - Journal Article
+TA  - J Biol Chem
+JT  - The Journal of biological chemistry
+IS  - 1083-351X (Electronic)
+VI  - 294
+IP  - 11
+MH  - Humans
+MH  - *Adenosine/metabolism
+MH  - Adenosine/pharmacology
+OT  - fibroblast
+
+PMID- 12345678
+DP  - 2001
+TI  - A second record.
+AU  - Doe J
+TA  - Nature
+";
+
+    #[test]
+    fn test_medline_record() {
+        let entries = NbibParser::new(SAMPLE).parse().unwrap();
+        assert_eq!(entries.len(), 2);
+        let entry = &entries[0];
+
+        assert_eq!(entry.kind, BibTeXEntryKind::Article);
+        assert_eq!(entry.title().unwrap(), "Adenosine signalling in the regulation of cardiac fibroblast proliferation");
+        assert_eq!(entry.author().unwrap().to_bibtex_names(), "Smith, John Andrew and van der Berg, Jan");
+        assert_eq!(entry.journal().unwrap(), "The Journal of biological chemistry");
+        assert_eq!(entry.volume(), Some(&294));
+        assert_eq!(entry.number(), Some(&11));
+        assert_eq!(entry.pages().unwrap().to_string(), "4012--4025");
+        assert_eq!(entry.year(), Some(&2019));
+        assert_eq!(entry.month(), Some(&3));
+        assert_eq!(entry.doi().unwrap(), "10.1074/jbc.RA118.006");
+        assert_eq!(entry.non_standard_field("pmid").unwrap(), "30670592");
+        assert_eq!(entry.non_standard_field("issn").unwrap(), "1083-351X");
+        assert_eq!(entry.non_standard_field("keywords").unwrap(), "Humans, Adenosine, fibroblast");
+    }
+
+    #[test]
+    fn test_initials_only() {
+        let entries = NbibParser::new(SAMPLE).parse().unwrap();
+        let author = entries[1].author().unwrap();
+        assert_eq!(author.first().unwrap().surname(), "Doe");
+        assert_eq!(author.first().unwrap().forename(), "J.");
+        assert_eq!(entries[1].journal().unwrap(), "Nature");
+    }
+
+    #[test]
+    fn test_expand_page_range() {
+        assert_eq!(expand_page_range("1123-45"), "1123-1145");
+        assert_eq!(expand_page_range("e1234"), "e1234");
+        assert_eq!(expand_page_range("12-134"), "12-134");
+        assert_eq!(expand_page_range("5-123"), "5-123");
+        assert_eq!(expand_page_range("é12-3"), "é12-3");
+        assert_eq!(expand_page_range("S1é-2"), "S1é-2");
+    }
+}
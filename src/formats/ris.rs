@@ -0,0 +1,584 @@
+//! Reading and writing of RIS files, as exported by publishers, Web of
+//! Science and EndNote
+
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::bibtex::entry::{BibTeXEntry, BibTeXEntryKind};
+use crate::bibtex::fields::{Author, Authors, BibTeXField};
+
+use super::error::FormatError;
+use super::strip_doi_prefix;
+
+/// Matches a single `TY  - JOUR` style line. Some exporters drop the space
+/// after the dash on empty values (`ER  -`) or use a single space before it
+static TAG_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([A-Z][A-Z0-9])\s{1,2}-(?:\s(.*))?$").expect("valid RIS tag pattern"));
+
+/// Whether the line opens a new record, i.e. is a `TY` tag
+pub fn is_record_start(line: &str) -> bool {
+    TAG_LINE
+        .captures(line.trim_end())
+        .is_some_and(|captures| &captures[1] == "TY")
+}
+
+/// A single RIS record, i.e. everything between a `TY` and an `ER` tag
+#[derive(Debug, Default)]
+struct RisRecord {
+    tags: Vec<(String, String)>,
+}
+
+impl RisRecord {
+    /// The first value of any of the given tags, in order of preference
+    fn first(&self, tags: &[&str]) -> Option<&str> {
+        tags.iter().find_map(|tag| {
+            self.tags
+                .iter()
+                .find(|(t, value)| t == tag && !value.is_empty())
+                .map(|(_, value)| value.as_str())
+        })
+    }
+
+    /// Every value of any of the given tags, in the order they appear
+    fn all(&self, tags: &[&str]) -> Vec<&str> {
+        self.tags
+            .iter()
+            .filter(|(t, value)| tags.contains(&t.as_str()) && !value.is_empty())
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+}
+
+pub struct RisParser<'a> {
+    /// The raw input from a RIS file
+    input: &'a str,
+}
+
+impl<'a> RisParser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        RisParser { input: input.trim_start_matches('\u{feff}') }
+    }
+
+    /// Parses every record in the input into an entry. Entries without an
+    /// `ID` tag are left with an empty citekey
+    pub fn parse(&mut self) -> Result<Vec<BibTeXEntry>, FormatError> {
+        let records = self.parse_records()?;
+        Ok(records.iter().map(record_to_entry).collect())
+    }
+
+    fn parse_records(&self) -> Result<Vec<RisRecord>, FormatError> {
+        let mut records = Vec::new();
+        let mut current: Option<RisRecord> = None;
+
+        // `lines` also strips the `\r` of CRLF line endings
+        for (index, line) in self.input.lines().enumerate() {
+            let line = line.trim_end();
+            let Some(captures) = TAG_LINE.captures(line) else {
+                // untagged lines continue the previous value, e.g. wrapped
+                // abstracts, and are ignored between records
+                if let Some((_, value)) = current.as_mut().and_then(|r| r.tags.last_mut()) {
+                    if !line.trim().is_empty() {
+                        if !value.is_empty() {
+                            value.push(' ');
+                        }
+                        value.push_str(line.trim());
+                    }
+                }
+                continue;
+            };
+
+            let tag = captures[1].to_string();
+            let value = captures.get(2).map_or("", |m| m.as_str()).trim().to_string();
+
+            match (tag.as_str(), current.as_mut()) {
+                ("TY", _) => {
+                    // a new record without an `ER` for the previous one
+                    records.extend(current.take());
+                    current = Some(RisRecord { tags: vec![(tag, value)] });
+                }
+                ("ER", Some(_)) => records.extend(current.take()),
+                (_, Some(record)) => record.tags.push((tag, value)),
+                (_, None) => {
+                    return Err(FormatError::MissingTag { line: index + 1, tag: "TY" })
+                }
+            }
+        }
+        records.extend(current);
+
+        Ok(records)
+    }
+}
+
+fn record_to_entry(record: &RisRecord) -> BibTeXEntry {
+    let kind = kind_from_ris(record);
+    let citekey = record.first(&["ID"]).unwrap_or_default().to_string();
+    let mut entry = BibTeXEntry::new(kind, citekey);
+
+    let authors = parse_names(&record.all(&["AU", "A1"]));
+    let editors = parse_names(&record.all(&["ED", "A2"]));
+    if let Some(authors) = authors {
+        entry.add_field(BibTeXField::Author(authors));
+    }
+    if let Some(editors) = editors {
+        entry.add_field(BibTeXField::Editor(editors));
+    }
+
+    if let Some(title) = record.first(&["TI", "T1", "CT"]) {
+        entry.add_field(BibTeXField::Title(title.to_string()));
+    }
+
+    // `T2` is the secondary title, which is the journal for an article but
+    // the book or proceedings title for everything else
+    match entry.kind {
+        BibTeXEntryKind::Article => {
+            if let Some(journal) = record.first(&["JF", "JO", "T2", "JA", "J2"]) {
+                entry.add_field(BibTeXField::Journal(journal.to_string()));
+            }
+        }
+        _ => {
+            if let Some(book_title) = record.first(&["BT", "T2"]) {
+                entry.add_field(BibTeXField::BookTitle(book_title.to_string()));
+            }
+        }
+    }
+
+    let simple_fields = [
+        (&["VL"][..], "volume"),
+        (&["IS", "CP"][..], "number"),
+        (&["ET"][..], "edition"),
+        (&["CY", "PP"][..], "address"),
+        (&["T3"][..], "series"),
+    ];
+    for (tags, key) in simple_fields {
+        if let Some(value) = record.first(tags) {
+            entry.add_field(BibTeXField::from_key_value(key, value));
+        }
+    }
+
+    match entry.kind {
+        BibTeXEntryKind::PhdThesis | BibTeXEntryKind::MasterThesis => {
+            if let Some(school) = record.first(&["PB"]) {
+                entry.add_field(BibTeXField::School(school.to_string()));
+            }
+        }
+        BibTeXEntryKind::TechReport => {
+            if let Some(institution) = record.first(&["PB"]) {
+                entry.add_field(BibTeXField::Institution(institution.to_string()));
+            }
+        }
+        _ => {
+            if let Some(publisher) = record.first(&["PB"]) {
+                entry.add_field(BibTeXField::Publisher(publisher.to_string()));
+            }
+        }
+    }
+
+    if let Some(pages) = ris_pages(record) {
+        entry.add_field(BibTeXField::from_key_value("pages", &pages));
+    }
+
+    let date = RisDate::from_record(record);
+    if let Some(year) = date.year {
+        entry.add_field(BibTeXField::Year(year));
+    }
+    if let Some(month) = date.month {
+        entry.add_field(BibTeXField::Month(month));
+    }
+    if let Some(day) = date.day {
+        entry.add_field(BibTeXField::Day(day));
+    }
+
+    if let Some(doi) = record.first(&["DO"]) {
+        entry.add_field(BibTeXField::Doi(strip_doi_prefix(doi).to_string()));
+    }
+    if let Some(note) = record.first(&["N1"]) {
+        entry.add_field(BibTeXField::Note(note.to_string()));
+    }
+
+    let non_standard = [
+        (&["UR"][..], "url"),
+        (&["AB", "N2"][..], "abstract"),
+        (&["SN"][..], if entry.kind == BibTeXEntryKind::Book { "isbn" } else { "issn" }),
+        (&["LA"][..], "language"),
+        (&["L1"][..], "file"),
+    ];
+    for (tags, key) in non_standard {
+        if let Some(value) = record.first(tags) {
+            entry.add_field(BibTeXField::NonStandard { key: key.to_string(), value: value.to_string() });
+        }
+    }
+
+    let keywords = record.all(&["KW"]);
+    if !keywords.is_empty() {
+        entry.add_field(BibTeXField::NonStandard {
+            key: String::from("keywords"),
+            value: keywords.join(", "),
+        });
+    }
+
+    entry
+}
+
+fn kind_from_ris(record: &RisRecord) -> BibTeXEntryKind {
+    let ty = record.first(&["TY"]).unwrap_or_default().to_uppercase();
+    match ty.as_str() {
+        "JOUR" | "JFULL" | "MGZN" | "NEWS" | "EJOUR" => BibTeXEntryKind::Article,
+        "BOOK" | "EBOOK" | "EDBOOK"                  => BibTeXEntryKind::Book,
+        "CHAP" | "ECHAP"                             => BibTeXEntryKind::InCollection,
+        "CPAPER"                                     => BibTeXEntryKind::InProceedings,
+        // plenty of exporters use `CONF` for a paper within the proceedings
+        "CONF" if record.first(&["AU", "A1"]).is_some() => BibTeXEntryKind::InProceedings,
+        "CONF"                                       => BibTeXEntryKind::Proceedings,
+        "PAMP"                                       => BibTeXEntryKind::Booklet,
+        "RPRT" | "GOVDOC"                            => BibTeXEntryKind::TechReport,
+        "STAND" | "COMP"                             => BibTeXEntryKind::Manual,
+        "UNPB" | "MANSCPT"                           => BibTeXEntryKind::Unpublished,
+        "THES" => {
+            let thesis_type = record.first(&["M3"]).unwrap_or_default().to_lowercase();
+            if thesis_type.contains("master") {
+                BibTeXEntryKind::MasterThesis
+            } else {
+                BibTeXEntryKind::PhdThesis
+            }
+        }
+        _ => BibTeXEntryKind::Misc,
+    }
+}
+
+fn kind_to_ris(kind: &BibTeXEntryKind) -> &'static str {
+    match kind {
+        BibTeXEntryKind::Article       => "JOUR",
+        BibTeXEntryKind::Book          => "BOOK",
+        BibTeXEntryKind::Booklet       => "PAMP",
+        BibTeXEntryKind::Conference    => "CPAPER",
+        BibTeXEntryKind::Inbook        => "CHAP",
+        BibTeXEntryKind::InCollection  => "CHAP",
+        BibTeXEntryKind::InProceedings => "CPAPER",
+        BibTeXEntryKind::Manual        => "STAND",
+        BibTeXEntryKind::MasterThesis  => "THES",
+        BibTeXEntryKind::PhdThesis     => "THES",
+        BibTeXEntryKind::Proceedings   => "CONF",
+        BibTeXEntryKind::TechReport    => "RPRT",
+        BibTeXEntryKind::Unpublished   => "UNPB",
+        BibTeXEntryKind::Misc          => "GEN",
+        BibTeXEntryKind::Unknown       => "GEN",
+    }
+}
+
+fn parse_names(names: &[&str]) -> Option<Authors> {
+    let authors: Vec<Author> = names
+        .iter()
+        .filter_map(|name| Author::from_str(name).ok())
+        .collect();
+    (!authors.is_empty()).then(|| Authors::new(authors))
+}
+
+/// Joins `SP` and `EP`, handling exporters that put the whole range in `SP`
+fn ris_pages(record: &RisRecord) -> Option<String> {
+    let start = record.first(&["SP"])?;
+    match record.first(&["EP"]) {
+        Some(end) if !start.contains(['-', '\u{2013}']) => Some(format!("{start}--{end}")),
+        _ => Some(start.to_string()),
+    }
+}
+
+/// The publication date, taken from `PY`/`Y1` (`2015///`, `2015/03/12/`)
+/// and `DA` (`2015/03/12`)
+#[derive(Debug, Default, PartialEq)]
+struct RisDate {
+    year: Option<u16>,
+    month: Option<u8>,
+    day: Option<u8>,
+}
+
+impl RisDate {
+    fn from_record(record: &RisRecord) -> Self {
+        let primary = record.first(&["PY", "Y1"]).map(Self::parse).unwrap_or_default();
+        let secondary = record.first(&["DA", "Y2"]).map(Self::parse).unwrap_or_default();
+
+        Self {
+            year: primary.year.or(secondary.year),
+            month: primary.month.or(secondary.month),
+            day: primary.day.or(secondary.day),
+        }
+    }
+
+    fn parse(s: &str) -> Self {
+        let mut parts = s.split(['/', '-']).map(str::trim);
+        let year = parts.next().and_then(|y| y.get(..4)).and_then(|y| y.parse().ok());
+        let month = parts.next().and_then(|m| m.parse().ok()).filter(|m| (1..=12).contains(m));
+        let day = parts.next().and_then(|d| d.parse().ok()).filter(|d| (1..=31).contains(d));
+        Self { year, month, day }
+    }
+}
+
+/// Writes the entries out as a RIS file
+pub fn write(entries: &[BibTeXEntry]) -> String {
+    entries.iter().map(write_entry).collect()
+}
+
+/// Writes a single entry out as a RIS record
+pub fn write_entry(entry: &BibTeXEntry) -> String {
+    let mut record = RisRecord::default();
+    let mut push = |tag: &str, value: String| record.tags.push((tag.to_string(), value));
+
+    push("TY", kind_to_ris(&entry.kind).to_string());
+    if !entry.citekey.is_empty() {
+        push("ID", entry.citekey.clone());
+    }
+    for author in entry.author().into_iter().flat_map(Authors::iter) {
+        push("AU", author.to_bibtex_name());
+    }
+    for editor in entry.editor().into_iter().flat_map(Authors::iter) {
+        push("ED", editor.to_bibtex_name());
+    }
+    if let Some(title) = entry.title() {
+        push("TI", title.clone());
+    }
+    if let Some(secondary) = entry.journal().or(entry.book_title()) {
+        push("T2", secondary.clone());
+    }
+    if let Some(series) = entry.series() {
+        push("T3", series.clone());
+    }
+    if let Some(volume) = entry.volume() {
+        push("VL", volume.to_string());
+    }
+    if let Some(number) = entry.number() {
+        push("IS", number.to_string());
+    }
+    if let Some(pages) = entry.pages() {
+        if let Some(first) = pages.first_page() {
+            push("SP", first.to_string());
+        }
+        if let Some(last) = pages.last_page().filter(|&last| Some(last) != pages.first_page()) {
+            push("EP", last.to_string());
+        }
+    } else if let Some(pages) = entry.non_standard_field("pages") {
+        // pages that are not numbers, e.g. `e1234` or `A12--A19`
+        let mut range = pages.splitn(2, ['-', '\u{2013}']).map(|page| page.trim_matches(['-', '\u{2013}', ' ']));
+        if let Some(first) = range.next().filter(|first| !first.is_empty()) {
+            push("SP", first.to_string());
+        }
+        if let Some(last) = range.next().filter(|last| !last.is_empty()) {
+            push("EP", last.to_string());
+        }
+    }
+    if let Some(edition) = entry.edition() {
+        push("ET", edition.to_string());
+    }
+    if let Some(year) = entry.year() {
+        push("PY", year.to_string());
+        if let Some(month) = entry.month() {
+            let day = entry.day().map(|d| format!("{d:02}")).unwrap_or_default();
+            push("DA", format!("{year}/{month:02}/{day}"));
+        }
+    }
+    if matches!(entry.kind, BibTeXEntryKind::MasterThesis) {
+        push("M3", String::from("Master's thesis"));
+    }
+    let publisher = entry
+        .publisher()
+        .or(entry.school())
+        .or(entry.institution());
+    if let Some(publisher) = publisher {
+        push("PB", publisher.clone());
+    }
+    if let Some(address) = entry.address() {
+        push("CY", address.clone());
+    }
+    for key in ["isbn", "issn"] {
+        if let Some(value) = entry.non_standard_field(key) {
+            push("SN", value.clone());
+        }
+    }
+    if let Some(doi) = entry.doi() {
+        push("DO", doi.clone());
+    }
+    if let Some(url) = entry.non_standard_field("url") {
+        push("UR", url.clone());
+    }
+    if let Some(abstract_) = entry.non_standard_field("abstract") {
+        push("AB", abstract_.clone());
+    }
+    if let Some(keywords) = entry.non_standard_field("keywords") {
+        for keyword in keywords.split([',', ';']).map(str::trim).filter(|k| !k.is_empty()) {
+            push("KW", keyword.to_string());
+        }
+    }
+    if let Some(note) = entry.note() {
+        push("N1", note.clone());
+    }
+    if let Some(language) = entry.non_standard_field("language") {
+        push("LA", language.clone());
+    }
+    if let Some(file) = entry.non_standard_field("file") {
+        push("L1", file.clone());
+    }
+
+    let mut output = String::new();
+    for (tag, value) in &record.tags {
+        output.push_str(&format!("{tag}  - {value}\n"));
+    }
+    output.push_str("ER  - \n\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "TY  - JOUR
+ID  - Kenway2019
+AU  - Kenway, Gaetan K. W.
+AU  - Mader, Charles A.
+AU  - He, Ping
+AU  - Martins, Joaquim R. R. A.
+TI  - Effective adjoint approaches for computational fluid dynamics
+T2  - Progress in Aerospace Sciences
+VL  - 110
+SP  - 100542
+PY  - 2019
+DA  - 2019/10/01
+PB  - Elsevier
+DO  - 10.1016/j.paerosci.2019.05.002
+AB  - The adjoint method is used for high-fidelity aerodynamic shape optimization.
+KW  - adjoint
+KW  - cfd
+ER  - \n
+";
+
+    fn parse(input: &str) -> Vec<BibTeXEntry> {
+        RisParser::new(input).parse().unwrap()
+    }
+
+    #[test]
+    fn test_journal_article() {
+        let entries = parse(SAMPLE);
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+
+        assert_eq!(entry.kind, BibTeXEntryKind::Article);
+        assert_eq!(entry.citekey, "Kenway2019");
+        let authors = entry.author().unwrap();
+        assert_eq!(authors.len(), 4);
+        assert_eq!(authors.first().unwrap().surname(), "Kenway");
+        assert_eq!(authors.first().unwrap().forename(), "Gaetan K. W.");
+        assert_eq!(entry.journal().unwrap(), "Progress in Aerospace Sciences");
+        assert_eq!(entry.volume(), Some(&110));
+        assert_eq!(entry.pages().unwrap().to_string(), "100542");
+        assert_eq!(entry.year(), Some(&2019));
+        assert_eq!(entry.month(), Some(&10));
+        assert_eq!(entry.day(), Some(&1));
+        assert_eq!(entry.doi().unwrap(), "10.1016/j.paerosci.2019.05.002");
+        assert_eq!(entry.non_standard_field("keywords").unwrap(), "adjoint, cfd");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let entries = parse(SAMPLE);
+        let written = write(&entries);
+        assert_eq!(written, SAMPLE);
+        assert_eq!(parse(&written), entries);
+    }
+
+    #[test]
+    fn test_round_trip_from_entry() {
+        let mut entry = BibTeXEntry::new(BibTeXEntryKind::InProceedings, String::from("Smith2020"));
+        entry.add_field(BibTeXField::from_key_value("author", "van der Berg, Jan and Smith, Jr, John"));
+        entry.add_field(BibTeXField::Title(String::from("Shock boundary layer interaction")));
+        entry.add_field(BibTeXField::BookTitle(String::from("AIAA SciTech Forum")));
+        entry.add_field(BibTeXField::from_key_value("pages", "12--19"));
+        entry.add_field(BibTeXField::Year(2020));
+
+        let entries = parse(&write_entry(&entry));
+        assert_eq!(entries, vec![entry]);
+    }
+
+    #[test]
+    fn test_non_numeric_pages() {
+        let mut entry = BibTeXEntry::new(BibTeXEntryKind::Article, String::from("Doe2021"));
+        entry.add_field(BibTeXField::from_key_value("pages", "A12--A19"));
+        assert!(write_entry(&entry).contains("SP  - A12\nEP  - A19\n"));
+        assert_eq!(parse(&write_entry(&entry)), vec![entry]);
+
+        let mut entry = BibTeXEntry::new(BibTeXEntryKind::Article, String::from("Doe2022"));
+        entry.add_field(BibTeXField::from_key_value("pages", "e1234"));
+        assert!(write_entry(&entry).contains("SP  - e1234\nER"));
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let entries = parse(&SAMPLE.replace('\n', "\r\n"));
+        assert_eq!(entries, parse(SAMPLE));
+        assert_eq!(entries[0].title().unwrap(), "Effective adjoint approaches for computational fluid dynamics");
+    }
+
+    #[test]
+    fn test_repeated_tags() {
+        let input = "TY  - JOUR
+TI  - The first title
+TI  - A duplicated title
+A1  - Doe, Jane
+AU  - Roe, Richard
+KW  - one
+KW  - two
+KW  - three
+JA  - J. Fluid Mech.
+JO  - Journal of Fluid Mechanics
+ER  -
+";
+        let entry = &parse(input)[0];
+        assert_eq!(entry.title().unwrap(), "The first title");
+        assert_eq!(entry.author().unwrap().to_bibtex_names(), "Doe, Jane and Roe, Richard");
+        assert_eq!(entry.journal().unwrap(), "Journal of Fluid Mechanics");
+        assert_eq!(entry.non_standard_field("keywords").unwrap(), "one, two, three");
+    }
+
+    #[test]
+    fn test_publisher_quirks() {
+        // a byte order mark, a header line, a wrapped abstract, a page range
+        // crammed into `SP`, a doi url and no final `ER`
+        let input = "\u{feff}Provider: Example Publisher\r
+\r
+TY  - CONF\r
+AU  - Doe, J.\r
+T1  - Wall-modelled LES\r
+BT  - Proceedings of the Summer Program\r
+SP  - 101-112\r
+Y1  - 2016///\r
+DO  - https://doi.org/10.1000/xyz\r
+N2  - The first line of the abstract\r
+  and its continuation.\r
+";
+        let entry = &parse(input)[0];
+        assert_eq!(entry.kind, BibTeXEntryKind::InProceedings);
+        assert_eq!(entry.citekey, "");
+        assert_eq!(entry.book_title().unwrap(), "Proceedings of the Summer Program");
+        assert_eq!(entry.pages().unwrap().to_string(), "101--112");
+        assert_eq!(entry.year(), Some(&2016));
+        assert_eq!(entry.month(), None);
+        assert_eq!(entry.doi().unwrap(), "10.1000/xyz");
+        assert_eq!(
+            entry.non_standard_field("abstract").unwrap(),
+            "The first line of the abstract and its continuation."
+        );
+    }
+
+    #[test]
+    fn test_multiple_records_without_er() {
+        let input = "TY  - BOOK\nTI  - First\nTY  - THES\nTI  - Second\nM3  - Master's thesis\nER  - \n";
+        let entries = parse(input);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].kind, BibTeXEntryKind::Book);
+        assert_eq!(entries[1].kind, BibTeXEntryKind::MasterThesis);
+    }
+
+    #[test]
+    fn test_tag_outside_record() {
+        let result = RisParser::new("TI  - No type\nER  - \n").parse();
+        assert_eq!(result, Err(FormatError::MissingTag { line: 1, tag: "TY" }));
+    }
+}
@@ -0,0 +1,141 @@
+//! Library-wide statistics for `litrev insights`: the most frequent
+//! authors and keywords, and the number of entries per year
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::bibtex::entry::BibTeXEntry;
+use crate::bibtex::fields::Author;
+use crate::output::SCHEMA_VERSION;
+use crate::style::case::strip_braces;
+
+/// The longest bar drawn in the timeline
+const TIMELINE_WIDTH: usize = 40;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Count {
+    pub name: String, 
+    pub count: usize, 
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct YearCount {
+    pub year: u16, 
+    pub count: usize, 
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Insights {
+    pub schema_version: u32, 
+    pub entries: usize, 
+    pub top_authors: Vec<Count>, 
+    pub top_keywords: Vec<Count>, 
+    /// Entries per year, oldest first, leaving out entries without a year
+    pub timeline: Vec<YearCount>, 
+}
+
+impl Insights {
+    /// Gathers the statistics, keeping the `limit` most frequent authors
+    /// and keywords
+    pub fn new(entries: &[BibTeXEntry], limit: usize) -> Self {
+        let mut authors: HashMap<String, usize> = HashMap::new();
+        let mut keywords: HashMap<String, usize> = HashMap::new();
+        let mut timeline: BTreeMap<u16, usize> = BTreeMap::new();
+
+        for entry in entries {
+            for author in entry.author().into_iter().flat_map(|authors| authors.iter()) {
+                *authors.entry(author_key(author)).or_default() += 1;
+            }
+            let entry_keywords = entry.non_standard_field("keywords").into_iter().flat_map(|k| k.split([',', ';']));
+            for keyword in entry_keywords {
+                let keyword = strip_braces(keyword.trim()).to_lowercase();
+                if !keyword.is_empty() {
+                    *keywords.entry(keyword).or_default() += 1;
+                }
+            }
+            if let Some(&year) = entry.year() {
+                *timeline.entry(year).or_default() += 1;
+            }
+        }
+
+        Self {
+            schema_version: SCHEMA_VERSION,
+            entries: entries.len(),
+            top_authors: most_frequent(authors, limit),
+            top_keywords: most_frequent(keywords, limit),
+            timeline: timeline.into_iter().map(|(year, count)| YearCount { year, count }).collect(),
+        }
+    }
+
+    /// Writes the statistics as plain text, with the timeline as a bar chart
+    pub fn write_text(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "{} entries", self.entries)?;
+
+        for (heading, counts) in [("Top authors", &self.top_authors), ("Top keywords", &self.top_keywords)] {
+            if counts.is_empty() {
+                continue;
+            }
+            writeln!(out, "\n{heading}")?;
+            let width = counts.iter().map(|c| c.name.chars().count()).max().unwrap_or(0);
+            for count in counts {
+                let padding = " ".repeat(width - count.name.chars().count());
+                writeln!(out, "  {}{padding}  {}", count.name, count.count)?;
+            }
+        }
+
+        if !self.timeline.is_empty() {
+            writeln!(out, "\nPublication timeline")?;
+            let most = self.timeline.iter().map(|y| y.count).max().unwrap_or(1);
+            for year in &self.timeline {
+                let length = (year.count * TIMELINE_WIDTH).div_ceil(most);
+                writeln!(out, "  {}  {} {}", year.year, "\u{2588}".repeat(length), year.count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Authors are counted by surname and first initial, so "Kenway, G." and
+/// "Kenway, Gaetan" are the same person
+fn author_key(author: &Author) -> String {
+    let surname = match author.prefix() {
+        Some(prefix) => format!("{} {}", prefix, author.surname()),
+        None => author.surname().to_string(),
+    };
+    match strip_braces(author.forename()).chars().find(|c| c.is_alphabetic()) {
+        Some(initial) => format!("{}, {initial}.", strip_braces(&surname)),
+        None => strip_braces(&surname),
+    }
+}
+
+/// The `limit` most frequent names, ties broken alphabetically
+fn most_frequent(counts: HashMap<String, usize>, limit: usize) -> Vec<Count> {
+    let mut counts: Vec<Count> = counts.into_iter().map(|(name, count)| Count { name, count }).collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    counts.truncate(limit);
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibtex::parser::BibTeXParser;
+
+    #[test]
+    fn test_insights() {
+        let input = r#"
+            @article{a, author = {Kenway, Gaetan and Martins, Joaquim}, year = {2019}, keywords = {CFD, adjoint}}
+            @article{b, author = {Kenway, G. K. W.}, year = {2019}, keywords = {cfd}}
+            @article{c, author = {Martins, J. R. R. A.}, year = {2021}}
+        "#;
+        let entries = BibTeXParser::new(input).parse().unwrap();
+        let insights = Insights::new(&entries, 1);
+
+        assert_eq!(insights.entries, 3);
+        assert_eq!(insights.top_authors, [Count { name: String::from("Kenway, G."), count: 2 }]);
+        assert_eq!(insights.top_keywords, [Count { name: String::from("cfd"), count: 2 }]);
+        assert_eq!(insights.timeline, [YearCount { year: 2019, count: 2 }, YearCount { year: 2021, count: 1 }]);
+    }
+}
@@ -1 +1,13 @@
-mod bibtex;
\ No newline at end of file
+pub mod bibtex;
+pub mod files;
+pub mod formats;
+pub mod insights;
+pub mod link;
+pub mod notes;
+pub mod output;
+pub mod pdf;
+pub mod project;
+pub mod references;
+pub mod search;
+pub mod store;
+pub mod style;
@@ -0,0 +1,458 @@
+//! Linking entries to their PDFs. Each entry is tried against a series of
+//! signals, from the most to the least certain, and linked by the first
+//! that finds a PDF. Every link carries a confidence, and links below
+//! [`REVIEW_BELOW`] are left for a person to confirm or reject. Links are
+//! kept in `.litrev/links.json` so those decisions survive the next run
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bibtex::entry::BibTeXEntry;
+use crate::pdf::normalise_doi;
+use crate::search::text;
+use crate::store::StoredPdf;
+
+const LINKS_VERSION: u32 = 1;
+
+/// Links less confident than this are listed by `litrev link --review`
+pub const REVIEW_BELOW: f64 = 0.8;
+
+/// How alike a title must be to a PDF's for the two to be linked, as the
+/// share of their words in common
+const TITLE_SIMILARITY: f64 = 0.6;
+
+/// Words too common to tell two titles apart
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "by", "for", "from", "in", "is", "of", "on", "the", "to", "with",
+];
+
+/// What an entry was linked to a PDF by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Signal {
+    /// The path is in the entry's `file` field
+    FileField,
+    /// The PDF is named after the citekey
+    Citekey,
+    /// The entry's DOI is in the PDF's metadata or on its first page
+    Doi,
+    /// The entry's title is close to the PDF's title or name
+    Title,
+    /// A person linked them with `litrev link --confirm`
+    Manual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkStatus {
+    /// Found by the linker, and replaced the next time it runs
+    Auto,
+    Confirmed,
+    /// Never to be linked again
+    Rejected,
+}
+
+/// A PDF linked to an entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Link {
+    pub citekey: String,
+    pub path: PathBuf,
+    pub signal: Signal,
+    /// How likely the PDF is the entry's, from 0 to 1
+    pub confidence: f64,
+    pub status: LinkStatus,
+}
+
+/// Every link of a project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Links {
+    version: u32,
+    links: Vec<Link>,
+}
+
+impl std::fmt::Display for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::FileField => "file field",
+            Self::Citekey   => "citekey",
+            Self::Doi       => "DOI",
+            Self::Title     => "title",
+            Self::Manual    => "manual",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl std::fmt::Display for LinkStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Auto      => "auto",
+            Self::Confirmed => "confirmed",
+            Self::Rejected  => "rejected",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl Link {
+    fn auto(citekey: &str, path: &Path, signal: Signal, confidence: f64) -> Self {
+        Self { citekey: citekey.to_string(), path: path.to_path_buf(), signal, confidence, status: LinkStatus::Auto }
+    }
+
+    pub fn needs_review(&self) -> bool {
+        self.status == LinkStatus::Auto && self.confidence < REVIEW_BELOW
+    }
+}
+
+impl Default for Links {
+    fn default() -> Self {
+        Self { version: LINKS_VERSION, links: Vec::new() }
+    }
+}
+
+impl Links {
+    pub fn iter(&self) -> impl Iterator<Item = &Link> {
+        self.links.iter()
+    }
+
+    /// The PDFs linked to an entry and not rejected, the most confident
+    /// first
+    pub fn of(&self, citekey: &str) -> Vec<&Link> {
+        let mut links: Vec<&Link> = self
+            .links
+            .iter()
+            .filter(|link| link.citekey == citekey && link.status != LinkStatus::Rejected)
+            .collect();
+        links.sort_by(|a, b| {
+            (b.status == LinkStatus::Confirmed).cmp(&(a.status == LinkStatus::Confirmed))
+                .then(b.confidence.total_cmp(&a.confidence))
+        });
+        links
+    }
+
+    /// The entry a PDF is most likely the paper of, which is its confirmed
+    /// link or else its most confident
+    pub fn best_for(&self, path: &Path) -> Option<&Link> {
+        self.links
+            .iter()
+            .filter(|link| link.path == path && link.status != LinkStatus::Rejected)
+            .max_by(|a, b| {
+                (a.status == LinkStatus::Confirmed).cmp(&(b.status == LinkStatus::Confirmed))
+                    .then(a.confidence.total_cmp(&b.confidence))
+            })
+    }
+
+    /// Rewrites the path of every link
+    pub fn map_paths(&mut self, map: impl Fn(&Path) -> PathBuf) {
+        for link in &mut self.links {
+            link.path = map(&link.path);
+        }
+    }
+
+    /// The automatic links a person should look over
+    pub fn to_review(&self) -> impl Iterator<Item = &Link> {
+        self.links.iter().filter(|link| link.needs_review())
+    }
+
+    /// Replaces the automatic links with those just found, keeping every
+    /// link a person confirmed or rejected. Nothing is linked against a
+    /// rejection, or to an entry with a confirmed PDF, and links to PDFs
+    /// that no longer exist are dropped
+    pub fn update(&mut self, found: Vec<Link>) {
+        self.links.retain(|link| link.status != LinkStatus::Auto && link.path.is_file());
+        let confirmed: HashSet<&str> = self
+            .links
+            .iter()
+            .filter(|link| link.status == LinkStatus::Confirmed)
+            .map(|link| link.citekey.as_str())
+            .collect();
+        let decided: HashSet<(&str, &Path)> = self
+            .links
+            .iter()
+            .map(|link| (link.citekey.as_str(), link.path.as_path()))
+            .collect();
+        let found: Vec<Link> = found
+            .into_iter()
+            .filter(|link| {
+                !confirmed.contains(link.citekey.as_str()) && !decided.contains(&(link.citekey.as_str(), link.path.as_path()))
+            })
+            .collect();
+        self.links.extend(found);
+        self.links.sort_by(|a, b| a.citekey.cmp(&b.citekey).then(a.path.cmp(&b.path)));
+    }
+
+    /// Marks the links of an entry as confirmed, or only its link to `path`,
+    /// linking the two if they were not. Returns how many links changed
+    pub fn confirm(&mut self, citekey: &str, path: Option<&Path>) -> Result<usize, String> {
+        self.set_status(citekey, path, LinkStatus::Confirmed)
+    }
+
+    /// Marks the links of an entry as rejected, or only its link to `path`,
+    /// which keeps the linker from linking the two. Returns how many links
+    /// changed
+    pub fn reject(&mut self, citekey: &str, path: Option<&Path>) -> Result<usize, String> {
+        self.set_status(citekey, path, LinkStatus::Rejected)
+    }
+
+    fn set_status(&mut self, citekey: &str, path: Option<&Path>, status: LinkStatus) -> Result<usize, String> {
+        if let Some(path) = path {
+            if !self.links.iter().any(|link| link.citekey == citekey && link.path == path) {
+                let link = Link::auto(citekey, path, Signal::Manual, 1.0);
+                self.links.push(Link { status, ..link });
+                return Ok(1);
+            }
+        } else if !self.links.iter().any(|link| link.citekey == citekey) {
+            return Err(format!("{citekey} is not linked to any PDF"));
+        }
+        let mut changed = 0;
+        for link in &mut self.links {
+            let wanted = match path {
+                Some(path) => link.path == path,
+                // without a path only the linker's guesses are decided on
+                None => link.status == LinkStatus::Auto,
+            };
+            if link.citekey == citekey && wanted && link.status != status {
+                link.status = status;
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// Links entries to PDFs. `resolve` turns the paths in `file` fields into
+/// paths that can be checked for, which need not be among `pdfs`
+pub fn link(entries: &[BibTeXEntry], pdfs: &[StoredPdf], resolve: impl Fn(&Path) -> PathBuf) -> Vec<Link> {
+    let mut by_stem: HashMap<String, Vec<&Path>> = HashMap::new();
+    let mut by_doi: HashMap<&str, Vec<(&Path, usize)>> = HashMap::new();
+    for pdf in pdfs {
+        if let Some(stem) = pdf.path.file_stem() {
+            by_stem.entry(stem.to_string_lossy().to_lowercase()).or_default().push(&pdf.path);
+        }
+        for (i, doi) in pdf.dois.iter().enumerate() {
+            by_doi.entry(doi).or_default().push((&pdf.path, i));
+        }
+    }
+
+    let mut links = Vec::new();
+    let mut unlinked = Vec::new();
+    for entry in entries {
+        let found = exact_links(entry, &by_stem, &by_doi, &resolve);
+        if found.is_empty() {
+            unlinked.push(entry);
+        }
+        links.extend(found);
+    }
+
+    // a PDF found by a surer signal is not also guessed at by title
+    let claimed: HashSet<&Path> = links.iter().map(|link: &Link| link.path.as_path()).collect();
+    let unclaimed: Vec<&StoredPdf> = pdfs.iter().filter(|pdf| !claimed.contains(pdf.path.as_path())).collect();
+    let titles = TitleIndex::new(&unclaimed);
+    let mut by_title = Vec::new();
+    for entry in unlinked {
+        let Some(title) = entry.title() else {
+            continue;
+        };
+        if let Some((path, similarity)) = titles.best_match(title) {
+            by_title.push(Link::auto(&entry.citekey, path, Signal::Title, 0.85 * similarity));
+        }
+    }
+    links.extend(by_title);
+    links
+}
+
+/// The links of an entry from the signals that are certain when they match:
+/// the file field, the citekey and the DOI
+fn exact_links(
+    entry: &BibTeXEntry,
+    by_stem: &HashMap<String, Vec<&Path>>,
+    by_doi: &HashMap<&str, Vec<(&Path, usize)>>,
+    resolve: &impl Fn(&Path) -> PathBuf,
+) -> Vec<Link> {
+    let in_field: Vec<Link> = entry
+        .non_standard_field("file")
+        .into_iter()
+        .flat_map(|value| file_field_paths(value))
+        .map(|path| resolve(&path))
+        .filter(|path| path.is_file())
+        .map(|path| Link::auto(&entry.citekey, &path, Signal::FileField, 1.0))
+        .collect();
+    if !in_field.is_empty() {
+        return in_field;
+    }
+
+    if let Some(paths) = by_stem.get(&entry.citekey.to_lowercase()) {
+        return paths.iter().map(|path| Link::auto(&entry.citekey, path, Signal::Citekey, 0.95)).collect();
+    }
+
+    let doi = entry.doi().map(|doi| normalise_doi(doi));
+    match doi.and_then(|doi| by_doi.get(doi.as_str())) {
+        // the first DOI of a PDF is almost always its own, but a later one
+        // may be a reference or a related article
+        Some(paths) => paths
+            .iter()
+            .map(|&(path, i)| Link::auto(&entry.citekey, path, Signal::Doi, if i == 0 { 0.9 } else { 0.7 }))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// The paths in a `file` field, which is either a plain path or JabRef's
+/// `description:path:type` triples separated by `;`
+pub fn file_field_paths(value: &str) -> Vec<PathBuf> {
+    value
+        .split(';')
+        .filter(|file| !file.trim().is_empty())
+        .map(|file| {
+            let parts: Vec<&str> = file.split(':').collect();
+            match parts.as_slice() {
+                // a Windows drive letter also splits on ':'
+                [_, drive, path, _] if drive.len() == 1 => PathBuf::from(format!("{drive}:{path}")),
+                [_, path, _] => PathBuf::from(path),
+                _ => Path::new(file.trim()).to_path_buf(),
+            }
+        })
+        .collect()
+}
+
+/// The titles of PDFs by their words, for finding the closest to a title
+/// without comparing it against every PDF
+struct TitleIndex<'a> {
+    paths: Vec<&'a Path>,
+    /// How many distinct words each PDF's title has
+    lengths: Vec<usize>,
+    words: HashMap<String, Vec<usize>>,
+}
+
+impl<'a> TitleIndex<'a> {
+    /// Indexes the PDFs by the title in their metadata, or else their file
+    /// name
+    fn new(pdfs: &[&'a StoredPdf]) -> Self {
+        let mut index = Self { paths: Vec::new(), lengths: Vec::new(), words: HashMap::new() };
+        for pdf in pdfs {
+            let name = pdf.path.file_stem().map(|stem| stem.to_string_lossy().into_owned());
+            let Some(title) = pdf.title.clone().or(name) else {
+                continue;
+            };
+            let words = title_words(&title);
+            for word in &words {
+                index.words.entry(word.clone()).or_default().push(index.paths.len());
+            }
+            index.paths.push(&pdf.path);
+            index.lengths.push(words.len());
+        }
+        index
+    }
+
+    /// The PDF whose title shares the most words with `title`, measured by
+    /// the Dice coefficient, if it is similar enough
+    fn best_match(&self, title: &str) -> Option<(&'a Path, f64)> {
+        let words = title_words(title);
+        let mut shared: HashMap<usize, usize> = HashMap::new();
+        for word in &words {
+            for &i in self.words.get(word).into_iter().flatten() {
+                *shared.entry(i).or_default() += 1;
+            }
+        }
+        shared
+            .into_iter()
+            .map(|(i, shared)| (i, 2.0 * shared as f64 / (words.len() + self.lengths[i]) as f64))
+            .filter(|&(_, similarity)| similarity >= TITLE_SIMILARITY)
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+            .map(|(i, similarity)| (self.paths[i], similarity))
+    }
+}
+
+/// The distinct folded words of a title, without stopwords
+fn title_words(title: &str) -> HashSet<String> {
+    text::words(title)
+        .into_iter()
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibtex::parser::BibTeXParser;
+
+    fn pdf(path: &str, title: Option<&str>, dois: &[&str]) -> StoredPdf {
+        StoredPdf {
+            path: PathBuf::from(path),
+            hash: String::new(),
+            title: title.map(String::from),
+            author: None,
+            dois: dois.iter().map(|doi| doi.to_string()).collect(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_link() {
+        let entries = BibTeXParser::new(r"
+            @article{Kenway2019, title = {Effective Adjoint Approaches for Computational Fluid Dynamics}, doi = {10.1016/J.PAEROSCI.2019.05.002}}
+            @article{Martins2013, title = {Multidisciplinary Design Optimization: A Survey of Architectures}}
+            @article{He2018, title = {An Aerodynamic Design Optimization Framework Using a Discrete Adjoint Approach with OpenFOAM}}
+            @article{Jameson1988, title = {Aerodynamic Design via Control Theory}}
+        ").parse().unwrap();
+        let pdfs = [
+            pdf("papers/kenway-review.pdf", None, &["10.1016/j.paerosci.2019.05.002"]),
+            pdf("papers/martins2013.pdf", Some("Multidisciplinary Design Optimization"), &[]),
+            pdf("papers/he_aerodynamic_design_optimization_framework_discrete_adjoint_openfoam.pdf", None, &[]),
+            pdf("papers/control-theory-notes.pdf", None, &[]),
+        ];
+        let links = link(&entries, &pdfs, Path::to_path_buf);
+        let found: Vec<(&str, &str, Signal)> = links
+            .iter()
+            .map(|link| (link.citekey.as_str(), link.path.to_str().unwrap(), link.signal))
+            .collect();
+        assert_eq!(found, [
+            ("Kenway2019", "papers/kenway-review.pdf", Signal::Doi),
+            ("Martins2013", "papers/martins2013.pdf", Signal::Citekey),
+            ("He2018", "papers/he_aerodynamic_design_optimization_framework_discrete_adjoint_openfoam.pdf", Signal::Title),
+        ]);
+        // the PDF named after Martins2013 is not also a guess for another
+        // design optimization paper, and too few words are shared for Jameson
+        assert!(links[2].confidence > 0.6 && links[2].needs_review());
+        assert!(!links[0].needs_review());
+    }
+
+    #[test]
+    fn test_update() {
+        let dir = std::env::temp_dir().join(format!("litrev-links-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (first, second) = (dir.join("first.pdf"), dir.join("second.pdf"));
+        std::fs::write(&first, "").unwrap();
+        std::fs::write(&second, "").unwrap();
+
+        let mut links = Links::default();
+        links.update(vec![Link::auto("A", &first, Signal::Title, 0.7), Link::auto("B", &second, Signal::Title, 0.7)]);
+        assert_eq!(links.to_review().count(), 2);
+        links.reject("A", None).unwrap();
+        links.confirm("B", Some(&first)).unwrap();
+        assert!(links.confirm("C", None).is_err());
+
+        // decisions survive the next run, and a rejected link stays rejected
+        links.update(vec![Link::auto("A", &first, Signal::Title, 0.7), Link::auto("B", &second, Signal::Title, 0.7)]);
+        assert!(links.of("A").is_empty());
+        let b: Vec<(&Path, LinkStatus)> = links.of("B").iter().map(|link| (link.path.as_path(), link.status)).collect();
+        assert_eq!(b, [(first.as_path(), LinkStatus::Confirmed)]);
+        assert_eq!(links.best_for(&first).map(|link| link.citekey.as_str()), Some("B"));
+
+        std::fs::remove_file(&first).unwrap();
+        links.update(Vec::new());
+        assert_eq!(links.iter().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_field_paths() {
+        assert_eq!(file_field_paths("papers/a.pdf"), [PathBuf::from("papers/a.pdf")]);
+        assert_eq!(
+            file_field_paths(":papers/a.pdf:PDF;Supplement:C:\\b.pdf:PDF"),
+            [PathBuf::from("papers/a.pdf"), PathBuf::from("C:\\b.pdf")]
+        );
+    }
+}
@@ -1,3 +1,138 @@
+mod commands;
+
+use std::error::Error;
+use std::io;
+
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::CompleteEnv;
+
+use litrev::output::OutputFormat;
+use litrev::project::ProjectError;
+use litrev::search::QueryError;
+use litrev::style::csl::CslError;
+
+use commands::{UnknownCitekey, UnsupportedFormat, UsageError};
+
+const EXIT_CODES: &str = "\
+Exit codes:
+  0  success
+  1  any other error, e.g. an unreadable or malformed bibliography
+  2  invalid arguments or search query
+  3  no litrev project found, see `litrev init`
+  4  no entry with the given citekey
+
+Shell completion:
+  source <(COMPLETE=bash litrev)    in ~/.bashrc, or zsh in ~/.zshrc
+  COMPLETE=fish litrev | source     in ~/.config/fish/config.fish";
+
+#[derive(Parser)]
+#[command(name = "litrev", version, about = "Streamlines the academic literature review process", after_help = EXIT_CODES)]
+struct Cli {
+    /// The format to write entries in: table, json, jsonl, csv, bibtex, ris
+    /// or hayagriva. JSON objects carry a `schema_version` field
+    #[arg(long, global = true, default_value = "table")]
+    format: OutputFormat,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Creates a litrev project in the current directory
+    Init(commands::init::InitArgs),
+    /// Drafts an entry for a PDF and adds it once reviewed
+    Add(commands::add::AddArgs),
+    /// Adds the entries of bibliography files to the project
+    Import(commands::import::ImportArgs),
+    /// Re-indexes the bibliographies that changed since they were last indexed
+    Index(commands::index::IndexArgs),
+    /// Re-indexes the bibliographies and links new PDFs as they change, until
+    /// interrupted
+    Watch(commands::watch::WatchArgs),
+    /// Lists the entries of the project
+    List(commands::list::ListArgs),
+    /// Shows every field of an entry
+    Show(commands::show::ShowArgs),
+    /// Finds entries matching a query, e.g. `author:kenway year:2015..`
+    Search(commands::search::SearchArgs),
+    /// Links entries to the PDFs in the PDF directories, see --review
+    Link(commands::link::LinkArgs),
+    /// Opens the PDF of an entry, or else its DOI or URL
+    Open(commands::open::OpenArgs),
+    /// Finds the local PDFs whose reference lists cite an entry
+    FindRefs(commands::find_refs::FindRefsArgs),
+    /// Lists the entries an entry cites, as found by `litrev link`
+    Cites(commands::cites::CitesArgs),
+    /// Lists the entries that cite an entry, as found by `litrev link`
+    CitedBy(commands::cites::CitesArgs),
+    /// Writes the citations between entries as a DOT, GraphML or JSON graph
+    Graph(commands::graph::GraphArgs),
+    /// Finds copies of the same PDF, or files PDFs under their entries
+    Files(commands::files::FilesArgs),
+    /// Adds or removes tags of an entry
+    Tag(commands::tag::TagArgs),
+    /// Opens the notes on an entry in $EDITOR
+    Note(commands::note::NoteArgs),
+    /// Adds the highlights and notes in the PDF of an entry to its notes
+    Annotations(commands::annotations::AnnotationsArgs),
+    /// Converts bibliography files into another format
+    Export(commands::export::ExportArgs),
+    /// Formats entries as references in a citation style
+    Cite(commands::cite::CiteArgs),
+    /// Summarises the library: top authors, keywords and a timeline
+    Insights(commands::insights::InsightsArgs),
+}
+
 fn main() {
-    println!("hello")
-}
\ No newline at end of file
+    CompleteEnv::with_factory(Cli::command).complete();
+    let cli = Cli::parse();
+
+    if let Err(e) = run(cli) {
+        // a closed pipe, e.g. `litrev list | head`, is not an error
+        if e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) {
+            return;
+        }
+        eprintln!("error: {e}");
+        std::process::exit(exit_code(e.as_ref()));
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let format = cli.format;
+    match cli.command {
+        Command::Init(args)        => commands::init::run(args),
+        Command::Add(args)         => commands::add::run(args),
+        Command::Import(args)      => commands::import::run(args),
+        Command::Index(args)       => commands::index::run(args),
+        Command::Watch(args)       => commands::watch::run(args),
+        Command::List(args)        => commands::list::run(args, format),
+        Command::Show(args)        => commands::show::run(args, format),
+        Command::Search(args)      => commands::search::run(args, format),
+        Command::Link(args)        => commands::link::run(args, format),
+        Command::Open(args)        => commands::open::run(args),
+        Command::FindRefs(args)    => commands::find_refs::run(args, format),
+        Command::Cites(args)       => commands::cites::run(args, format),
+        Command::CitedBy(args)     => commands::cites::cited_by(args, format),
+        Command::Graph(args)       => commands::graph::run(args),
+        Command::Files(args)       => commands::files::run(args, format),
+        Command::Tag(args)         => commands::tag::run(args),
+        Command::Note(args)        => commands::note::run(args),
+        Command::Annotations(args) => commands::annotations::run(args),
+        Command::Export(args)      => commands::export::run(args, format),
+        Command::Cite(args)        => commands::cite::run(args),
+        Command::Insights(args)    => commands::insights::run(args, format),
+    }
+}
+
+/// The documented exit code of an error, see [`EXIT_CODES`]
+fn exit_code(error: &(dyn Error + 'static)) -> i32 {
+    if matches!(error.downcast_ref(), Some(ProjectError::NotFound { .. })) {
+        3
+    } else if error.is::<UnknownCitekey>() || matches!(error.downcast_ref(), Some(CslError::UnknownCitekey(_))) {
+        4
+    } else if error.is::<UnsupportedFormat>() || error.is::<UsageError>() || error.is::<QueryError>() {
+        2
+    } else {
+        1
+    }
+}
@@ -0,0 +1,127 @@
+//! The section of a note the annotations of an entry's PDF are written
+//! into. Each annotation ends in a hidden id, so that running
+//! `litrev annotations` again only adds the ones not already there, and
+//! whatever was written into the section since is kept
+
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::pdf::Annotation;
+
+/// The markers around the section
+pub const START: &str = "<!-- litrev:annotations -->";
+pub const END: &str = "<!-- /litrev:annotations -->";
+
+const HEADING: &str = "## Annotations";
+
+/// The hidden id at the end of an annotation's first line
+static ID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<!-- ([0-9a-f]{12}) -->").expect("valid id pattern"));
+
+/// The id of an annotation, from what it is and says
+fn id(annotation: &Annotation) -> String {
+    let key = format!(
+        "{}\n{}\n{}\n{}",
+        annotation.kind,
+        annotation.page,
+        annotation.text.as_deref().unwrap_or_default(),
+        annotation.comment.as_deref().unwrap_or_default(),
+    );
+    blake3::hash(key.as_bytes()).to_hex()[..12].to_string()
+}
+
+/// An annotation as a markdown list item, e.g.
+/// `- **p. 3** highlight: “the text under it” — a comment`
+pub fn item(annotation: &Annotation) -> String {
+    let mut item = format!("- **p. {}** {}", annotation.page, annotation.kind);
+    if let Some(author) = &annotation.author {
+        item.push_str(&format!(" ({author})"));
+    }
+    if let Some(text) = &annotation.text {
+        item.push_str(&format!(": “{text}”"));
+    }
+    let mut comment = annotation.comment.as_deref().unwrap_or_default().lines();
+    if let Some(first) = comment.next() {
+        item.push_str(if annotation.text.is_some() { " — " } else { ": " });
+        item.push_str(first);
+    }
+    item.push_str(&format!(" <!-- {} -->", id(annotation)));
+    // the rest of a comment stays inside the list item
+    for line in comment {
+        item.push_str("\n  ");
+        item.push_str(line);
+    }
+    item
+}
+
+/// The note with the annotations not already in its section added to the
+/// end of the section, which is added to the end of the note if it has
+/// none, and how many were added
+pub fn merge(note: &str, annotations: &[Annotation]) -> (String, usize) {
+    let section = note.find(START).and_then(|start| Some((start, start + note[start..].find(END)?)));
+    let existing: HashSet<&str> = match section {
+        Some((start, end)) => ID.captures_iter(&note[start..end]).filter_map(|captures| captures.get(1)).map(|id| id.as_str()).collect(),
+        None => HashSet::new(),
+    };
+    let mut added = HashSet::new();
+    let items: Vec<String> = annotations
+        .iter()
+        .filter(|annotation| {
+            let id = id(annotation);
+            !existing.contains(id.as_str()) && added.insert(id)
+        })
+        .map(item)
+        .collect();
+    if items.is_empty() {
+        return (note.to_string(), 0);
+    }
+
+    let count = items.len();
+    let items = items.join("\n");
+    let merged = match section {
+        Some((start, end)) => {
+            let before = note[..end].trim_end();
+            let separator = if before.len() == start + START.len() { "\n\n" } else { "\n" };
+            format!("{before}{separator}{items}\n{}", &note[end..])
+        }
+        None => {
+            let note = note.trim_end();
+            let gap = if note.is_empty() { "" } else { "\n\n" };
+            format!("{note}{gap}{HEADING}\n\n{START}\n\n{items}\n{END}\n")
+        }
+    };
+    (merged, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::AnnotationKind;
+
+    fn annotation(kind: AnnotationKind, page: u32, text: Option<&str>, comment: Option<&str>) -> Annotation {
+        Annotation { kind, page, text: text.map(String::from), comment: comment.map(String::from), author: None }
+    }
+
+    #[test]
+    fn test_merge() {
+        let highlight = annotation(AnnotationKind::Highlight, 2, Some("flows are chaotic"), Some("the key claim\nsee §3"));
+        let note = annotation(AnnotationKind::Note, 5, None, Some("Compare with Kenway2019"));
+        let (merged, added) = merge("# Turbulence\n\nMy own thoughts.\n", std::slice::from_ref(&highlight));
+        assert_eq!(added, 1);
+        let id = id(&highlight);
+        assert_eq!(merged, format!(
+            "# Turbulence\n\nMy own thoughts.\n\n## Annotations\n\n{START}\n\n\
+             - **p. 2** highlight: “flows are chaotic” — the key claim <!-- {id} -->\n  see §3\n{END}\n"
+        ));
+
+        // what was added by hand or extracted before is kept, and only the
+        // new annotation is added
+        let edited = merged.replace("the key claim", "THE key claim").replace(END, &format!("- a thought of my own\n{END}"));
+        let (remerged, added) = merge(&format!("{edited}\nMore thoughts.\n"), &[highlight, note.clone()]);
+        assert_eq!(added, 1);
+        assert!(remerged.contains(&format!("- a thought of my own\n{}\n{END}\n\nMore thoughts.\n", item(&note))));
+        assert!(remerged.contains("THE key claim"));
+        assert_eq!(merge(&remerged, &[note]), (remerged.clone(), 0));
+    }
+}
@@ -0,0 +1,9 @@
+//! The markdown notes kept on each entry, in the project's notes directory
+
+pub mod annotations;
+pub mod template;
+
+/// The text of a note below its frontmatter
+pub fn body(note: &str) -> &str {
+    template::split_frontmatter(note).1
+}
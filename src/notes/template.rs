@@ -0,0 +1,180 @@
+//! The template new notes are made from. `{{citekey}}`, `{{title}}`,
+//! `{{authors}}`, `{{year}}` and `{{doi}}` are filled in from the entry, as
+//! YAML values in the frontmatter and as plain text below it
+
+use crate::bibtex::entry::BibTeXEntry;
+use crate::bibtex::latex;
+
+/// The template used when the project does not name one
+pub const DEFAULT: &str = "\
+---
+citekey: {{citekey}}
+title: {{title}}
+authors: {{authors}}
+year: {{year}}
+doi: {{doi}}
+---
+
+# {{title}}
+
+";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateError {
+    /// A `{{` with no `}}` after it
+    Unclosed,
+    Unknown(String),
+}
+
+impl std::error::Error for TemplateError {}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unclosed => write!(f, "the note template has a `{{{{` with no `}}}}`"),
+            Self::Unknown(name) => write!(
+                f,
+                "the note template has an unknown placeholder `{{{{{name}}}}}`, expected citekey, title, authors, year or doi"
+            ),
+        }
+    }
+}
+
+/// A value of an entry, as it is written in YAML and in text
+enum Value {
+    Text(String),
+    List(Vec<String>),
+    Number(u16),
+    Missing,
+}
+
+impl Value {
+    fn to_yaml(&self) -> String {
+        // a JSON string is a YAML string too, and is never read as a number
+        // or a boolean
+        let quoted = |text: &str| serde_json::Value::from(text).to_string();
+        match self {
+            Self::Text(text) => quoted(text),
+            Self::List(items) => format!("[{}]", items.iter().map(|item| quoted(item)).collect::<Vec<_>>().join(", ")),
+            Self::Number(number) => number.to_string(),
+            Self::Missing => String::from("null"),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::List(items) => match items.split_last() {
+                Some((last, rest)) if !rest.is_empty() => format!("{} and {last}", rest.join(", ")),
+                _ => items.join(""),
+            },
+            Self::Number(number) => number.to_string(),
+            Self::Missing => String::new(),
+        }
+    }
+}
+
+fn value(entry: &BibTeXEntry, name: &str) -> Result<Value, TemplateError> {
+    let text = |value: Option<&String>| value.map_or(Value::Missing, |value| Value::Text(latex::decode(value)));
+    Ok(match name {
+        "citekey" => Value::Text(entry.citekey.clone()),
+        "title" => text(entry.title()),
+        "authors" => match entry.author() {
+            Some(authors) => Value::List(authors.iter().map(|author| latex::decode(&author.to_string())).collect()),
+            None => Value::Missing,
+        },
+        "year" => entry.year().map_or(Value::Missing, |year| Value::Number(*year)),
+        "doi" => text(entry.doi()),
+        name => return Err(TemplateError::Unknown(name.to_string())),
+    })
+}
+
+/// The note a template makes for an entry
+pub fn render(template: &str, entry: &BibTeXEntry) -> Result<String, TemplateError> {
+    let (frontmatter, body) = split_frontmatter(template);
+    Ok(fill(frontmatter, entry, Value::to_yaml)? + &fill(body, entry, Value::to_text)?)
+}
+
+/// The frontmatter of a note, from its opening `---` line to its closing
+/// one, and the rest of it
+pub fn split_frontmatter(note: &str) -> (&str, &str) {
+    if !note.starts_with("---\n") && !note.starts_with("---\r\n") {
+        return ("", note);
+    }
+    let mut offset = 0;
+    for line in note.split_inclusive('\n') {
+        offset += line.len();
+        if offset > 4 && line.trim_end() == "---" {
+            return note.split_at(offset);
+        }
+    }
+    ("", note)
+}
+
+fn fill(template: &str, entry: &BibTeXEntry, write: fn(&Value) -> String) -> Result<String, TemplateError> {
+    let mut filled = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        filled.push_str(&rest[..start]);
+        let end = rest[start..].find("}}").ok_or(TemplateError::Unclosed)?;
+        let name = rest[start + 2..start + end].trim();
+        filled.push_str(&write(&value(entry, name)?));
+        rest = &rest[start + end + 2..];
+    }
+    filled.push_str(rest);
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibtex::parser::BibTeXParser;
+
+    #[test]
+    fn test_render() {
+        let entries = BibTeXParser::new(r#"
+            @article{Muller2012,
+                author = {M{\"u}ller, Hans and Smith, Jane and Roe, R.},
+                title = {Shocks: a "Review"},
+                year = {2012}
+            }
+        "#).parse().unwrap();
+        assert_eq!(render(DEFAULT, &entries[0]).unwrap(), concat!(
+            "---\n",
+            "citekey: \"Muller2012\"\n",
+            "title: \"Shocks: a \\\"Review\\\"\"\n",
+            "authors: [\"Hans Müller\", \"Jane Smith\", \"R. Roe\"]\n",
+            "year: 2012\n",
+            "doi: null\n",
+            "---\n\n",
+            "# Shocks: a \"Review\"\n\n",
+        ));
+        assert_eq!(render("By {{ authors }} ({{year}}), {{doi}}.", &entries[0]).unwrap(), "By Hans Müller, Jane Smith and R. Roe (2012), .");
+        assert_eq!(render("{{journal}}", &entries[0]), Err(TemplateError::Unknown(String::from("journal"))));
+        assert_eq!(render("{{title", &entries[0]), Err(TemplateError::Unclosed));
+        assert_eq!(split_frontmatter("---\na: 1\n---\nbody"), ("---\na: 1\n---\n", "body"));
+        assert_eq!(split_frontmatter("--- not frontmatter"), ("", "--- not frontmatter"));
+    }
+
+    #[test]
+    fn test_render_missing() {
+        let entries = BibTeXParser::new("@misc{Anon, title = {}}").parse().unwrap();
+        let note = render(DEFAULT, &entries[0]).unwrap();
+        // a missing value is null in the frontmatter, so that it still reads
+        // as YAML, and left out of the text
+        assert_eq!(note, concat!(
+            "---\n",
+            "citekey: \"Anon\"\n",
+            "title: \"\"\n",
+            "authors: null\n",
+            "year: null\n",
+            "doi: null\n",
+            "---\n\n",
+            "# \n\n",
+        ));
+        let (frontmatter, _) = split_frontmatter(&note);
+        let yaml: serde_yaml::Value = serde_yaml::from_str(frontmatter.trim_start_matches("---\n").trim_end_matches("---\n")).unwrap();
+        assert!(yaml["authors"].is_null() && yaml["year"].is_null());
+        assert_eq!(render("{{authors}} ({{year}})", &entries[0]).unwrap(), " ()");
+    }
+}
@@ -0,0 +1,310 @@
+//! The output of the listing commands in each `--format`. The JSON forms
+//! are an interface for scripts: fields are only ever added, and anything
+//! that would break a consumer bumps [`SCHEMA_VERSION`]
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::bibtex::entry::BibTeXEntry;
+use crate::formats::{hayagriva, ris};
+use crate::project::Tags;
+use crate::bibtex::latex;
+
+/// The version of the JSON entry objects, included in every one of them
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The widest a title is allowed to be in a table before it is cut short
+const TABLE_TITLE_WIDTH: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Jsonl,
+    Csv,
+    BibTeX,
+    Ris,
+    Hayagriva,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "table"          => Ok(Self::Table),
+            "json"           => Ok(Self::Json),
+            "jsonl"          => Ok(Self::Jsonl),
+            "csv"            => Ok(Self::Csv),
+            "bibtex" | "bib" => Ok(Self::BibTeX),
+            "ris"            => Ok(Self::Ris),
+            "hayagriva"      => Ok(Self::Hayagriva),
+            _ => Err(format!("unknown format: {s}, expected table, json, jsonl, csv, bibtex, ris or hayagriva")),
+        }
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Table     => write!(f, "table"),
+            Self::Json      => write!(f, "json"),
+            Self::Jsonl     => write!(f, "jsonl"),
+            Self::Csv       => write!(f, "csv"),
+            Self::BibTeX    => write!(f, "bibtex"),
+            Self::Ris       => write!(f, "ris"),
+            Self::Hayagriva => write!(f, "hayagriva"),
+        }
+    }
+}
+
+/// An entry as it appears in JSON output
+#[derive(Debug, Serialize)]
+pub struct EntryRecord<'a> {
+    pub schema_version: u32,
+    pub citekey: &'a str,
+    pub kind: String,
+    pub title: Option<String>,
+    /// "Family, Given" for each author
+    pub authors: Vec<String>,
+    pub editors: Vec<String>,
+    pub year: Option<u16>,
+    /// The journal or book the entry was published in
+    pub container: Option<String>,
+    pub doi: Option<String>,
+    pub tags: Vec<String>,
+    /// Every field as written in the bibliography, keyed by lowercase name
+    pub fields: BTreeMap<String, String>,
+}
+
+impl<'a> EntryRecord<'a> {
+    pub fn new(entry: &'a BibTeXEntry, tags: &Tags) -> Self {
+        let names = |authors: Option<&crate::bibtex::fields::Authors>| {
+            authors.map_or_else(Vec::new, |authors| {
+                authors.iter().map(|author| latex::decode(&author.to_bibtex_name())).collect()
+            })
+        };
+        Self {
+            schema_version: SCHEMA_VERSION,
+            citekey: &entry.citekey,
+            kind: entry.kind.to_string(),
+            title: entry.title().map(|title| latex::decode(title)),
+            authors: names(entry.author()),
+            editors: names(entry.editor()),
+            year: entry.year().copied(),
+            container: entry.journal().or(entry.book_title()).map(|c| latex::decode(c)),
+            doi: entry.doi().map(|doi| doi.trim().to_string()),
+            tags: tags.of(&entry.citekey),
+            fields: entry.fields().iter().map(|field| (field.key().to_string(), field.value())).collect(),
+        }
+    }
+}
+
+/// Writes a list of entries, e.g. the results of a search
+pub fn write_entries(
+    out: &mut impl Write,
+    entries: &[&BibTeXEntry],
+    tags: &Tags,
+    format: OutputFormat,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Table => write_table(out, entries),
+        OutputFormat::Json => {
+            let records: Vec<EntryRecord> = entries.iter().map(|entry| EntryRecord::new(entry, tags)).collect();
+            serde_json::to_writer_pretty(&mut *out, &records)?;
+            writeln!(out)
+        }
+        OutputFormat::Jsonl => {
+            for entry in entries {
+                serde_json::to_writer(&mut *out, &EntryRecord::new(entry, tags))?;
+                writeln!(out)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Csv => write_csv(out, entries, tags),
+        OutputFormat::BibTeX => write_bibtex(out, entries),
+        OutputFormat::Ris => entries.iter().try_for_each(|entry| write!(out, "{}", ris::write_entry(entry))),
+        OutputFormat::Hayagriva => {
+            let yaml = hayagriva::write(entries.iter().copied()).map_err(io::Error::other)?;
+            write!(out, "{yaml}")
+        }
+    }
+}
+
+/// Writes a single entry in full, e.g. for `litrev show`
+pub fn write_entry(
+    out: &mut impl Write,
+    entry: &BibTeXEntry,
+    tags: &Tags,
+    format: OutputFormat,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Table => {
+            writeln!(out, "{} ({})", entry.citekey, entry.kind)?;
+            let width = entry.fields().iter().map(|field| field.key().len()).max().unwrap_or(0).max(4);
+            for field in entry.fields() {
+                writeln!(out, "  {:width$}  {}", field.key(), field.value())?;
+            }
+            let entry_tags = tags.of(&entry.citekey);
+            if !entry_tags.is_empty() {
+                writeln!(out, "  {:width$}  {}", "tags", entry_tags.join(", "))?;
+            }
+            Ok(())
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, &EntryRecord::new(entry, tags))?;
+            writeln!(out)
+        }
+        format => write_entries(out, &[entry], tags, format),
+    }
+}
+
+fn write_table(out: &mut impl Write, entries: &[&BibTeXEntry]) -> io::Result<()> {
+    let rows: Vec<[String; 4]> = entries
+        .iter()
+        .map(|entry| {
+            let year = entry.year().map_or_else(String::new, u16::to_string);
+            let title = entry.title().map_or_else(String::new, |title| truncate(&latex::decode(title), TABLE_TITLE_WIDTH));
+            [entry.citekey.clone(), year, short_authors(entry), title]
+        })
+        .collect();
+
+    let headers = ["CITEKEY", "YEAR", "AUTHORS", "TITLE"];
+    let mut widths = headers.map(|header| header.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let write_row = |out: &mut dyn Write, cells: [&str; 4]| -> io::Result<()> {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell}{}", " ".repeat(width - cell.chars().count())))
+            .collect();
+        writeln!(out, "{}", padded.join("  ").trim_end())
+    };
+    write_row(out, headers)?;
+    for row in &rows {
+        write_row(out, [&row[0], &row[1], &row[2], &row[3]])?;
+    }
+    Ok(())
+}
+
+/// "Smith", "Smith & Doe" or "Smith et al."
+fn short_authors(entry: &BibTeXEntry) -> String {
+    let Some(authors) = entry.author().or(entry.editor()) else {
+        return String::new();
+    };
+    let surnames: Vec<String> = authors.iter().map(|author| latex::decode(author.surname())).collect();
+    match surnames.as_slice() {
+        [] => String::new(),
+        [one] => one.clone(),
+        [first, second] => format!("{first} & {second}"),
+        [first, ..] => format!("{first} et al."),
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(width - 1).collect();
+    truncated.push('\u{2026}');
+    truncated
+}
+
+fn write_csv(out: &mut impl Write, entries: &[&BibTeXEntry], tags: &Tags) -> io::Result<()> {
+    writeln!(out, "citekey,kind,year,authors,title,container,doi,tags")?;
+    for entry in entries {
+        let record = EntryRecord::new(entry, tags);
+        let cells = [
+            record.citekey.to_string(),
+            record.kind,
+            record.year.map_or_else(String::new, |year| year.to_string()),
+            record.authors.join("; "),
+            record.title.unwrap_or_default(),
+            record.container.unwrap_or_default(),
+            record.doi.unwrap_or_default(),
+            record.tags.join("; "),
+        ];
+        let escaped: Vec<String> = cells.iter().map(|cell| csv_escape(cell)).collect();
+        writeln!(out, "{}", escaped.join(","))?;
+    }
+    Ok(())
+}
+
+/// Quotes a cell if it contains a comma, quote or line break, doubling any
+/// quotes inside it
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+fn write_bibtex(out: &mut impl Write, entries: &[&BibTeXEntry]) -> io::Result<()> {
+    for (i, entry) in entries.iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        write!(out, "{entry}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibtex::parser::BibTeXParser;
+
+    const SAMPLE: &str = r#"
+        @article{Smith2019,
+            author = {M{\"u}ller, John and Doe, Jane and Roe, Richard},
+            title = {Adjoint Methods, {R}evisited},
+            journal = {AIAA Journal},
+            year = {2019}
+        }
+    "#;
+
+    fn render(format: OutputFormat) -> String {
+        let entries = BibTeXParser::new(SAMPLE).parse().unwrap();
+        let refs: Vec<&BibTeXEntry> = entries.iter().collect();
+        let mut tags = Tags::default();
+        tags.add("Smith2019", "cfd");
+        let mut out = Vec::new();
+        write_entries(&mut out, &refs, &tags, format).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_table() {
+        assert_eq!(render(OutputFormat::Table), "\
+CITEKEY    YEAR  AUTHORS        TITLE
+Smith2019  2019  Müller et al.  Adjoint Methods, Revisited
+");
+    }
+
+    #[test]
+    fn test_jsonl() {
+        let line = render(OutputFormat::Jsonl);
+        let value: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["citekey"], "Smith2019");
+        assert_eq!(value["authors"][1], "Doe, Jane");
+        assert_eq!(value["tags"][0], "cfd");
+        assert_eq!(value["fields"]["journal"], "AIAA Journal");
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(render(OutputFormat::Csv), "\
+citekey,kind,year,authors,title,container,doi,tags
+Smith2019,article,2019,\"Müller, John; Doe, Jane; Roe, Richard\",\"Adjoint Methods, Revisited\",AIAA Journal,,cfd
+");
+    }
+}
@@ -0,0 +1,269 @@
+//! The annotations PDF viewers save in a PDF: highlights and underlines,
+//! with the text under them, sticky notes and free text written on a page
+
+use std::path::Path;
+
+use lopdf::{Dictionary, Document, Object};
+
+use super::text::{self, Glyph};
+use super::{Pdf, PdfError};
+
+/// How far a glyph may stick out of a highlighted area and still be under
+/// it, as a share of its size
+const TOLERANCE: f32 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationKind {
+    Highlight,
+    Underline,
+    /// A sticky note
+    Note,
+    /// Text written onto the page
+    FreeText,
+}
+
+/// An annotation on a page
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub kind: AnnotationKind,
+    /// The number of the page, from 1
+    pub page: u32,
+    /// The text under a highlight or underline
+    pub text: Option<String>,
+    /// What was written in the annotation: a note, free text or a comment
+    /// on a highlight
+    pub comment: Option<String>,
+    pub author: Option<String>,
+}
+
+impl AnnotationKind {
+    /// The kind of an annotation by its `/Subtype`, if it is one litrev reads
+    fn from_subtype(subtype: &[u8]) -> Option<Self> {
+        match subtype {
+            b"Highlight" => Some(Self::Highlight),
+            b"Underline" => Some(Self::Underline),
+            b"Text"      => Some(Self::Note),
+            b"FreeText"  => Some(Self::FreeText),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Highlight => "highlight",
+            Self::Underline => "underline",
+            Self::Note      => "note",
+            Self::FreeText  => "free text",
+        }
+    }
+}
+
+impl std::fmt::Display for AnnotationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Pdf {
+    /// The annotations of every page, from the top of each page down.
+    /// Pages whose annotations cannot be read are left out
+    pub fn annotations(&self) -> Vec<Annotation> {
+        let mut annotations = Vec::new();
+        for (number, page_id) in self.document.get_pages() {
+            let Ok(page) = self.document.get_dictionary(page_id) else {
+                continue;
+            };
+            let mut on_page: Vec<(f32, f32, Annotation)> = Vec::new();
+            let mut glyphs: Option<Vec<Glyph>> = None;
+            for dictionary in annotation_dictionaries(&self.document, page) {
+                let Some(kind) = dictionary.get(b"Subtype").and_then(Object::as_name).ok().and_then(AnnotationKind::from_subtype) else {
+                    continue;
+                };
+                let areas = areas(dictionary);
+                let text = match kind {
+                    AnnotationKind::Highlight | AnnotationKind::Underline => {
+                        let glyphs = glyphs.get_or_insert_with(|| text::read_glyphs(&self.document, page_id).unwrap_or_default());
+                        Some(text_under(glyphs, &areas)).filter(|text| !text.is_empty())
+                    }
+                    _ => None,
+                };
+                let comment = text_string(dictionary, b"Contents");
+                if text.is_none() && comment.is_none() {
+                    continue;
+                }
+                let (top, left) = areas.first().map_or((0.0, 0.0), |area| (area.3, area.0));
+                let author = text_string(dictionary, b"T");
+                on_page.push((top, left, Annotation { kind, page: number, text, comment, author }));
+            }
+            on_page.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.total_cmp(&b.1)));
+            annotations.extend(on_page.into_iter().map(|(_, _, annotation)| annotation));
+        }
+        annotations
+    }
+}
+
+/// Reads the annotations of a PDF, see [`Pdf::annotations`]
+pub fn read_annotations(path: &Path) -> Result<Vec<Annotation>, PdfError> {
+    Ok(Pdf::open(path)?.annotations())
+}
+
+/// The dictionaries in a page's `/Annots`, which may be references
+fn annotation_dictionaries<'a>(document: &'a Document, page: &'a Dictionary) -> Vec<&'a Dictionary> {
+    let annots = match page.get(b"Annots") {
+        Ok(Object::Reference(id)) => document.get_object(*id).ok(),
+        Ok(annots) => Some(annots),
+        Err(_) => None,
+    };
+    annots
+        .and_then(|annots| annots.as_array().ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|annot| match annot {
+            Object::Reference(id) => document.get_dictionary(*id).ok(),
+            Object::Dictionary(dictionary) => Some(dictionary),
+            _ => None,
+        })
+        .collect()
+}
+
+fn text_string(dictionary: &Dictionary, key: &[u8]) -> Option<String> {
+    let text = lopdf::decode_text_string(dictionary.get(key).ok()?).ok()?;
+    let text = text.replace('\r', "\n");
+    Some(text.trim().to_string()).filter(|text| !text.is_empty())
+}
+
+/// The areas an annotation covers as `(left, bottom, right, top)`: one for
+/// each quadrilateral in `/QuadPoints`, one per line of a highlight, or else
+/// its `/Rect`
+fn areas(dictionary: &Dictionary) -> Vec<(f32, f32, f32, f32)> {
+    let numbers = |key: &[u8]| -> Vec<f32> {
+        dictionary
+            .get(key)
+            .and_then(Object::as_array)
+            .map(|array| array.iter().filter_map(|number| number.as_float().ok()).collect())
+            .unwrap_or_default()
+    };
+    let bounds = |points: &[f32]| {
+        let xs = points.iter().step_by(2);
+        let ys = points.iter().skip(1).step_by(2);
+        (
+            xs.clone().copied().fold(f32::INFINITY, f32::min),
+            ys.clone().copied().fold(f32::INFINITY, f32::min),
+            xs.copied().fold(f32::NEG_INFINITY, f32::max),
+            ys.copied().fold(f32::NEG_INFINITY, f32::max),
+        )
+    };
+    let quads = numbers(b"QuadPoints");
+    if quads.len() >= 8 {
+        return quads.chunks_exact(8).map(bounds).collect();
+    }
+    let rect = numbers(b"Rect");
+    if rect.len() == 4 {
+        return vec![bounds(&rect)];
+    }
+    Vec::new()
+}
+
+/// The text of the glyphs whose middles are in the areas, an area at a time
+/// and with a hyphen broken across two areas joined up
+fn text_under(glyphs: &[Glyph], areas: &[(f32, f32, f32, f32)]) -> String {
+    let mut text = String::new();
+    for &(left, bottom, right, top) in areas {
+        let mut line = String::new();
+        let mut end: Option<f32> = None;
+        for glyph in glyphs {
+            let margin = TOLERANCE * glyph.size;
+            let middle = ((glyph.x.0 + glyph.x.1) / 2.0, glyph.y + 0.3 * glyph.size);
+            let inside = middle.0 >= left - margin
+                && middle.0 <= right + margin
+                && middle.1 >= bottom - margin
+                && middle.1 <= top + margin;
+            if !inside {
+                continue;
+            }
+            // TeX spaces words by moving along rather than with spaces
+            if end.is_some_and(|end| glyph.x.0 - end > text::WORD_GAP * glyph.size) && !line.ends_with(' ') {
+                line.push(' ');
+            }
+            line.push(glyph.c);
+            end = Some(glyph.x.1);
+        }
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() {
+            continue;
+        }
+        match text.strip_suffix('-') {
+            Some(broken) if line.starts_with(char::is_lowercase) => text = broken.to_string(),
+            _ if !text.is_empty() => text.push(' '),
+            _ => {}
+        }
+        text.push_str(&line);
+    }
+    text::expand_ligatures(&text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::write_sample;
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_annotations() {
+        let path = std::env::temp_dir().join(format!("litrev-annotations-{}.pdf", std::process::id()));
+        write_sample(&path, &[], &["Turbulent flows are chaotic and their\nprediction is hard but important"]);
+
+        // Courier glyphs default to 5 points wide at 10 points, with 2.5
+        // points between words; the lines sit at 800 and 786
+        let mut document = Document::load(&path).unwrap();
+        let page_id = document.get_pages()[&1];
+        let quad = |left: f32, right: f32, baseline: f32| {
+            let (bottom, top) = (baseline - 2.0, baseline + 9.0);
+            vec![left, top, right, top, left, bottom, right, bottom]
+        };
+        let mut points = quad(97.0, 238.0, 800.0);
+        points.extend(quad(49.0, 100.0, 786.0));
+        let annots = vec![
+            Object::Dictionary(dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "Highlight",
+                "Rect" => vec![49.into(), 784.into(), 238.into(), 809.into()],
+                "QuadPoints" => points.into_iter().map(Object::Real).collect::<Vec<_>>(),
+                "Contents" => Object::string_literal("the key claim"),
+                "T" => Object::string_literal("Ada"),
+            }),
+            Object::Dictionary(dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "Text",
+                "Rect" => vec![20.into(), 700.into(), 40.into(), 720.into()],
+                "Contents" => Object::string_literal("Compare with Kenway2019"),
+            }),
+            Object::Dictionary(dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "Link",
+                "Rect" => vec![0.into(), 0.into(), 10.into(), 10.into()],
+            }),
+        ];
+        document.get_object_mut(page_id).unwrap().as_dict_mut().unwrap().set("Annots", annots);
+        document.save(&path).unwrap();
+
+        let annotations = read_annotations(&path).unwrap();
+        assert_eq!(annotations, [
+            Annotation {
+                kind: AnnotationKind::Highlight,
+                page: 1,
+                text: Some(String::from("flows are chaotic and their prediction")),
+                comment: Some(String::from("the key claim")),
+                author: Some(String::from("Ada")),
+            },
+            Annotation {
+                kind: AnnotationKind::Note,
+                page: 1,
+                text: None,
+                comment: Some(String::from("Compare with Kenway2019")),
+                author: None,
+            },
+        ]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,235 @@
+//! Drafting an entry for a PDF that has none. The metadata is trusted first,
+//! and anything it leaves out is guessed from the first page: the title is
+//! the largest text that is not boilerplate and the authors are the lines
+//! of names below it. A draft is meant to be checked by a person
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::bibtex::entry::{BibTeXEntry, BibTeXEntryKind};
+use crate::bibtex::fields::BibTeXField;
+use crate::bibtex::latex;
+use crate::formats;
+
+use super::{Line, PdfMetadata};
+
+/// Text on a first page that is about the journal or the file rather than
+/// the paper, in lowercase
+const BOILERPLATE: &[&str] = &[
+    "@", "©", "arxiv:", "available online", "contents lists", "copyright", "doi", "http", "issn", "journal",
+    "preprint", "proceedings", "published", "received", "vol.", "volume", "www.",
+];
+
+/// Words in the names of institutions, which sit among the authors' names
+const AFFILIATIONS: &[&str] = &[
+    "centre", "center", "college", "company", "corporation", "department", "foundation", "group", "inc",
+    "institute", "laboratory", "ltd", "school", "university",
+];
+
+/// The lowercase words that may start a surname, e.g. `van` in `Ludwig van
+/// Beethoven`
+const PARTICLES: &[&str] = &["da", "de", "del", "della", "den", "der", "di", "du", "la", "le", "ten", "ter", "van", "von"];
+
+/// How many lines below the title the authors may start
+const AUTHOR_LINES: usize = 8;
+
+static YEAR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(?:19|20)\d{2}\b").expect("valid year pattern"));
+
+/// A draft entry from what could be read from a PDF, with a citekey
+/// generated from it
+pub fn draft(metadata: &PdfMetadata) -> BibTeXEntry {
+    let lines: &[Line] = metadata.pages.iter().map(|page| page.lines.as_slice()).find(|lines| !lines.is_empty()).unwrap_or(&[]);
+    let (title, after_title) = match &metadata.title {
+        Some(title) => (Some(title.clone()), lines.iter().position(|line| line.text == *title).map(|i| i + 1)),
+        None => match find_title(lines) {
+            Some((title, end)) => (Some(title), Some(end)),
+            None => (None, None),
+        },
+    };
+    let authors = match metadata.authors.is_empty() {
+        true => after_title.map(|start| find_authors(&lines[start..])).unwrap_or_default(),
+        false => metadata.authors.clone(),
+    };
+
+    let kind = if metadata.dois.is_empty() { BibTeXEntryKind::Misc } else { BibTeXEntryKind::Article };
+    let mut entry = BibTeXEntry::new(kind, String::new());
+    if !authors.is_empty() {
+        entry.add_field(BibTeXField::from_key_value("author", &authors.join(" and ")));
+    }
+    if let Some(title) = title {
+        entry.add_field(BibTeXField::from_key_value("title", &latex::escape(&title)));
+    }
+    if let Some(year) = metadata.year.or_else(|| metadata.arxiv_ids.first().and_then(|id| arxiv_year(id))).or_else(|| copyright_year(lines)) {
+        entry.add_field(BibTeXField::from_key_value("year", &year.to_string()));
+    }
+    if let Some(doi) = metadata.dois.first() {
+        entry.add_field(BibTeXField::from_key_value("doi", doi));
+    }
+    if let Some(id) = metadata.arxiv_ids.first() {
+        entry.add_field(BibTeXField::from_key_value("eprint", id));
+        entry.add_field(BibTeXField::from_key_value("archiveprefix", "arXiv"));
+    }
+    entry.citekey = formats::generate_citekey(&entry);
+    entry
+}
+
+/// The title among the lines, with the index of the line after it. It is
+/// the largest text that is not boilerplate, which may run over several
+/// lines, or the first long line if every line is the same size
+fn find_title(lines: &[Line]) -> Option<(String, usize)> {
+    let candidates: Vec<usize> = (0..lines.len())
+        .filter(|&i| {
+            let words = lines[i].text.split_whitespace().count();
+            (1..=30).contains(&words) && lines[i].text.chars().any(char::is_alphabetic) && !is_boilerplate(&lines[i].text)
+        })
+        .collect();
+    let largest = candidates.iter().map(|&i| lines[i].size).fold(0.0, f32::max);
+    let mut sizes: Vec<f32> = lines.iter().map(|line| line.size).collect();
+    sizes.sort_by(f32::total_cmp);
+    let body = sizes.get(sizes.len() / 2).copied().unwrap_or(0.0);
+
+    let start = if largest > body + 0.5 {
+        *candidates.iter().find(|&&i| lines[i].size >= largest - 0.5)?
+    } else {
+        *candidates.iter().find(|&&i| lines[i].text.split_whitespace().count() >= 3)?
+    };
+    let mut end = start + 1;
+    if largest > body + 0.5 {
+        while end < lines.len() && (lines[end].size - lines[start].size).abs() <= 0.5 && !is_boilerplate(&lines[end].text) {
+            end += 1;
+        }
+    }
+    let title = lines[start..end].iter().map(|line| line.text.as_str()).collect::<Vec<_>>().join(" ");
+    let title = title.trim_end_matches(['*', '∗', '†', '‡', '§']).trim().to_string();
+    Some((title, end))
+}
+
+fn is_boilerplate(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    BOILERPLATE.iter().any(|word| lower.contains(word))
+}
+
+/// The names on the lines of names among the first few lines
+fn find_authors(lines: &[Line]) -> Vec<String> {
+    let mut authors = Vec::new();
+    for line in lines.iter().take(AUTHOR_LINES) {
+        match names(&line.text) {
+            Some(names) => authors.extend(names),
+            None if authors.is_empty() => continue,
+            None => break,
+        }
+    }
+    authors
+}
+
+/// The names on a line, if it is only names. Footnote marks, affiliation
+/// numbers, emails and anything in parentheses are left out
+fn names(line: &str) -> Option<Vec<String>> {
+    let mut cleaned = String::new();
+    let mut depth = 0;
+    for c in line.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = (depth - 1).max(0),
+            _ if depth > 0 => {}
+            '*' | '∗' | '†' | '‡' | '§' | '¶' | '#' => {}
+            c if c.is_ascii_digit() => {}
+            c => cleaned.push(c),
+        }
+    }
+    let names: Vec<String> = cleaned
+        .split([',', ';', '&', '·'])
+        .flat_map(|part| part.split(" and "))
+        .map(|name| name.split_whitespace().filter(|word| !word.contains('@')).collect::<Vec<_>>().join(" "))
+        .filter(|name| !name.is_empty())
+        .collect();
+    (!names.is_empty() && names.iter().all(|name| is_name(name))).then_some(names)
+}
+
+/// Whether some words look like a person's name: two to four capitalised
+/// words, allowing initials and a `von` part
+fn is_name(name: &str) -> bool {
+    let words: Vec<&str> = name.split_whitespace().collect();
+    (2..=4).contains(&words.len())
+        && words.last().is_some_and(|last| last.starts_with(char::is_uppercase))
+        && words.iter().all(|word| {
+            let capitalised = word.starts_with(char::is_uppercase) || PARTICLES.contains(word);
+            let letters = word.chars().all(|c| c.is_alphabetic() || matches!(c, '.' | '-' | '\'' | '’'));
+            capitalised && letters && word.chars().count() <= 20 && !AFFILIATIONS.contains(&word.to_lowercase().trim_end_matches('.'))
+        })
+}
+
+/// The year an arXiv ID was submitted in, from its `YYMM`
+fn arxiv_year(id: &str) -> Option<u16> {
+    let digits = id.rsplit('/').next()?;
+    let year: u16 = digits.get(..2)?.parse().ok()?;
+    // old IDs started in 1991 and new ones in 2007
+    Some(if year >= 91 { 1900 + year } else { 2000 + year })
+}
+
+/// The latest year in a copyright notice, which is when most articles were
+/// published
+fn copyright_year(lines: &[Line]) -> Option<u16> {
+    lines
+        .iter()
+        .filter(|line| line.text.contains('©') || line.text.to_lowercase().contains("copyright"))
+        .flat_map(|line| YEAR.find_iter(&line.text).filter_map(|year| year.as_str().parse().ok()))
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::Page;
+
+    fn page(lines: &[(f32, &str)]) -> Page {
+        Page { number: 1, lines: lines.iter().map(|&(size, text)| Line { text: text.to_string(), size }).collect() }
+    }
+
+    #[test]
+    fn test_draft() {
+        let metadata = PdfMetadata {
+            pages: vec![page(&[
+                (8.0, "Progress in Aerospace Sciences 109 (2019) 100542"),
+                (16.0, "Effective adjoint approaches for computational fluid"),
+                (16.0, "dynamics & optimization*"),
+                (10.0, "Gaetan K.W. Kenway1, Charles A. Mader1 and Ping He2"),
+                (10.0, "1 Department of Aerospace Engineering, University of Michigan"),
+                (9.0, "© 2019 Elsevier Ltd. All rights reserved."),
+                (9.0, "Adjoint methods compute gradients of a few functions with respect to many variables."),
+            ])],
+            dois: vec![String::from("10.1016/j.paerosci.2019.05.002")],
+            ..PdfMetadata::default()
+        };
+        let entry = draft(&metadata);
+        assert_eq!(entry.to_string(), "@article{Kenway2019,
+  author = {Kenway, Gaetan K.W. and Mader, Charles A. and He, Ping},
+  title = {Effective adjoint approaches for computational fluid dynamics \\& optimization},
+  year = {2019},
+  doi = {10.1016/j.paerosci.2019.05.002}
+}
+");
+
+        // the metadata wins over the text, and arXiv preprints are dated by
+        // their ID
+        let metadata = PdfMetadata {
+            title: Some(String::from("Neural Operators")),
+            authors: vec![String::from("Kovachki, Nikola")],
+            arxiv_ids: vec![String::from("2108.08481")],
+            pages: vec![page(&[(10.0, "Neural Operators"), (10.0, "Someone Else")])],
+            ..PdfMetadata::default()
+        };
+        let entry = draft(&metadata);
+        assert_eq!(entry.citekey, "Kovachki2021");
+        assert_eq!(entry.kind, BibTeXEntryKind::Misc);
+        assert_eq!(entry.non_standard_field("eprint").unwrap(), "2108.08481");
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(names("Ludwig van Beethoven∗, Ada Lovelace (ada@example.org)").unwrap(), ["Ludwig van Beethoven", "Ada Lovelace"]);
+        assert_eq!(names("for version 4.19.0, 18 August 2022"), None);
+        assert_eq!(names("X Desktop Group"), None);
+    }
+}
@@ -0,0 +1,33 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum PdfError {
+    Io(String), 
+    /// The file is not a PDF litrev can read, e.g. it is encrypted or
+    /// corrupted
+    Malformed(String), 
+}
+
+impl std::error::Error for PdfError {}
+
+impl std::fmt::Display for PdfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Malformed(e) => write!(f, "unreadable PDF: {e}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for PdfError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e.to_string())
+    }
+}
+
+impl From<lopdf::Error> for PdfError {
+    fn from(e: lopdf::Error) -> Self {
+        match e {
+            lopdf::Error::IO(e) => Self::Io(e.to_string()),
+            e => Self::Malformed(e.to_string()),
+        }
+    }
+}
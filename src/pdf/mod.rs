@@ -0,0 +1,327 @@
+//! Reading PDFs in pure Rust: the document info dictionary, XMP metadata,
+//! the text of each page and its annotations, along with the DOIs and arXiv
+//! IDs in them
+
+pub mod annotations;
+pub mod draft;
+mod error;
+pub mod text;
+pub mod xmp;
+
+pub use annotations::{Annotation, AnnotationKind};
+pub use error::PdfError;
+pub use text::{Line, Page};
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+use lopdf::{Document, LoadOptions, Object};
+use regex::Regex;
+
+use xmp::Xmp;
+
+/// The most bytes any one stream may decompress to, so that a malicious or
+/// broken PDF cannot exhaust memory
+const MAX_DECOMPRESSED: usize = 64 << 20;
+
+/// How many pages [`read`] reads the text of, enough to get past a cover
+/// page
+pub const FIRST_PAGES: u32 = 2;
+
+/// The pattern of a DOI: a `10.` prefix with the registrant's code, a slash
+/// and then anything up to whitespace
+static DOI: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\b10\.\d{4,9}/[^\s"<>]+"#).expect("valid DOI pattern"));
+
+/// The pattern of an arXiv ID, either `1901.01234` or `math.AG/0601001`,
+/// after an `arXiv:` prefix or in an arxiv.org link
+static ARXIV: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:\barxiv:\s*|arxiv\.org/(?:abs|pdf)/)(\d{4}\.\d{4,5}|[a-z][a-z-]*(?:\.[a-z]{2})?/\d{7})(?:v\d+)?")
+        .expect("valid arXiv pattern")
+});
+
+/// An open PDF
+pub struct Pdf {
+    document: Document,
+}
+
+/// What litrev reads from a PDF to link it and draft an entry for it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PdfMetadata {
+    /// The title in the XMP metadata or else the info dictionary, if it has
+    /// a real one
+    pub title: Option<String>,
+    /// The authors in the XMP metadata, or else the info dictionary
+    pub authors: Vec<String>,
+    /// The year of publication in the XMP metadata
+    pub year: Option<u16>,
+    /// The DOIs in the metadata and then on the first page with any,
+    /// normalised with [`normalise_doi`]
+    pub dois: Vec<String>,
+    /// The arXiv IDs on the first page with any, without versions
+    pub arxiv_ids: Vec<String>,
+    /// The first [`FIRST_PAGES`] pages
+    pub pages: Vec<Page>,
+}
+
+impl Pdf {
+    pub fn open(path: &Path) -> Result<Self, PdfError> {
+        let options = LoadOptions::with_max_decompressed_size(MAX_DECOMPRESSED);
+        Ok(Self { document: Document::load_with_options(path, options)? })
+    }
+
+    pub fn page_count(&self) -> u32 {
+        self.document.get_pages().len() as u32
+    }
+
+    /// The text of a page, numbered from 1
+    pub fn page(&self, number: u32) -> Result<Page, PdfError> {
+        let id = *self.document.get_pages().get(&number).ok_or(PdfError::Malformed(format!("no page {number}")))?;
+        text::read_page(&self.document, number, id)
+    }
+
+    /// The text of every page. Pages whose text cannot be read are left out
+    pub fn pages(&self) -> Vec<Page> {
+        (1..=self.page_count()).filter_map(|number| self.page(number).ok()).collect()
+    }
+
+    /// The text entries of the document info dictionary
+    pub fn info(&self) -> Vec<(String, String)> {
+        let Ok(reference) = self.document.trailer.get(b"Info") else {
+            return Vec::new();
+        };
+        let dictionary = match reference {
+            Object::Reference(id) => self.document.get_dictionary(*id).ok(),
+            Object::Dictionary(dictionary) => Some(dictionary),
+            _ => None,
+        };
+        dictionary
+            .into_iter()
+            .flat_map(|dictionary| dictionary.iter())
+            .filter_map(|(key, value)| {
+                let value = lopdf::decode_text_string(value).ok()?;
+                Some((String::from_utf8_lossy(key).into_owned(), value))
+            })
+            .collect()
+    }
+
+    /// The XMP metadata of the document, empty if it has none
+    pub fn xmp(&self) -> Xmp {
+        let packet = self
+            .document
+            .catalog()
+            .and_then(|catalog| catalog.get(b"Metadata"))
+            .and_then(Object::as_reference)
+            .and_then(|id| self.document.get_object(id))
+            .and_then(Object::as_stream)
+            .and_then(|stream| stream.decompressed_content_with_limit(MAX_DECOMPRESSED));
+        match packet {
+            Ok(packet) => xmp::parse(&String::from_utf8_lossy(&packet)),
+            Err(_) => Xmp::default(),
+        }
+    }
+
+    /// The metadata of the PDF along with the text of its first pages. Pages
+    /// whose text cannot be read are left out
+    pub fn metadata(&self) -> PdfMetadata {
+        let info = self.info();
+        let xmp = self.xmp();
+        let pages: Vec<Page> = (1..=self.page_count().min(FIRST_PAGES))
+            .filter_map(|number| self.page(number).ok())
+            .collect();
+
+        let field = |key: &str| {
+            info.iter()
+                .find(|(name, value)| name == key && !value.trim().is_empty())
+                .map(|(_, value)| value.split_whitespace().collect::<Vec<_>>().join(" "))
+        };
+        let title = xmp.title.clone().into_iter().chain(field("Title")).find(|title| !is_placeholder_title(title));
+        let authors = match xmp.authors.is_empty() {
+            true => field("Author").map(|author| split_authors(&author)).unwrap_or_default(),
+            false => xmp.authors.clone(),
+        };
+
+        let mut dois: Vec<String> = info.iter().flat_map(|(_, value)| find_dois(value)).chain(xmp.dois).collect();
+        // a cover page may come before the article's own first page
+        let texts: Vec<String> = pages.iter().map(Page::text).collect();
+        dois.extend(texts.iter().map(|text| find_dois(text)).find(|found| !found.is_empty()).unwrap_or_default());
+        let mut unique = Vec::new();
+        for doi in dois {
+            if !unique.contains(&doi) {
+                unique.push(doi);
+            }
+        }
+        let arxiv_ids = texts.iter().map(|text| find_arxiv_ids(text)).find(|found| !found.is_empty()).unwrap_or_default();
+
+        PdfMetadata { title, authors, year: xmp.year, dois: unique, arxiv_ids, pages }
+    }
+}
+
+/// Reads the metadata and first pages of a PDF, see [`Pdf::metadata`]
+pub fn read(path: &Path) -> Result<PdfMetadata, PdfError> {
+    Ok(Pdf::open(path)?.metadata())
+}
+
+/// Reads the text of every page of a PDF, see [`Pdf::pages`]
+pub fn read_pages(path: &Path) -> Result<Vec<Page>, PdfError> {
+    Ok(Pdf::open(path)?.pages())
+}
+
+/// Whether a title is one that tools fill in rather than the paper's, e.g.
+/// "Microsoft Word - draft3.docx"
+fn is_placeholder_title(title: &str) -> bool {
+    let lower = title.to_lowercase();
+    lower.starts_with("microsoft word")
+        || lower == "untitled"
+        || [".doc", ".docx", ".dvi", ".pdf", ".tex"].iter().any(|extension| lower.ends_with(extension))
+}
+
+/// The names in an info dictionary's `Author`, which may separate them
+/// with `;`, `and`, `&` or, if no name is written surname first, commas
+fn split_authors(author: &str) -> Vec<String> {
+    let names: Vec<&str> = author
+        .split(';')
+        .flat_map(|part| part.split(" and "))
+        .flat_map(|part| part.split(" & "))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect();
+    match names.as_slice() {
+        [name] if name.matches(',').count() >= 2 || name.split(',').all(|part| part.trim().contains(' ')) => {
+            name.split(',').map(str::trim).filter(|name| !name.is_empty()).map(String::from).collect()
+        }
+        _ => names.into_iter().map(String::from).collect(),
+    }
+}
+
+/// Every DOI in some text, normalised
+pub fn find_dois(text: &str) -> Vec<String> {
+    DOI.find_iter(text).map(|found| normalise_doi(found.as_str())).collect()
+}
+
+/// Every arXiv ID in some text, without its version
+pub fn find_arxiv_ids(text: &str) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    for captures in ARXIV.captures_iter(text) {
+        let id = captures[1].to_string();
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    ids
+}
+
+/// A DOI in lowercase, without a resolver prefix or the punctuation of the
+/// sentence it was found in, so that two forms of one DOI compare equal
+pub fn normalise_doi(doi: &str) -> String {
+    let doi = doi.trim();
+    let doi = ["https://doi.org/", "http://doi.org/", "https://dx.doi.org/", "http://dx.doi.org/", "doi:"]
+        .iter()
+        .find_map(|prefix| doi.strip_prefix(prefix))
+        .unwrap_or(doi);
+    doi.trim_end_matches(['.', ',', ';', ':', ')', ']'])
+        .to_lowercase()
+}
+
+/// Writes a PDF with an info dictionary and a page of text for each of
+/// `pages`, for tests. Lines starting with `# ` are set large, as a title
+/// is, and words are spaced by kerning rather than spaces, as TeX does
+#[cfg(test)]
+pub(crate) fn write_sample(path: &Path, info: &[(&str, &str)], pages: &[&str]) {
+    use lopdf::content::{Content, Operation};
+    use lopdf::{dictionary, Dictionary, Stream};
+
+    let mut document = Document::with_version("1.5");
+    let pages_id = document.new_object_id();
+    let font_id = document.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Courier",
+    });
+    let resources_id = document.add_object(dictionary! { "Font" => dictionary! { "F1" => font_id } });
+    let mut kids = Vec::new();
+    for text in pages {
+        let mut operations = vec![Operation::new("BT", vec![]), Operation::new("Td", vec![50.into(), 800.into()])];
+        for line in text.lines() {
+            let (size, line) = match line.strip_prefix("# ") {
+                Some(title) => (18, title),
+                None => (10, line),
+            };
+            operations.push(Operation::new("Tf", vec!["F1".into(), size.into()]));
+            let mut words = Vec::new();
+            for word in line.split(' ') {
+                if !words.is_empty() {
+                    words.push((-250).into());
+                }
+                words.push(Object::string_literal(word));
+            }
+            operations.push(Operation::new("TJ", vec![Object::Array(words)]));
+            operations.push(Operation::new("Td", vec![0.into(), (-14).into()]));
+        }
+        operations.push(Operation::new("ET", vec![]));
+        let content = Content { operations }.encode().unwrap();
+        let content_id = document.add_object(Stream::new(dictionary! {}, content));
+        kids.push(document.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+        }).into());
+    }
+    let count = kids.len() as i64;
+    document.objects.insert(pages_id, Object::Dictionary(dictionary! {
+        "Type" => "Pages",
+        "Kids" => kids,
+        "Count" => count,
+        "Resources" => resources_id,
+        "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+    }));
+    let catalog_id = document.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    let mut dictionary = Dictionary::new();
+    for (key, value) in info {
+        dictionary.set(*key, Object::string_literal(*value));
+    }
+    let info_id = document.add_object(dictionary);
+    document.trailer.set("Root", catalog_id);
+    document.trailer.set("Info", info_id);
+    document.save(path).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read() {
+        let path = std::env::temp_dir().join(format!("litrev-pdf-{}.pdf", std::process::id()));
+        write_sample(
+            &path,
+            &[("Title", "Effective Adjoint Approaches"), ("Subject", "doi:10.1016/J.PAEROSCI.2019.05.002")],
+            &[
+                "# Effective Adjoint Approaches\nhttps://doi.org/10.2514/1.J058943. arXiv:1901.01234v2 [physics.flu-dyn]",
+                "Second page",
+                "Third page",
+            ],
+        );
+        let metadata = read(&path).unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("Effective Adjoint Approaches"));
+        assert_eq!(metadata.dois, ["10.1016/j.paerosci.2019.05.002", "10.2514/1.j058943"]);
+        assert_eq!(metadata.arxiv_ids, ["1901.01234"]);
+        assert_eq!(metadata.pages.len(), 2);
+        let title = &metadata.pages[0].lines[0];
+        assert_eq!((title.text.as_str(), title.size), ("Effective Adjoint Approaches", 18.0));
+        assert_eq!(metadata.pages[1].text(), "Second page");
+
+        std::fs::write(&path, "not a PDF").unwrap();
+        assert!(matches!(read(&path), Err(PdfError::Malformed(_))));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(normalise_doi("https://doi.org/10.2514/1.J058943"), "10.2514/1.j058943");
+        assert_eq!(find_arxiv_ids("see arxiv.org/abs/math.AG/0601001v3 and arXiv: 2101.00001"), ["math.AG/0601001", "2101.00001"]);
+        assert!(is_placeholder_title("Microsoft Word - draft3.docx"));
+        assert_eq!(split_authors("Kenway, Gaetan; Mader, Charles"), ["Kenway, Gaetan", "Mader, Charles"]);
+        assert_eq!(split_authors("Gaetan Kenway, Charles Mader"), ["Gaetan Kenway", "Charles Mader"]);
+        assert_eq!(split_authors("Kenway, G."), ["Kenway, G."]);
+    }
+}
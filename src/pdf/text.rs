@@ -0,0 +1,400 @@
+//! The text of a page as lines, each with the size of its font. Content
+//! streams only say where each run of text goes, so runs are joined into
+//! lines by their position: a run on the same baseline continues the line,
+//! with a space if there is a gap before it, and anything else starts a new
+//! one. Text inside form XObjects is not read
+
+use std::collections::{BTreeMap, HashMap};
+
+use lopdf::content::Content;
+use lopdf::{Dictionary, Document, Encoding, Object, ObjectId};
+
+use super::{PdfError, MAX_DECOMPRESSED};
+
+/// How far apart two runs of text must be, as a share of the font size, for
+/// a space to go between them
+pub(super) const WORD_GAP: f32 = 0.15;
+
+/// The width of a glyph whose font does not say, in thousandths of the font
+/// size
+const DEFAULT_WIDTH: f32 = 500.0;
+
+/// A page of text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    /// The number of the page, from 1
+    pub number: u32,
+    pub lines: Vec<Line>,
+}
+
+/// A line of text, in the order it was drawn on the page
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub text: String,
+    /// The largest font size on the line, in points
+    pub size: f32,
+}
+
+impl Page {
+    /// The lines of the page, one per line
+    pub fn text(&self) -> String {
+        self.lines.iter().map(|line| line.text.as_str()).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// An affine transformation as PDF writes it, `[a b c d e f]`
+#[derive(Debug, Clone, Copy)]
+struct Matrix([f32; 6]);
+
+impl Matrix {
+    const IDENTITY: Self = Self([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    fn translation(x: f32, y: f32) -> Self {
+        Self([1.0, 0.0, 0.0, 1.0, x, y])
+    }
+
+    /// `self` followed by `other`
+    fn then(self, other: Self) -> Self {
+        let [a, b, c, d, e, f] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = other.0;
+        Self([
+            a * a2 + b * c2,
+            a * b2 + b * d2,
+            c * a2 + d * c2,
+            c * b2 + d * d2,
+            e * a2 + f * c2 + e2,
+            e * b2 + f * d2 + f2,
+        ])
+    }
+
+    fn origin(self) -> (f32, f32) {
+        (self.0[4], self.0[5])
+    }
+
+    /// How much the transformation scales lengths by
+    fn scale(self) -> f32 {
+        let [a, b, c, d, ..] = self.0;
+        (a * d - b * c).abs().sqrt()
+    }
+
+    fn from_operands(operands: &[Object]) -> Option<Self> {
+        let numbers: Vec<f32> = operands.iter().filter_map(|operand| operand.as_float().ok()).collect();
+        numbers.try_into().ok().map(Self)
+    }
+}
+
+/// What is needed of a font to read the text set in it
+struct Font<'a> {
+    encoding: Encoding<'a>,
+    widths: Widths,
+}
+
+/// The advance of each glyph of a font, in thousandths of the font size
+enum Widths {
+    /// A font with a byte per glyph and the widths of a range of codes
+    Simple { first: u32, widths: Vec<f32> },
+    /// A composite font with two bytes per glyph
+    Composite { default: f32, widths: HashMap<u32, f32> },
+}
+
+impl Widths {
+    fn read(document: &Document, font: &Dictionary) -> Self {
+        let composite = font.get(b"Subtype").and_then(Object::as_name).is_ok_and(|subtype| subtype == b"Type0");
+        if !composite {
+            let first = font.get(b"FirstChar").and_then(Object::as_i64).unwrap_or(0).max(0) as u32;
+            let widths = resolve(document, font.get(b"Widths").ok())
+                .and_then(|widths| widths.as_array().ok())
+                .map(|widths| widths.iter().map(|width| width.as_float().unwrap_or(DEFAULT_WIDTH)).collect())
+                .unwrap_or_default();
+            return Self::Simple { first, widths };
+        }
+
+        let descendant = resolve(document, font.get(b"DescendantFonts").ok())
+            .and_then(|fonts| fonts.as_array().ok())
+            .and_then(|fonts| resolve(document, fonts.first()))
+            .and_then(|font| font.as_dict().ok());
+        let default = descendant
+            .and_then(|font| font.get(b"DW").and_then(Object::as_float).ok())
+            .unwrap_or(1000.0);
+        let mut widths = HashMap::new();
+        let array = descendant
+            .and_then(|font| resolve(document, font.get(b"W").ok()))
+            .and_then(|array| array.as_array().ok());
+        // `W` mixes `first [w1 w2 ...]` and `first last w`
+        let mut items = array.into_iter().flatten().peekable();
+        while let Some(first) = items.next().and_then(|first| first.as_i64().ok()) {
+            match items.next().map(|next| resolve(document, Some(next))) {
+                Some(Some(Object::Array(run))) => {
+                    for (i, width) in run.iter().enumerate() {
+                        widths.insert(first as u32 + i as u32, width.as_float().unwrap_or(default));
+                    }
+                }
+                Some(Some(last)) => {
+                    let (Ok(last), Some(Ok(width))) = (last.as_i64(), items.next().map(Object::as_float)) else {
+                        break;
+                    };
+                    for code in first..=last.min(first + 0xFFFF) {
+                        widths.insert(code as u32, width);
+                    }
+                }
+                _ => break,
+            }
+        }
+        Self::Composite { default, widths }
+    }
+
+    /// The codes of the glyphs in a string, with the width of each
+    fn glyphs(&self, bytes: &[u8]) -> Vec<(u32, f32)> {
+        match self {
+            Self::Simple { first, widths } => bytes
+                .iter()
+                .map(|&code| {
+                    let width = (code as u32).checked_sub(*first).and_then(|i| widths.get(i as usize));
+                    (code as u32, width.copied().unwrap_or(DEFAULT_WIDTH))
+                })
+                .collect(),
+            Self::Composite { default, widths } => bytes
+                .chunks(2)
+                .map(|pair| {
+                    let code = pair.iter().fold(0, |code, &byte| code << 8 | byte as u32);
+                    (code, widths.get(&code).copied().unwrap_or(*default))
+                })
+                .collect(),
+        }
+    }
+}
+
+fn resolve<'a>(document: &'a Document, object: Option<&'a Object>) -> Option<&'a Object> {
+    document.dereference(object?).ok().map(|(_, object)| object)
+}
+
+/// The state of the text being drawn, see section 9.3 of the PDF standard
+struct TextState<'a> {
+    font: Option<&'a Font<'a>>,
+    size: f32,
+    char_spacing: f32,
+    word_spacing: f32,
+    horizontal_scale: f32,
+    leading: f32,
+    matrix: Matrix,
+    line_matrix: Matrix,
+}
+
+/// A character drawn on a page, where it is in default user space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Glyph {
+    pub c: char,
+    /// Where the glyph starts and ends along the baseline
+    pub x: (f32, f32),
+    /// The height of the baseline
+    pub y: f32,
+    pub size: f32,
+}
+
+/// Collects runs of text into lines
+#[derive(Default)]
+struct Lines {
+    lines: Vec<Line>,
+    /// Where the last run ended, in device space
+    end: Option<(f32, f32)>,
+    /// Every glyph drawn, if they are wanted
+    glyphs: Option<Vec<Glyph>>,
+}
+
+impl Lines {
+    fn push(&mut self, text: &str, start: (f32, f32), end: (f32, f32), size: f32) {
+        if text.is_empty() {
+            return;
+        }
+        let same_line = self.end.zip(self.lines.last()).is_some_and(|((x, y), line)| {
+            let tolerance = 0.5 * size.max(line.size).max(1.0);
+            (start.1 - y).abs() <= tolerance && start.0 >= x - 2.0 * tolerance
+        });
+        match self.lines.last_mut() {
+            Some(line) if same_line => {
+                let gap = start.0 - self.end.map_or(start.0, |(x, _)| x);
+                if gap > WORD_GAP * size && !line.text.ends_with(' ') && !text.starts_with(' ') {
+                    line.text.push(' ');
+                }
+                line.text.push_str(text);
+                line.size = line.size.max(size);
+            }
+            _ => self.lines.push(Line { text: text.to_string(), size }),
+        }
+        self.end = Some(end);
+    }
+
+    fn finish(self) -> Vec<Line> {
+        self.lines
+            .into_iter()
+            .map(|line| {
+                let text = expand_ligatures(&line.text);
+                Line { text: text.split_whitespace().collect::<Vec<_>>().join(" "), size: line.size }
+            })
+            .filter(|line| !line.text.is_empty())
+            .collect()
+    }
+}
+
+/// Spells out the ligatures fonts draw as one glyph, e.g. `ﬁ` as `fi`
+pub(super) fn expand_ligatures(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_string();
+    }
+    text.chars()
+        .flat_map(|c| match c {
+            '\u{FB00}' => "ff".chars().collect(),
+            '\u{FB01}' => "fi".chars().collect(),
+            '\u{FB02}' => "fl".chars().collect(),
+            '\u{FB03}' => "ffi".chars().collect(),
+            '\u{FB04}' => "ffl".chars().collect(),
+            '\u{FB05}' | '\u{FB06}' => "st".chars().collect(),
+            c => vec![c],
+        })
+        .collect()
+}
+
+/// Reads the lines of text of a page
+pub(super) fn read_page(document: &Document, page: u32, page_id: ObjectId) -> Result<Page, PdfError> {
+    let mut lines = Lines::default();
+    draw(document, page_id, &mut lines)?;
+    Ok(Page { number: page, lines: lines.finish() })
+}
+
+/// Reads where each character of a page is drawn, in the order they are
+pub(super) fn read_glyphs(document: &Document, page_id: ObjectId) -> Result<Vec<Glyph>, PdfError> {
+    let mut lines = Lines { glyphs: Some(Vec::new()), ..Lines::default() };
+    draw(document, page_id, &mut lines)?;
+    Ok(lines.glyphs.unwrap_or_default())
+}
+
+/// Runs the content stream of a page, collecting the text it draws
+fn draw(document: &Document, page_id: ObjectId, lines: &mut Lines) -> Result<(), PdfError> {
+    let fonts: BTreeMap<Vec<u8>, Font> = document
+        .get_page_fonts(page_id)?
+        .into_iter()
+        .filter_map(|(name, font)| {
+            let encoding = font.get_font_encoding_with_limit(document, MAX_DECOMPRESSED).ok()?;
+            Some((name, Font { encoding, widths: Widths::read(document, font) }))
+        })
+        .collect();
+    let content = Content::decode(&document.get_page_content_with_limit(page_id, MAX_DECOMPRESSED)?)?;
+
+    let mut graphics = vec![Matrix::IDENTITY];
+    let mut state = TextState {
+        font: None,
+        size: 0.0,
+        char_spacing: 0.0,
+        word_spacing: 0.0,
+        horizontal_scale: 1.0,
+        leading: 0.0,
+        matrix: Matrix::IDENTITY,
+        line_matrix: Matrix::IDENTITY,
+    };
+    for operation in &content.operations {
+        let operands = &operation.operands;
+        let number = |i: usize| operands.get(i).and_then(|operand| operand.as_float().ok()).unwrap_or(0.0);
+        let ctm = *graphics.last().unwrap_or(&Matrix::IDENTITY);
+        match operation.operator.as_str() {
+            "q" => graphics.push(ctm),
+            "Q" if graphics.len() > 1 => {
+                graphics.pop();
+            }
+            "cm" => {
+                if let (Some(matrix), Some(last)) = (Matrix::from_operands(operands), graphics.last_mut()) {
+                    *last = matrix.then(ctm);
+                }
+            }
+            "BT" => {
+                state.matrix = Matrix::IDENTITY;
+                state.line_matrix = Matrix::IDENTITY;
+            }
+            "Tf" => {
+                state.font = operands.first().and_then(|name| name.as_name().ok()).and_then(|name| fonts.get(name));
+                state.size = number(1);
+            }
+            "Tc" => state.char_spacing = number(0),
+            "Tw" => state.word_spacing = number(0),
+            "Tz" => state.horizontal_scale = number(0) / 100.0,
+            "TL" => state.leading = number(0),
+            "Td" | "TD" => {
+                if operation.operator == "TD" {
+                    state.leading = -number(1);
+                }
+                state.line_matrix = Matrix::translation(number(0), number(1)).then(state.line_matrix);
+                state.matrix = state.line_matrix;
+            }
+            "Tm" => {
+                if let Some(matrix) = Matrix::from_operands(operands) {
+                    state.line_matrix = matrix;
+                    state.matrix = matrix;
+                }
+            }
+            "T*" => next_line(&mut state),
+            "Tj" => show(&mut state, operands.first(), ctm, lines),
+            "'" => {
+                next_line(&mut state);
+                show(&mut state, operands.first(), ctm, lines);
+            }
+            "\"" => {
+                state.word_spacing = number(0);
+                state.char_spacing = number(1);
+                next_line(&mut state);
+                show(&mut state, operands.get(2), ctm, lines);
+            }
+            "TJ" => {
+                for item in operands.first().and_then(|array| array.as_array().ok()).into_iter().flatten() {
+                    match item {
+                        Object::String(..) => show(&mut state, Some(item), ctm, lines),
+                        adjustment => {
+                            let shift = -adjustment.as_float().unwrap_or(0.0) / 1000.0 * state.size * state.horizontal_scale;
+                            state.matrix = Matrix::translation(shift, 0.0).then(state.matrix);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn next_line(state: &mut TextState) {
+    state.line_matrix = Matrix::translation(0.0, -state.leading).then(state.line_matrix);
+    state.matrix = state.line_matrix;
+}
+
+/// Draws a string in the current font, moving past it
+fn show(state: &mut TextState, string: Option<&Object>, ctm: Matrix, lines: &mut Lines) {
+    let (Some(font), Some(Object::String(bytes, _))) = (state.font, string) else {
+        return;
+    };
+    let text = font.encoding.bytes_to_string(bytes).unwrap_or_default();
+    let rendering = state.matrix.then(ctm);
+    let start = rendering.origin();
+    let size = state.size * rendering.scale();
+
+    let mut spans = Vec::new();
+    for (code, width) in font.widths.glyphs(bytes) {
+        let spacing = state.char_spacing + if code == 32 { state.word_spacing } else { 0.0 };
+        let advance = (width / 1000.0 * state.size + spacing) * state.horizontal_scale;
+        let from = state.matrix.then(ctm).origin().0;
+        state.matrix = Matrix::translation(advance, 0.0).then(state.matrix);
+        spans.push((from, state.matrix.then(ctm).origin().0));
+    }
+    let end = state.matrix.then(ctm).origin();
+    if let Some(glyphs) = &mut lines.glyphs {
+        let chars: Vec<char> = text.chars().collect();
+        // a code may decode to several characters or none, and then the
+        // characters are spread evenly over the run
+        let width = (end.0 - start.0) / chars.len().max(1) as f32;
+        for (i, &c) in chars.iter().enumerate() {
+            let x = match chars.len() == spans.len() {
+                true => spans[i],
+                false => (start.0 + i as f32 * width, start.0 + (i + 1) as f32 * width),
+            };
+            glyphs.push(Glyph { c, x, y: start.1, size });
+        }
+    }
+    lines.push(&text, start, end, size);
+}
@@ -0,0 +1,107 @@
+//! XMP, the RDF metadata many publishers embed in their PDFs alongside or
+//! instead of the document info dictionary
+
+use roxmltree::Node;
+
+use super::find_dois;
+
+const DUBLIN_CORE: &str = "http://purl.org/dc/elements/1.1/";
+
+/// What litrev reads from an XMP packet
+#[derive(Debug, Default, PartialEq)]
+pub struct Xmp {
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    /// The year of publication, if the packet gives one
+    pub year: Option<u16>,
+    pub dois: Vec<String>,
+}
+
+/// Reads an XMP packet, which is empty if it is not XML
+pub fn parse(packet: &str) -> Xmp {
+    let mut xmp = Xmp::default();
+    let packet = packet.trim_start_matches('\u{feff}');
+    let Ok(document) = roxmltree::Document::parse(packet) else {
+        return xmp;
+    };
+    for node in document.descendants().filter(Node::is_element) {
+        let name = node.tag_name();
+        let dublin_core = name.namespace() == Some(DUBLIN_CORE);
+        match name.name() {
+            "title" if dublin_core && xmp.title.is_none() => {
+                xmp.title = items(node).into_iter().next();
+            }
+            "creator" if dublin_core && xmp.authors.is_empty() => xmp.authors = items(node),
+            "date" if dublin_core => xmp.year = xmp.year.or_else(|| year(&text(node))),
+            "publicationDate" | "coverDate" => xmp.year = year(&text(node)).or(xmp.year),
+            "identifier" if dublin_core => xmp.dois.extend(find_dois(&text(node))),
+            "doi" | "DOI" => xmp.dois.extend(find_dois(&text(node))),
+            _ => {}
+        }
+        // simple properties may also be written as attributes
+        for attribute in node.attributes() {
+            match attribute.name() {
+                "doi" | "DOI" => xmp.dois.extend(find_dois(attribute.value())),
+                "publicationDate" | "coverDate" => xmp.year = year(attribute.value()).or(xmp.year),
+                _ => {}
+            }
+        }
+    }
+    xmp.dois.dedup();
+    xmp
+}
+
+/// The values of a property, which are the items of an `rdf:Seq`, `rdf:Bag`
+/// or `rdf:Alt` if it has one
+fn items(node: Node) -> Vec<String> {
+    let items: Vec<String> = node
+        .descendants()
+        .filter(|item| item.tag_name().name() == "li")
+        .map(text)
+        .filter(|item| !item.is_empty())
+        .collect();
+    if items.is_empty() {
+        return Some(text(node)).filter(|text| !text.is_empty()).into_iter().collect();
+    }
+    items
+}
+
+fn text(node: Node) -> String {
+    let text: String = node.descendants().filter(Node::is_text).filter_map(|text| text.text()).collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The year a date such as `2019-05-02` starts with
+fn year(date: &str) -> Option<u16> {
+    date.trim().get(..4)?.parse().ok().filter(|year| (1000..=2999).contains(year))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let xmp = parse(r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+            <x:xmpmeta xmlns:x="adobe:ns:meta/">
+              <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+                <rdf:Description xmlns:dc="http://purl.org/dc/elements/1.1/"
+                    xmlns:prism="http://prismstandard.org/namespaces/basic/2.0/"
+                    prism:doi="10.1016/j.paerosci.2019.05.002">
+                  <dc:title><rdf:Alt><rdf:li xml:lang="x-default">Effective adjoint
+                    approaches</rdf:li></rdf:Alt></dc:title>
+                  <dc:creator><rdf:Seq><rdf:li>Gaetan K.W. Kenway</rdf:li><rdf:li>Charles A. Mader</rdf:li></rdf:Seq></dc:creator>
+                  <prism:coverDate>2019-08-01</prism:coverDate>
+                </rdf:Description>
+              </rdf:RDF>
+            </x:xmpmeta>
+            <?xpacket end="w"?>"#);
+        assert_eq!(xmp, Xmp {
+            title: Some(String::from("Effective adjoint approaches")),
+            authors: vec![String::from("Gaetan K.W. Kenway"), String::from("Charles A. Mader")],
+            year: Some(2019),
+            dois: vec![String::from("10.1016/j.paerosci.2019.05.002")],
+        });
+        assert_eq!(parse("not xml"), Xmp::default());
+    }
+}
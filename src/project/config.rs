@@ -0,0 +1,108 @@
+//! The `.litrev/config.toml` of a project
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::ProjectError;
+
+/// The config version this litrev writes. Configs written by a newer
+/// litrev are refused rather than misread
+pub const CONFIG_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    pub version: u32, 
+    /// The bibliography files of the project, relative to the project root
+    #[serde(default)]
+    pub bib: Vec<PathBuf>, 
+    /// The directories searched for PDFs, relative to the project root
+    #[serde(default)]
+    pub pdf_dirs: Vec<PathBuf>, 
+    /// Where per-entry notes are kept, relative to the project root
+    #[serde(default = "default_notes_dir")]
+    pub notes_dir: PathBuf, 
+    /// The markdown file new notes are made from, relative to the project
+    /// root, see [`crate::notes::template`]. Notes are made from the
+    /// built-in template if it is not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note_template: Option<PathBuf>, 
+    /// The citation style used when none is given, e.g. "apa" or a path to
+    /// a `.csl` file
+    #[serde(default = "default_style")]
+    pub default_style: String, 
+    /// Where `litrev files organize` moves PDFs, relative to the first PDF
+    /// directory, see [`crate::files::organize`]
+    #[serde(default = "default_organize")]
+    pub organize: String, 
+}
+
+fn default_notes_dir() -> PathBuf {
+    PathBuf::from(".litrev/notes")
+}
+
+fn default_style() -> String {
+    String::from("apa")
+}
+
+fn default_organize() -> String {
+    String::from("{year}/{citekey}.pdf")
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            bib: Vec::new(),
+            pdf_dirs: Vec::new(),
+            notes_dir: default_notes_dir(),
+            note_template: None,
+            default_style: default_style(),
+            organize: default_organize(),
+        }
+    }
+}
+
+impl Config {
+    pub fn parse(contents: &str) -> Result<Self, ProjectError> {
+        let config: Config = toml::from_str(contents)?;
+        if config.version > CONFIG_VERSION {
+            return Err(ProjectError::UnsupportedVersion { found: config.version, supported: CONFIG_VERSION });
+        }
+        Ok(config)
+    }
+
+    pub fn to_toml(&self) -> Result<String, ProjectError> {
+        Ok(toml::to_string(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let config = Config {
+            bib: vec![PathBuf::from("refs.bib")],
+            pdf_dirs: vec![PathBuf::from("papers")],
+            note_template: Some(PathBuf::from("templates/note.md")),
+            ..Config::default()
+        };
+        assert_eq!(Config::parse(&config.to_toml().unwrap()).unwrap(), config);
+    }
+
+    #[test]
+    fn test_versions() {
+        let minimal = Config::parse("version = 1\n").unwrap();
+        assert_eq!(minimal.notes_dir, PathBuf::from(".litrev/notes"));
+        assert_eq!(minimal.default_style, "apa");
+        assert_eq!(minimal.organize, "{year}/{citekey}.pdf");
+
+        assert!(matches!(
+            Config::parse("version = 99\n"), 
+            Err(ProjectError::UnsupportedVersion { found: 99, .. })
+        ));
+        assert!(matches!(Config::parse("bib = []\n"), Err(ProjectError::Config(_))));
+    }
+}
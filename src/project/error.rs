@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use crate::store::StoreError;
+
+#[derive(Debug)]
+pub enum ProjectError {
+    /// No `.litrev/` directory in the starting directory or any parent, and
+    /// no global library either
+    NotFound { searched_from: PathBuf }, 
+    AlreadyInitialised(PathBuf), 
+    /// There is no home directory to put the global library in
+    NoGlobalLibrary, 
+    /// The config was written by a newer litrev
+    UnsupportedVersion { found: u32, supported: u32 }, 
+    Config(String), 
+    /// A file in `.litrev/` could not be read, e.g. a corrupted tags file
+    Data { path: PathBuf, error: String }, 
+    /// The index could not be read or brought up to date, e.g. because a
+    /// bibliography of the project is malformed
+    Store(StoreError), 
+    Io(String), 
+}
+
+impl std::fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound { searched_from } => write!(
+                f, 
+                "not inside a litrev project: no .litrev directory in {} or any of its parents. \
+                Run `litrev init` to create one here, or `litrev init --global` for a user-level library", 
+                searched_from.display(),
+            ),
+            Self::AlreadyInitialised(path) => write!(f, "a litrev project already exists at {}", path.display()),
+            Self::NoGlobalLibrary => write!(f, "could not find a home directory for the global library"),
+            Self::UnsupportedVersion { found, supported } => write!(
+                f, 
+                "config version {found} is newer than this litrev supports ({supported}), please upgrade litrev",
+            ),
+            Self::Config(e) => write!(f, "invalid config: {e}"),
+            Self::Data { path, error } => write!(f, "{}: {error}", path.display()),
+            Self::Store(e) => write!(f, "{e}"),
+            Self::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+impl From<std::io::Error> for ProjectError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e.to_string())
+    }
+}
+
+impl From<toml::de::Error> for ProjectError {
+    fn from(e: toml::de::Error) -> Self {
+        Self::Config(e.to_string())
+    }
+}
+
+impl From<toml::ser::Error> for ProjectError {
+    fn from(e: toml::ser::Error) -> Self {
+        Self::Config(e.to_string())
+    }
+}
+
+impl From<StoreError> for ProjectError {
+    fn from(e: StoreError) -> Self {
+        Self::Store(e)
+    }
+}
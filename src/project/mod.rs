@@ -0,0 +1,329 @@
+//! litrev projects: a `.litrev/` directory holding the config and
+//! everything litrev derives from the bibliographies. Commands find their
+//! project by walking up from the current directory, the way git finds
+//! `.git`, falling back to the user-level global library
+
+mod config;
+mod error;
+mod searches;
+mod tags;
+
+pub use config::{Config, CONFIG_VERSION};
+pub use error::ProjectError;
+pub use searches::{SavedSearch, SavedSearches};
+pub use tags::Tags;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::bibtex::entry::BibTeXEntry;
+use crate::link::Links;
+use crate::store::{IndexSummary, Store};
+use crate::style::case::strip_braces;
+
+/// The name of the directory that marks a project root
+pub const DIRECTORY: &str = ".litrev";
+
+/// The name of the config file inside [`DIRECTORY`]
+pub const CONFIG_FILE: &str = "config.toml";
+const TAGS_FILE: &str = "tags.json";
+const SEARCHES_FILE: &str = "searches.json";
+const LINKS_FILE: &str = "links.json";
+const INDEX_FILE: &str = "index.db";
+
+#[derive(Debug, Clone)]
+pub struct Project {
+    /// The directory containing `.litrev/`, which paths in the config are
+    /// relative to
+    root: PathBuf, 
+    config: Config, 
+}
+
+impl Project {
+    /// Creates a project in `root` with the given config
+    pub fn init(root: &Path, config: Config) -> Result<Self, ProjectError> {
+        let dir = root.join(DIRECTORY);
+        if dir.join(CONFIG_FILE).exists() {
+            return Err(ProjectError::AlreadyInitialised(root.to_path_buf()));
+        }
+        fs::create_dir_all(&dir)?;
+        let project = Self { root: root.to_path_buf(), config };
+        fs::create_dir_all(project.notes_dir())?;
+        project.save_config()?;
+        Ok(project)
+    }
+
+    /// Opens the project rooted at `root`
+    pub fn open(root: &Path) -> Result<Self, ProjectError> {
+        let path = root.join(DIRECTORY).join(CONFIG_FILE);
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| ProjectError::Io(format!("{}: {e}", path.display())))?;
+        let config = Config::parse(&contents)?;
+        Ok(Self { root: root.to_path_buf(), config })
+    }
+
+    /// Finds the project enclosing `start`, or else the global library
+    pub fn discover(start: &Path) -> Result<Self, ProjectError> {
+        Self::discover_with_global(start, global_root().ok().as_deref())
+    }
+
+    fn discover_with_global(start: &Path, global: Option<&Path>) -> Result<Self, ProjectError> {
+        let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+        if let Some(root) = start.ancestors().find(|dir| dir.join(DIRECTORY).join(CONFIG_FILE).is_file()) {
+            return Self::open(root);
+        }
+        match global {
+            Some(global) if global.join(DIRECTORY).join(CONFIG_FILE).is_file() => Self::open(global),
+            _ => Err(ProjectError::NotFound { searched_from: start }),
+        }
+    }
+
+    /// The global library, which must already have been created with
+    /// `litrev init --global`
+    pub fn global() -> Result<Self, ProjectError> {
+        Self::open(&global_root()?)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The `.litrev/` directory
+    pub fn dir(&self) -> PathBuf {
+        self.root.join(DIRECTORY)
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+
+    pub fn save_config(&self) -> Result<(), ProjectError> {
+        fs::write(self.dir().join(CONFIG_FILE), self.config.to_toml()?)?;
+        Ok(())
+    }
+
+    /// Resolves a path from the config against the project root
+    pub fn resolve(&self, path: &Path) -> PathBuf {
+        self.root.join(path)
+    }
+
+    pub fn bib_files(&self) -> Vec<PathBuf> {
+        self.config.bib.iter().map(|path| self.resolve(path)).collect()
+    }
+
+    pub fn pdf_dirs(&self) -> Vec<PathBuf> {
+        self.config.pdf_dirs.iter().map(|path| self.resolve(path)).collect()
+    }
+
+    /// Every PDF in the project's PDF directories and their subdirectories,
+    /// sorted by path. Directories that do not exist have none
+    pub fn pdf_files(&self) -> Vec<PathBuf> {
+        let mut pdfs: Vec<PathBuf> = self
+            .pdf_dirs()
+            .iter()
+            .flat_map(|dir| walkdir::WalkDir::new(dir).follow_links(true))
+            .filter_map(Result::ok)
+            .filter(|file| file.file_type().is_file())
+            .map(walkdir::DirEntry::into_path)
+            .filter(|path| path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("pdf")))
+            .collect();
+        pdfs.sort();
+        pdfs.dedup();
+        pdfs
+    }
+
+    pub fn notes_dir(&self) -> PathBuf {
+        self.resolve(&self.config.notes_dir)
+    }
+
+    /// The index of the project's bibliographies, brought up to date first
+    /// if any of them has changed since it was last indexed
+    pub fn store(&self) -> Result<Store, ProjectError> {
+        let mut store = Store::open(&self.dir().join(INDEX_FILE))?;
+        let sources = self.bib_files();
+        if !store.is_fresh(&sources)? {
+            store.update(&sources)?;
+        }
+        Ok(store)
+    }
+
+    /// Brings the index up to date with the bibliographies, returning what
+    /// changed
+    pub fn update_store(&self) -> Result<IndexSummary, ProjectError> {
+        let mut store = Store::open(&self.dir().join(INDEX_FILE))?;
+        Ok(store.update(&self.bib_files())?)
+    }
+
+    /// Deletes the index and indexes every bibliography of the project from
+    /// scratch
+    pub fn rebuild_store(&self) -> Result<IndexSummary, ProjectError> {
+        let path = self.dir().join(INDEX_FILE);
+        for stale in [path.clone(), path.with_extension("db-wal"), path.with_extension("db-shm")] {
+            match fs::remove_file(stale) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        let mut store = Store::open(&path)?;
+        Ok(store.rebuild(&self.bib_files())?)
+    }
+
+    /// Every entry of the project, from the index
+    pub fn entries(&self) -> Result<Vec<BibTeXEntry>, ProjectError> {
+        let entries = self.store()?.entries()?;
+        Ok(entries.into_iter().map(|stored| stored.entry).collect())
+    }
+
+    /// Every citekey in the library with its title, which is cheaper than
+    /// loading every entry
+    pub fn citekeys(&self) -> Result<Vec<(String, String)>, ProjectError> {
+        let citekeys = self.store()?.citekeys()?;
+        Ok(citekeys
+            .into_iter()
+            .map(|(citekey, title)| {
+                let title = strip_braces(&title).split_whitespace().collect::<Vec<_>>().join(" ");
+                (citekey, title)
+            })
+            .collect())
+    }
+
+    /// The tags of the project, empty if nothing has been tagged yet
+    pub fn tags(&self) -> Result<Tags, ProjectError> {
+        self.read_data(TAGS_FILE)
+    }
+
+    pub fn save_tags(&self, tags: &Tags) -> Result<(), ProjectError> {
+        self.write_data(TAGS_FILE, tags)
+    }
+
+    /// The saved searches of the project, empty if none have been saved
+    pub fn saved_searches(&self) -> Result<SavedSearches, ProjectError> {
+        self.read_data(SEARCHES_FILE)
+    }
+
+    pub fn save_searches(&self, searches: &SavedSearches) -> Result<(), ProjectError> {
+        self.write_data(SEARCHES_FILE, searches)
+    }
+
+    /// The links between entries and PDFs, with paths resolved against the
+    /// project root
+    pub fn links(&self) -> Result<Links, ProjectError> {
+        let mut links: Links = self.read_data(LINKS_FILE)?;
+        links.map_paths(|path| self.resolve(path));
+        Ok(links)
+    }
+
+    /// Saves the links, with the paths under the project root relative to
+    /// it so that the project can be moved
+    pub fn save_links(&self, links: &Links) -> Result<(), ProjectError> {
+        let mut links = links.clone();
+        links.map_paths(|path| path.strip_prefix(&self.root).map_or_else(|_| path.to_path_buf(), Path::to_path_buf));
+        self.write_data(LINKS_FILE, &links)
+    }
+
+    /// Reads a JSON file in `.litrev/`, which is empty until first written
+    fn read_data<T: DeserializeOwned + Default>(&self, name: &str) -> Result<T, ProjectError> {
+        let path = self.dir().join(name);
+        match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| ProjectError::Data { path, error: e.to_string() }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write_data(&self, name: &str, data: &impl Serialize) -> Result<(), ProjectError> {
+        let path = self.dir().join(name);
+        let json = serde_json::to_string_pretty(data)
+            .map_err(|e| ProjectError::Data { path: path.clone(), error: e.to_string() })?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+/// Where the global library lives: `$LITREV_HOME` if set, or else the
+/// platform's data directory, e.g. `~/.local/share/litrev`
+pub fn global_root() -> Result<PathBuf, ProjectError> {
+    if let Some(home) = std::env::var_os("LITREV_HOME") {
+        return Ok(PathBuf::from(home));
+    }
+    dirs::data_dir()
+        .map(|dir| dir.join("litrev"))
+        .ok_or(ProjectError::NoGlobalLibrary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh, empty directory for a test
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("litrev-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_init_and_discover() {
+        let root = scratch_dir("discover");
+        let config = Config { bib: vec![PathBuf::from("refs.bib")], ..Config::default() };
+        Project::init(&root, config.clone()).unwrap();
+        assert!(root.join(".litrev/config.toml").is_file());
+        assert!(root.join(".litrev/notes").is_dir());
+        assert!(matches!(Project::init(&root, Config::default()), Err(ProjectError::AlreadyInitialised(_))));
+
+        let nested = root.join("chapters/intro");
+        fs::create_dir_all(&nested).unwrap();
+        let project = Project::discover_with_global(&nested, None).unwrap();
+        assert_eq!(project.root(), root.canonicalize().unwrap());
+        assert_eq!(project.config(), &config);
+        assert_eq!(project.bib_files(), [project.root().join("refs.bib")]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_global_fallback() {
+        let outside = scratch_dir("outside");
+        let global = scratch_dir("global");
+        assert!(matches!(
+            Project::discover_with_global(&outside, Some(&global)), 
+            Err(ProjectError::NotFound { .. })
+        ));
+
+        Project::init(&global, Config::default()).unwrap();
+        let project = Project::discover_with_global(&outside, Some(&global)).unwrap();
+        assert_eq!(project.root(), global);
+
+        fs::remove_dir_all(&outside).unwrap();
+        fs::remove_dir_all(&global).unwrap();
+    }
+
+    #[test]
+    fn test_store_refresh() {
+        let root = scratch_dir("store");
+        let bib = root.join("refs.bib");
+        fs::write(&bib, "@article{Smith2019, title = {Adjoint {CFD}\n  Methods}}").unwrap();
+        let config = Config { bib: vec![PathBuf::from("refs.bib")], ..Config::default() };
+        let project = Project::init(&root, config).unwrap();
+
+        assert_eq!(project.citekeys().unwrap(), [(String::from("Smith2019"), String::from("Adjoint CFD Methods"))]);
+        assert!(root.join(".litrev").join(INDEX_FILE).is_file());
+
+        // a bibliography that changed since it was indexed is indexed again
+        fs::write(&bib, "@article{Doe2020, title = {Other}}").unwrap();
+        fs::File::options().write(true).open(&bib).unwrap()
+            .set_modified(std::time::SystemTime::UNIX_EPOCH).unwrap();
+        assert_eq!(project.entries().unwrap()[0].citekey, "Doe2020");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
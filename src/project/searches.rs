@@ -0,0 +1,93 @@
+//! Saved searches, kept in `.litrev/searches.json`. A saved search is run
+//! again every time it is used, so it doubles as a smart group that follows
+//! the library as it changes
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+const SEARCHES_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSearches {
+    version: u32, 
+    searches: BTreeMap<String, SavedSearch>, 
+}
+
+/// A search as it was given to `litrev search`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    /// The query, empty if the search only has patterns
+    pub query: String, 
+    /// The `field=pattern` regular expressions entries must also match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub regex: Vec<String>, 
+    /// Whether every word of the query tolerates typos
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fuzzy: bool, 
+}
+
+impl Default for SavedSearches {
+    fn default() -> Self {
+        Self { version: SEARCHES_VERSION, searches: BTreeMap::new() }
+    }
+}
+
+impl SavedSearches {
+    pub fn get(&self, name: &str) -> Option<&SavedSearch> {
+        self.searches.get(name)
+    }
+
+    /// Saves a search under a name, replacing any saved under it before
+    pub fn insert(&mut self, name: &str, search: SavedSearch) -> Result<(), String> {
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("invalid name '{name}', use letters, digits, '-' and '_'"));
+        }
+        self.searches.insert(name.to_string(), search);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<SavedSearch> {
+        self.searches.remove(name)
+    }
+
+    /// The saved searches in alphabetical order of their names
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SavedSearch)> {
+        self.searches.iter().map(|(name, search)| (name.as_str(), search))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert() {
+        let mut searches = SavedSearches::default();
+        let search = SavedSearch { query: String::from("adjoint year:2018.."), ..SavedSearch::default() };
+        searches.insert("to-read", search.clone()).unwrap();
+        assert!(searches.insert("to read", search.clone()).is_err());
+        assert_eq!(searches.get("to-read"), Some(&search));
+
+        let json = serde_json::to_string(&searches).unwrap();
+        assert_eq!(json, r#"{"version":1,"searches":{"to-read":{"query":"adjoint year:2018.."}}}"#);
+        assert_eq!(serde_json::from_str::<SavedSearches>(&json).unwrap(), searches);
+    }
+
+    #[test]
+    fn test_overwrite_and_remove() {
+        let mut searches = SavedSearches::default();
+        let old = SavedSearch { query: String::from("adjoint"), fuzzy: true, ..SavedSearch::default() };
+        let new = SavedSearch { query: String::new(), regex: vec![String::from("title=^On")], ..SavedSearch::default() };
+        searches.insert("reading", old).unwrap();
+        // saving under a name taken replaces the search rather than merging
+        // with it
+        searches.insert("reading", new.clone()).unwrap();
+        assert_eq!(searches.iter().collect::<Vec<_>>(), [("reading", &new)]);
+
+        assert_eq!(searches.remove("reading"), Some(new));
+        assert_eq!(searches.remove("reading"), None);
+        assert_eq!(searches.get("reading"), None);
+        assert_eq!(serde_json::to_string(&searches).unwrap(), r#"{"version":1,"searches":{}}"#);
+    }
+}
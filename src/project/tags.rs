@@ -0,0 +1,83 @@
+//! User tags on entries, kept in `.litrev/tags.json` rather than in the
+//! bibliographies so that shared `.bib` files are left untouched
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+const TAGS_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tags {
+    version: u32, 
+    /// The tags of each citekey
+    tags: BTreeMap<String, BTreeSet<String>>, 
+}
+
+impl Default for Tags {
+    fn default() -> Self {
+        Self { version: TAGS_VERSION, tags: BTreeMap::new() }
+    }
+}
+
+impl Tags {
+    /// The tags of an entry, in alphabetical order
+    pub fn of(&self, citekey: &str) -> Vec<String> {
+        self.tags.get(citekey).map_or_else(Vec::new, |tags| tags.iter().cloned().collect())
+    }
+
+    pub fn has(&self, citekey: &str, tag: &str) -> bool {
+        self.tags.get(citekey).is_some_and(|tags| tags.contains(tag))
+    }
+
+    /// Adds a tag, normalised to lowercase with surrounding whitespace
+    /// removed
+    pub fn add(&mut self, citekey: &str, tag: &str) {
+        let tag = normalise(tag);
+        if !tag.is_empty() {
+            self.tags.entry(citekey.to_string()).or_default().insert(tag);
+        }
+    }
+
+    pub fn remove(&mut self, citekey: &str, tag: &str) {
+        if let Some(tags) = self.tags.get_mut(citekey) {
+            tags.remove(&normalise(tag));
+            if tags.is_empty() {
+                self.tags.remove(citekey);
+            }
+        }
+    }
+
+    /// Every tag in use and how many entries have it
+    pub fn counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for tag in self.tags.values().flatten() {
+            *counts.entry(tag.as_str()).or_default() += 1;
+        }
+        counts
+    }
+}
+
+fn normalise(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_remove() {
+        let mut tags = Tags::default();
+        tags.add("Smith2019", " CFD ");
+        tags.add("Smith2019", "adjoint");
+        tags.add("Doe2020", "cfd");
+        assert_eq!(tags.of("Smith2019"), ["adjoint", "cfd"]);
+        assert!(tags.has("Doe2020", "cfd"));
+        assert_eq!(tags.counts().get("cfd"), Some(&2));
+
+        tags.remove("Doe2020", "CFD");
+        assert!(tags.of("Doe2020").is_empty());
+        assert_eq!(tags.counts().get("cfd"), Some(&1));
+    }
+}
@@ -0,0 +1,153 @@
+//! The citation graph of the library, with an entry for each node and a
+//! citation for each edge, written as DOT, GraphML or JSON
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::bibtex::entry::BibTeXEntry;
+use crate::bibtex::latex;
+use crate::store::Citation;
+
+use super::Evidence;
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+/// An entry that cites or is cited
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Node {
+    pub citekey: String,
+    pub title: Option<String>,
+    pub year: Option<u16>,
+}
+
+/// A citation from one entry to another
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Edge {
+    pub citing: String,
+    pub cited: String,
+    pub evidence: Evidence,
+}
+
+impl Graph {
+    /// The graph of the citations, with the entries that take part in them
+    /// in library order
+    pub fn new(entries: &[BibTeXEntry], citations: &[Citation]) -> Self {
+        let cited: HashSet<&str> = citations
+            .iter()
+            .flat_map(|citation| [citation.citing.as_str(), citation.cited.as_str()])
+            .collect();
+        let nodes = entries
+            .iter()
+            .filter(|entry| cited.contains(entry.citekey.as_str()))
+            .map(|entry| Node {
+                citekey: entry.citekey.clone(),
+                title: entry.title().map(|title| latex::decode(title)),
+                year: entry.year().copied(),
+            })
+            .collect();
+        let edges = citations
+            .iter()
+            .map(|citation| Edge { citing: citation.citing.clone(), cited: citation.cited.clone(), evidence: citation.evidence })
+            .collect();
+        Self { nodes, edges }
+    }
+
+    /// The graph in Graphviz's DOT language, labelling each node with its
+    /// citekey and title
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph citations {\n    node [shape=box];\n");
+        for node in &self.nodes {
+            let label = match &node.title {
+                Some(title) => format!("{}\n{title}", node.citekey),
+                None => node.citekey.clone(),
+            };
+            dot.push_str(&format!("    {} [label={}];\n", dot_string(&node.citekey), dot_string(&label)));
+        }
+        for edge in &self.edges {
+            dot.push_str(&format!("    {} -> {};\n", dot_string(&edge.citing), dot_string(&edge.cited)));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The graph in GraphML, with the title and year of each node and the
+    /// evidence of each edge as data
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n",
+            "  <key id=\"year\" for=\"node\" attr.name=\"year\" attr.type=\"int\"/>\n",
+            "  <key id=\"evidence\" for=\"edge\" attr.name=\"evidence\" attr.type=\"string\"/>\n",
+            "  <graph id=\"citations\" edgedefault=\"directed\">\n",
+        ));
+        for node in &self.nodes {
+            xml.push_str(&format!("    <node id=\"{}\">\n", xml_escape(&node.citekey)));
+            if let Some(title) = &node.title {
+                xml.push_str(&format!("      <data key=\"title\">{}</data>\n", xml_escape(title)));
+            }
+            if let Some(year) = node.year {
+                xml.push_str(&format!("      <data key=\"year\">{year}</data>\n"));
+            }
+            xml.push_str("    </node>\n");
+        }
+        for edge in &self.edges {
+            xml.push_str(&format!(
+                "    <edge source=\"{}\" target=\"{}\">\n      <data key=\"evidence\">{}</data>\n    </edge>\n",
+                xml_escape(&edge.citing),
+                xml_escape(&edge.cited),
+                edge.evidence.name(),
+            ));
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+}
+
+/// A quoted DOT string
+fn dot_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibtex::parser::BibTeXParser;
+
+    #[test]
+    fn test_graph() {
+        let entries = BibTeXParser::new(r#"
+            @article{A, title = {Flows \& {"Waves"}}, year = {2020}}
+            @article{B, title = {Adjoints}}
+            @article{C, title = {Uncited}}
+        "#).parse().unwrap();
+        let citations = [Citation {
+            citing: String::from("A"),
+            cited: String::from("B"),
+            evidence: Evidence::Doi,
+            reference: String::from("[1] B, Adjoints"),
+        }];
+        let graph = Graph::new(&entries, &citations);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.to_dot(), r#"digraph citations {
+    node [shape=box];
+    "A" [label="A\nFlows & \"Waves\""];
+    "B" [label="B\nAdjoints"];
+    "A" -> "B";
+}
+"#);
+        let graphml = graph.to_graphml();
+        assert!(graphml.contains(r#"<data key="title">Flows &amp; &quot;Waves&quot;</data>"#));
+        assert!(graphml.contains("<edge source=\"A\" target=\"B\">\n      <data key=\"evidence\">doi</data>"));
+        roxmltree::Document::parse(&graphml).unwrap();
+    }
+}
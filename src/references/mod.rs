@@ -0,0 +1,453 @@
+//! The reference lists of papers, read from the text of their PDFs, and
+//! which entries of the library the references are to. A reference list is
+//! the text after the last `References` or `Bibliography` heading, split
+//! into references by their numbers or, in author-year styles, by where a
+//! new surname starts a line
+
+pub mod graph;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::bibtex::entry::BibTeXEntry;
+use crate::pdf::{find_dois, normalise_doi};
+use crate::search::{fuzzy, text};
+use crate::store::{Citation, PageText};
+
+/// A heading that starts a reference list, possibly numbered
+static HEADING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:[0-9]+\.?\s+|[ivx]+\.\s+)?(?:references|bibliography|works cited|literature cited|references and notes)$")
+        .expect("valid heading pattern")
+});
+
+/// A heading that ends a reference list
+static END: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:[a-z]\.?\s+)?(?:appendix|appendices|supplementary material)\b").expect("valid end pattern")
+});
+
+/// The number a reference starts with in numbered styles, `[12]` or `12.`
+static NUMBER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*(?:\[\d{1,4}\]|\d{1,4}\.\s)").expect("valid number pattern"));
+
+/// A surname and a comma, which start a reference in author-year styles
+static SURNAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:(?:van|von|de|der|den|di|da|du|le|la)\s+)*\p{Lu}[\p{L}'’-]+(?:\s\p{Lu}[\p{L}'’-]+)?,\s").expect("valid surname pattern")
+});
+
+static YEAR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(?:1[89]|20)\d{2}").expect("valid year pattern"));
+
+/// A year in brackets, as author-year styles put it after the authors
+static BRACKETED_YEAR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\(((?:1[89]|20)\d{2})[a-z]?\)").expect("valid year pattern"));
+
+/// The sentence after a year that ends the authors, which is the title in
+/// author-year styles, e.g. `Kenway, G. (2019). Effective adjoint ...`
+static TITLE_AFTER_YEAR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[^(]{0,200}?(?:\((?:1[89]|20)\d{2}[a-z]?\)|\b(?:1[89]|20)\d{2}[a-z]?)\.?\s+([^.?!]+)").expect("valid title pattern")
+});
+
+/// A title in quotes, as many numbered styles put it
+static QUOTED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"[“"]([^”"]{8,})[”"]"#).expect("valid quote pattern"));
+
+/// The lowercase words that may start a surname
+const PARTICLES: &[&str] = &["da", "de", "del", "der", "den", "di", "du", "la", "le", "van", "von"];
+
+/// How many of the lines after a heading are looked at to tell whether the
+/// references are numbered
+const STYLE_LINES: usize = 10;
+
+/// How many of a long title's words may be misspelt or broken in a
+/// reference, as a share of them
+const TITLE_TYPOS: f64 = 0.15;
+
+/// A reference in a paper's reference list, with what could be made out of
+/// it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Reference {
+    /// The page the reference starts on
+    pub page: u32,
+    /// The reference on one line, with words broken over lines put back
+    /// together
+    pub text: String,
+    /// The surnames of the authors, as far as they could be told apart
+    pub authors: Vec<String>,
+    pub year: Option<u16>,
+    pub title: Option<String>,
+    /// The DOI, normalised
+    pub doi: Option<String>,
+}
+
+/// How a reference was found to be to an entry, from the most to the least
+/// certain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Evidence {
+    /// The reference has the entry's DOI
+    Doi,
+    /// The reference has the entry's title, give or take a typo, along with
+    /// its first author's surname or its year
+    Title,
+    /// The reference has the entry's first author's surname and year, but
+    /// not its title, which some styles leave out
+    AuthorYear,
+}
+
+impl Evidence {
+    /// The name the evidence is stored and serialised under
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Doi        => "doi",
+            Self::Title      => "title",
+            Self::AuthorYear => "author-year",
+        }
+    }
+}
+
+impl std::str::FromStr for Evidence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "doi"         => Ok(Self::Doi),
+            "title"       => Ok(Self::Title),
+            "author-year" => Ok(Self::AuthorYear),
+            _ => Err(format!("unknown evidence '{s}'")),
+        }
+    }
+}
+
+impl std::fmt::Display for Evidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Doi        => write!(f, "DOI"),
+            Self::Title      => write!(f, "title"),
+            Self::AuthorYear => write!(f, "author and year"),
+        }
+    }
+}
+
+/// The references in the reference list of a paper, empty if it has no
+/// reference list that could be found
+pub fn reference_list(pages: &[PageText]) -> Vec<Reference> {
+    let lines: Vec<(u32, &str)> = pages
+        .iter()
+        .flat_map(|page| page.text.lines().map(move |line| (page.number, line.trim())))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+    let Some(heading) = lines.iter().rposition(|(_, line)| HEADING.is_match(line)) else {
+        return Vec::new();
+    };
+    let lines = &lines[heading + 1..];
+    let lines = &lines[..lines.iter().position(|(_, line)| END.is_match(line)).unwrap_or(lines.len())];
+
+    let numbered = lines.iter().take(STYLE_LINES).filter(|(_, line)| NUMBER.is_match(line)).count() >= 2;
+    let mut texts: Vec<(u32, String)> = Vec::new();
+    let mut previous = "";
+    for &(page, line) in lines {
+        let starts = match numbered {
+            true => NUMBER.is_match(line),
+            false => previous.is_empty() || (previous.ends_with('.') && SURNAME.is_match(line)),
+        };
+        match texts.last_mut() {
+            Some((_, text)) if !starts => join_line(text, line),
+            _ => texts.push((page, line.to_string())),
+        }
+        previous = line;
+    }
+    texts.into_iter().map(|(page, text)| parse(page, text)).collect()
+}
+
+/// Adds a line to the text of a reference, putting a word hyphenated over
+/// the line break back together
+fn join_line(text: &mut String, line: &str) {
+    if !text.ends_with('-') {
+        text.push(' ');
+    } else if line.starts_with(char::is_lowercase) && text[..text.len() - 1].ends_with(char::is_lowercase) {
+        // a DOI or URL may break at any hyphen, so only the hyphens of
+        // broken words are dropped
+        text.pop();
+    }
+    text.push_str(line);
+}
+
+/// Makes out the authors, year, title and DOI of a reference. The title is
+/// the quoted part, or the sentence after the year in author-year styles,
+/// or else the first part between commas that is not a name
+fn parse(page: u32, text: String) -> Reference {
+    let body = NUMBER.replace(&text, "").trim().to_string();
+    let doi = find_dois(&body).into_iter().next();
+    let year = BRACKETED_YEAR
+        .captures(&body)
+        .map(|captures| captures[1].to_string())
+        .or_else(|| YEAR.find_iter(&body).last().map(|year| year.as_str().to_string()))
+        .and_then(|year| year.parse().ok());
+
+    let is_title = |title: &str| title.chars().any(char::is_alphabetic) && !title.contains("://") && !title.contains("doi");
+    let quoted = QUOTED.captures(&body).map(|captures| captures.get(1).expect("a quoted title").range());
+    let after_year = TITLE_AFTER_YEAR
+        .captures(&body)
+        .map(|captures| captures.get(1).expect("a title after the year").range())
+        .filter(|range| body[range.clone()].split_whitespace().count() >= 2 && is_title(&body[range.clone()]));
+    let (title, names) = match quoted.or(after_year) {
+        Some(range) => {
+            let names = body[..range.start].split(['(', '“', '"']).next().unwrap_or_default();
+            (Some(body[range].trim().trim_end_matches([',', '.']).to_string()), names.to_string())
+        }
+        None => {
+            let parts: Vec<&str> = body.split(", ").collect();
+            let names = parts.iter().take_while(|part| is_names(part)).count();
+            let title = parts[names..].first().filter(|part| is_title(part) && !part.chars().any(|c| c.is_ascii_digit()));
+            (title.map(|title| title.trim_end_matches('.').to_string()), parts[..names].join(", "))
+        }
+    };
+    let authors = names.split([',', ';', '&']).flat_map(|part| part.split(" and ")).filter_map(surname).collect();
+
+    Reference { page, text, authors, year, title, doi }
+}
+
+/// Whether a part of a reference between commas is only names or initials
+fn is_names(part: &str) -> bool {
+    let words: Vec<&str> = part.split_whitespace().filter(|&word| word != "and" && word != "&").collect();
+    !words.is_empty()
+        && words.len() <= 5
+        && words.iter().all(|word| {
+            is_initials(word) || PARTICLES.contains(word) || word.starts_with(char::is_uppercase) && word.chars().all(|c| c.is_alphabetic() || "'’-".contains(c))
+        })
+}
+
+/// Whether a word is initials, e.g. `G.`, `G.K.W.` or, in Vancouver style,
+/// `GKW`
+fn is_initials(word: &str) -> bool {
+    let letters = word.replace(['.', '-'], "");
+    !letters.is_empty() && (word.contains('.') || letters.chars().count() <= 3) && letters.chars().all(char::is_uppercase)
+}
+
+/// The surname in a name with its initials, e.g. `Leer` in `B. van Leer`
+fn surname(name: &str) -> Option<String> {
+    let words: Vec<&str> = name
+        .split_whitespace()
+        .filter(|&word| !is_initials(word) && !matches!(word, "et" | "al" | "al." | "and"))
+        .collect();
+    let start = words.iter().position(|word| word.starts_with(char::is_uppercase) || PARTICLES.contains(word))?;
+    let surname = words[start..].join(" ");
+    surname.chars().any(char::is_uppercase).then_some(surname)
+}
+
+/// How a reference is to an entry, if it is. A DOI in the reference that
+/// is not the entry's rules it out
+pub fn evidence(entry: &BibTeXEntry, reference: &str) -> Option<Evidence> {
+    Haystack::new(reference).evidence(entry)
+}
+
+/// A reference prepared for matching against many entries
+struct Haystack<'a> {
+    text: &'a str,
+    words: Vec<String>,
+    dois: Vec<String>,
+}
+
+impl<'a> Haystack<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, words: text::words(text), dois: find_dois(text) }
+    }
+
+    fn evidence(&self, entry: &BibTeXEntry) -> Option<Evidence> {
+        if let Some(doi) = entry.doi().map(|doi| normalise_doi(doi)) {
+            if self.dois.contains(&doi) {
+                return Some(Evidence::Doi);
+            }
+            if !self.dois.is_empty() {
+                return None;
+            }
+        }
+
+        let has_surname = first_surname(entry).is_some_and(|surname| contains(&self.words, &surname));
+        let has_year = entry
+            .year()
+            .is_some_and(|year| YEAR.find_iter(self.text).any(|found| found.as_str() == year.to_string()));
+        let title = entry.title().map(|title| text::words(title)).unwrap_or_default();
+        if !title.is_empty() && contains_title(&self.words, &title) && (has_surname || has_year) {
+            return Some(Evidence::Title);
+        }
+        (has_surname && has_year).then_some(Evidence::AuthorYear)
+    }
+}
+
+fn first_surname(entry: &BibTeXEntry) -> Option<Vec<String>> {
+    entry
+        .author()
+        .and_then(|authors| authors.first())
+        .map(|author| text::words(author.surname()))
+        .filter(|surname| !surname.is_empty())
+}
+
+fn contains(words: &[String], phrase: &[String]) -> bool {
+    words.windows(phrase.len()).any(|window| window == phrase)
+}
+
+/// Whether the words of a title appear one after another. Long titles may
+/// have a few words misspelt or broken apart in the reference
+fn contains_title(words: &[String], title: &[String]) -> bool {
+    let allowed = (title.len() as f64 * TITLE_TYPOS) as usize;
+    if allowed == 0 {
+        return contains(words, title);
+    }
+    words.windows(title.len()).any(|window| {
+        let misses = window.iter().zip(title).filter(|(word, expected)| word != expected && !fuzzy::matches(expected, word)).count();
+        misses <= allowed
+    })
+}
+
+/// Resolves references to the entries of a library
+pub struct Resolver<'a> {
+    entries: &'a [BibTeXEntry],
+    /// The entries with each word of their titles or first surnames
+    by_word: HashMap<String, Vec<usize>>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(entries: &'a [BibTeXEntry]) -> Self {
+        let mut by_word: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, entry) in entries.iter().enumerate() {
+            let title = entry.title().map(|title| text::words(title)).unwrap_or_default();
+            let words: HashSet<String> = title.into_iter().chain(first_surname(entry).unwrap_or_default()).collect();
+            for word in words {
+                by_word.entry(word).or_default().push(i);
+            }
+        }
+        Self { entries, by_word }
+    }
+
+    /// The entry a reference is to, if any. A reference that is only
+    /// matched by its author and year has to match one entry alone
+    pub fn resolve(&self, reference: &Reference) -> Option<(&'a BibTeXEntry, Evidence)> {
+        let haystack = Haystack::new(&reference.text);
+        let mut candidates: Vec<usize> = haystack.words.iter().filter_map(|word| self.by_word.get(word)).flatten().copied().collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+        if reference.doi.is_some() {
+            // an entry with the DOI may share no word with the reference
+            candidates = (0..self.entries.len()).collect();
+        }
+
+        let mut found: Vec<(&BibTeXEntry, Evidence)> = candidates
+            .into_iter()
+            .filter_map(|i| Some((&self.entries[i], haystack.evidence(&self.entries[i])?)))
+            .collect();
+        found.sort_by_key(|&(_, evidence)| evidence);
+        match found.as_slice() {
+            [(entry, Evidence::AuthorYear)] => Some((entry, Evidence::AuthorYear)),
+            [(_, Evidence::AuthorYear), ..] | [] => None,
+            [first, ..] => Some(*first),
+        }
+    }
+
+    /// The entries an entry cites, from the reference list in the text of
+    /// its PDF
+    pub fn citations(&self, citing: &BibTeXEntry, pages: &[PageText]) -> Vec<Citation> {
+        let mut citations: Vec<Citation> = Vec::new();
+        for reference in reference_list(pages) {
+            let Some((cited, evidence)) = self.resolve(&reference) else {
+                continue;
+            };
+            // a paper may list itself, e.g. in a "cite this article" box
+            if cited.citekey != citing.citekey && !citations.iter().any(|citation| citation.cited == cited.citekey) {
+                citations.push(Citation {
+                    citing: citing.citekey.clone(),
+                    cited: cited.citekey.clone(),
+                    evidence,
+                    reference: reference.text,
+                });
+            }
+        }
+        citations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibtex::parser::BibTeXParser;
+
+    fn page(number: u32, text: &str) -> PageText {
+        PageText { number, text: text.to_string() }
+    }
+
+    #[test]
+    fn test_reference_list() {
+        let pages = [
+            page(1, "1 Introduction\nAs shown in [1], the references are at the end."),
+            page(9, "6 References\n[1] G. K. W. Kenway, C. A. Mader, P. He, Effective adjoint ap-\nproaches for computational fluid dynamics, Prog. Aerosp."),
+            page(10, "Sci. 109 (2019) 100542.\n[2] H. Müller, “Turbulence Modelling,” 2012.\nAppendix A. Derivations"),
+        ];
+        let references = reference_list(&pages);
+        assert_eq!(references, [
+            Reference {
+                page: 9,
+                text: String::from("[1] G. K. W. Kenway, C. A. Mader, P. He, Effective adjoint approaches for computational fluid dynamics, Prog. Aerosp. Sci. 109 (2019) 100542."),
+                authors: vec![String::from("Kenway"), String::from("Mader"), String::from("He")],
+                year: Some(2019),
+                title: Some(String::from("Effective adjoint approaches for computational fluid dynamics")),
+                doi: None,
+            },
+            Reference {
+                page: 10,
+                text: String::from("[2] H. Müller, “Turbulence Modelling,” 2012."),
+                authors: vec![String::from("Müller")],
+                year: Some(2012),
+                title: Some(String::from("Turbulence Modelling")),
+                doi: None,
+            },
+        ]);
+
+        // author-year references start where a surname follows a full stop
+        let pages = [page(3, "References\nKenway, G. K. W., Mader, C. A.\n(2019). Effective adjoint approaches. Prog. Aerosp. Sci.\nvan Leer, B. (1979). Towards the ultimate\nconservative difference scheme. doi:10.1016/0021-9991(79)90145-1")];
+        let references = reference_list(&pages);
+        assert_eq!(references.len(), 2);
+        assert_eq!(references[0].authors, ["Kenway", "Mader"]);
+        assert_eq!(references[0].title.as_deref(), Some("Effective adjoint approaches"));
+        assert_eq!(references[1].authors, ["van Leer"]);
+        assert_eq!(references[1].year, Some(1979));
+        assert_eq!(references[1].title.as_deref(), Some("Towards the ultimate conservative difference scheme"));
+        assert_eq!(references[1].doi.as_deref(), Some("10.1016/0021-9991(79)90145-1"));
+        assert!(reference_list(&[page(1, "No reference list here")]).is_empty());
+    }
+
+    const LIBRARY: &str = r#"
+        @article{Kenway2019, author = {Kenway, Gaetan}, year = {2019}, doi = {10.1016/j.paerosci.2019.05.002},
+            title = {Effective Adjoint Approaches for Computational Fluid Dynamics}}
+        @article{Muller2012, author = {M{\"u}ller, Hans}, title = {Turbulence Modelling}, year = {2012}}
+        @article{Muller2012b, author = {M{\"u}ller, Hans}, title = {Wall Functions}, year = {2012}}
+    "#;
+
+    #[test]
+    fn test_evidence() {
+        let entries = BibTeXParser::new(LIBRARY).parse().unwrap();
+        let (kenway, muller) = (&entries[0], &entries[1]);
+
+        assert_eq!(evidence(kenway, "Kenway G. Prog. Aerosp. Sci. https://doi.org/10.1016/J.PAEROSCI.2019.05.002."), Some(Evidence::Doi));
+        assert_eq!(evidence(kenway, "Kenway (2019) Effective adjoint approaches for computational fluid dynamics."), Some(Evidence::Title));
+        assert_eq!(evidence(kenway, "Kenway (2019) Efective adjoint approaches for computational fluid dynamics."), Some(Evidence::Title));
+        assert_eq!(evidence(kenway, "Kenway (2019) Effective adjoint approaches, doi:10.1234/other"), None);
+        assert_eq!(evidence(muller, "H. Müller, Turbulence Modelling, 2012."), Some(Evidence::Title));
+        assert_eq!(evidence(muller, "Muller H., Phys. Fluids 3 (2012) 45."), Some(Evidence::AuthorYear));
+        assert_eq!(evidence(muller, "Muller H., Phys. Fluids 3 (2013) 45."), None);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let entries = BibTeXParser::new(LIBRARY).parse().unwrap();
+        let resolver = Resolver::new(&entries);
+        let resolve = |text: &str| {
+            let reference = parse(1, text.to_string());
+            resolver.resolve(&reference).map(|(entry, evidence)| (entry.citekey.as_str(), evidence))
+        };
+        assert_eq!(resolve("[3] G. Kenway, doi:10.1016/j.paerosci.2019.05.002"), Some(("Kenway2019", Evidence::Doi)));
+        assert_eq!(resolve("[4] H. Muller, Wall functions, Phys. Fluids (2012)."), Some(("Muller2012b", Evidence::Title)));
+        // two entries by the same author in the same year cannot be told
+        // apart without a title
+        assert_eq!(resolve("[5] H. Muller, Phys. Fluids 3 (2012) 45."), None);
+        assert_eq!(resolve("[6] A. Nobody, Something else (2020)."), None);
+    }
+}
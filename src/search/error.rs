@@ -0,0 +1,25 @@
+/// A query that could not be parsed. It keeps the query so that it can
+/// point at where things went wrong
+#[derive(Debug, PartialEq)]
+pub struct QueryError {
+    pub query: String, 
+    /// The character the error was found at
+    pub position: usize, 
+    pub message: String, 
+}
+
+impl QueryError {
+    pub fn new(query: &str, position: usize, message: impl Into<String>) -> Self {
+        Self { query: query.to_string(), position, message: message.into() }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl std::fmt::Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "invalid query: {}", self.message)?;
+        writeln!(f, "  {}", self.query)?;
+        write!(f, "  {}^", " ".repeat(self.position))
+    }
+}
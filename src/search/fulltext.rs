@@ -0,0 +1,174 @@
+//! Searching the text of the PDFs linked to entries with `fulltext:`, and
+//! the snippets of it shown alongside the results
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use super::{text, window_matches, Query};
+
+/// The query field that looks in the text of linked PDFs
+pub const FIELD: &str = "fulltext";
+
+/// How many words of context a snippet has on either side of its match
+const CONTEXT: usize = 8;
+
+/// The pages of the PDFs linked to each entry, by citekey
+pub type FullText = HashMap<String, Vec<TextPage>>;
+
+/// A page of a PDF linked to an entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextPage {
+    pub path: PathBuf,
+    /// The page number, from 1
+    pub number: u32,
+    pub text: String,
+}
+
+/// Where a query matched the text of a PDF, with some words around it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snippet {
+    pub path: PathBuf,
+    pub page: u32,
+    /// The text around the match, on one line
+    pub text: String,
+    /// The byte ranges of the text that matched
+    pub spans: Vec<Range<usize>>,
+}
+
+/// Whether a query looks in the text of PDFs at all
+pub fn uses_full_text(query: &Query) -> bool {
+    query.fields().contains(&FIELD)
+}
+
+/// A snippet for each page the `fulltext:` words of a query appear on, in
+/// page order. Words under `NOT` are not looked for
+pub fn snippets(pages: &[TextPage], query: &Query) -> Vec<Snippet> {
+    let mut phrases = Vec::new();
+    collect_phrases(query, &mut phrases);
+    if phrases.is_empty() {
+        return Vec::new();
+    }
+
+    let mut snippets = Vec::new();
+    for page in pages {
+        let words = spanned_words(&page.text);
+        let folded: Vec<String> = words.iter().map(|(_, word)| word.clone()).collect();
+        let mut matches: Vec<Range<usize>> = Vec::new();
+        for &(phrase, fuzzy) in &phrases {
+            for (i, window) in folded.windows(phrase.len()).enumerate() {
+                if window_matches(window, phrase, fuzzy) {
+                    matches.push(i..i + phrase.len());
+                }
+            }
+        }
+        matches.sort_by_key(|found| found.start);
+        let Some(first) = matches.first() else {
+            continue;
+        };
+
+        let start = first.start.saturating_sub(CONTEXT);
+        let end = (first.end + CONTEXT).min(words.len());
+        let (from, to) = (words[start].0.start, words[end - 1].0.end);
+        let prefix = if start > 0 { "…" } else { "" };
+        let suffix = if end < words.len() { "…" } else { "" };
+        // newlines become spaces, which keeps every byte where it was
+        let text = format!("{prefix}{}{suffix}", page.text[from..to].replace(['\n', '\r', '\t'], " "));
+        let spans = matches
+            .iter()
+            .filter(|found| found.start >= start && found.end <= end)
+            .map(|found| prefix.len() + words[found.start].0.start - from..prefix.len() + words[found.end - 1].0.end - from)
+            .collect();
+        snippets.push(Snippet { path: page.path.clone(), page: page.number, text, spans });
+    }
+    snippets
+}
+
+/// The `fulltext:` phrases of a query outside `NOT`, with whether each is
+/// fuzzy
+fn collect_phrases<'a>(query: &'a Query, phrases: &mut Vec<(&'a [String], bool)>) {
+    match query {
+        Query::Words { field: Some(field), words, fuzzy } if field == FIELD && !words.is_empty() => {
+            phrases.push((words, *fuzzy));
+        }
+        Query::And(left, right) | Query::Or(left, right) => {
+            collect_phrases(left, phrases);
+            collect_phrases(right, phrases);
+        }
+        _ => {}
+    }
+}
+
+/// The folded words of some text with the bytes each came from, so that
+/// matches can be found in the original
+fn spanned_words(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(from)) => {
+                words.extend(text::words(&text[from..i]).into_iter().map(|word| (from..i, word)));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibtex::parser::BibTeXParser;
+    use crate::search::{self, QueryParser};
+    use crate::store::Store;
+    use crate::project::Tags;
+
+    #[test]
+    fn test_snippets() {
+        let pages = [
+            TextPage { path: PathBuf::from("a.pdf"), number: 1, text: String::from("Introduction to chaos") },
+            TextPage {
+                path: PathBuf::from("a.pdf"),
+                number: 4,
+                text: String::from("one two three four five six seven eight nine ten\nthe largest Lyapunov\nexponent is positive, so the flow is chaotic"),
+            },
+        ];
+        let query = QueryParser::new(r#"fulltext:"lyapunov exponent" OR fulltext:chao NOT fulltext:introduction"#).parse().unwrap();
+        let snippets = snippets(&pages, &query);
+        assert_eq!(snippets.len(), 2);
+        assert_eq!(snippets[0].page, 1);
+        assert_eq!(snippets[1].text, "…five six seven eight nine ten the largest Lyapunov exponent is positive, so the flow is chaotic");
+        let matched: Vec<&str> = snippets[1].spans.iter().map(|span| &snippets[1].text[span.clone()]).collect();
+        assert_eq!(matched, ["Lyapunov exponent", "chaotic"]);
+    }
+
+    #[test]
+    fn test_no_text_layer() {
+        // a scanned PDF has pages but no text on them
+        let path = std::env::temp_dir().join(format!("litrev-scanned-{}.pdf", std::process::id()));
+        crate::pdf::write_sample(&path, &[], &["", ""]);
+        let mut store = Store::open_in_memory().unwrap();
+        let pdfs = store.pdfs(std::slice::from_ref(&path)).unwrap();
+        let texts = store.texts(&pdfs).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let pages: Vec<TextPage> = texts[&pdfs[0].hash]
+            .iter()
+            .map(|page| TextPage { path: path.clone(), number: page.number, text: page.text.clone() })
+            .collect();
+        assert_eq!(pages.iter().map(|page| (page.number, page.text.as_str())).collect::<Vec<_>>(), [(1, ""), (2, "")]);
+        // the empty pages are cached rather than read again each time
+        assert_eq!(store.texts(&[]).unwrap()[&pdfs[0].hash].len(), 2);
+
+        let entry = &BibTeXParser::new("@misc{Scan1950, title = {Scanned}}").parse().unwrap()[0];
+        let full_text = FullText::from([(entry.citekey.clone(), pages.clone())]);
+        let matches = |query: &str| {
+            let query = QueryParser::new(query).parse().unwrap();
+            search::is_match(entry, &Tags::default(), &full_text, &Default::default(), &query)
+        };
+        assert!(!matches("fulltext:scanned"));
+        assert!(matches("scanned NOT fulltext:scanned"));
+        assert!(snippets(&pages, &QueryParser::new("fulltext:scanned").parse().unwrap()).is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+//! Typo-tolerant matching by edit distance, for half-remembered names such
+//! as `kenwey` for Kenway
+
+/// The number of single-character insertions, deletions, substitutions and
+/// swaps of neighbours it takes to turn one string into the other
+pub fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // three rows of the table are enough, the swap looks back two
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1).min(current[j - 1] + 1).min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// How many typos a word may have and still match, which grows with its
+/// length so that short words do not match everything
+pub fn tolerance(word: &str) -> usize {
+    match word.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Whether a word is within its tolerance of a term, or starts it
+pub fn matches(word: &str, term: &str) -> bool {
+    if term.starts_with(word) {
+        return true;
+    }
+    // words further apart in length than that cannot be close enough
+    let tolerance = tolerance(word);
+    tolerance > 0
+        && word.chars().count().abs_diff(term.chars().count()) <= tolerance
+        && distance(word, term) <= tolerance
+}
+
+/// The candidates closest to a string that are within its tolerance,
+/// ignoring case, closest first
+pub fn suggestions<'a>(target: &str, candidates: impl IntoIterator<Item = &'a str>, limit: usize) -> Vec<&'a str> {
+    let target = target.to_lowercase();
+    let tolerance = tolerance(&target);
+    let mut close: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| (distance(&target, &candidate.to_lowercase()), candidate))
+        .filter(|&(distance, _)| distance <= tolerance)
+        .collect();
+    close.sort_by_key(|&(distance, _)| distance);
+    close.dedup_by_key(|&mut (_, candidate)| candidate);
+    close.into_iter().take(limit).map(|(_, candidate)| candidate).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance("kenwey", "kenway"), 1);
+        assert_eq!(distance("schmid", "schmidt"), 1);
+        assert_eq!(distance("smtih", "smith"), 1);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert!(matches("kenwey", "kenway"));
+        assert!(!matches("doe", "dee"));
+
+        let citekeys = ["Smith2019", "Smith2020", "Doe2019", "smith2019"];
+        assert_eq!(suggestions("smith2091", citekeys, 3), ["Smith2019", "smith2019", "Smith2020"]);
+        assert!(suggestions("Kenway2019", citekeys, 3).is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+//! Searching the library with the query language in [`query`]. Values are
+//! decoded from LaTeX and folded before matching, so that case and accents
+//! make no difference
+
+pub mod error;
+pub mod fulltext;
+pub mod fuzzy;
+pub mod pattern;
+pub mod query;
+pub mod rank;
+pub mod text;
+
+use std::collections::HashMap;
+
+use crate::bibtex::entry::BibTeXEntry;
+use crate::project::Tags;
+use crate::store::Notes;
+
+pub use error::QueryError;
+pub use fulltext::{FullText, TextPage};
+pub use query::{Query, QueryParser, DEFAULT_FIELDS};
+
+/// The field that holds both an entry's `note` field and its markdown note
+pub const NOTE: &str = "note";
+
+/// An entry prepared for matching, with the fields a query looks in split
+/// into folded words
+pub struct Document<'a> {
+    pub entry: &'a BibTeXEntry,
+    /// The words of each field, see [`field_words`]
+    fields: HashMap<String, Vec<Vec<String>>>,
+    kind: String,
+    tags: Vec<String>,
+}
+
+impl<'a> Document<'a> {
+    /// Prepares the given fields of an entry, see [`Query::fields`]. The
+    /// `fulltext` field is the pages of the entry's linked PDFs, and the
+    /// `note` field has the entry's markdown note after its own
+    pub fn new(entry: &'a BibTeXEntry, tags: &Tags, pages: &[TextPage], note: Option<&str>, wanted: &[&str]) -> Self {
+        let words = |field: &str| match field {
+            fulltext::FIELD => pages.iter().map(|page| text::words(&page.text)).collect(),
+            NOTE => field_words(entry, NOTE).into_iter().chain(note.map(text::words)).collect(),
+            field => field_words(entry, field),
+        };
+        Self {
+            entry,
+            fields: wanted.iter().map(|&field| (field.to_string(), words(field))).collect(),
+            kind: text::fold(&entry.kind.to_string()),
+            tags: tags.of(&entry.citekey).iter().map(|tag| text::fold(tag)).collect(),
+        }
+    }
+
+    pub fn matches(&self, query: &Query) -> bool {
+        match query {
+            Query::Words { field: Some(field), words, fuzzy } => self.field_contains(field, words, *fuzzy),
+            Query::Words { field: None, words, fuzzy } => {
+                DEFAULT_FIELDS.iter().any(|field| self.field_contains(field, words, *fuzzy))
+            }
+            Query::Year { from, to } => self.entry.year().is_some_and(|year| {
+                from.is_none_or(|from| *year >= from) && to.is_none_or(|to| *year <= to)
+            }),
+            Query::Kind(kind) => &self.kind == kind,
+            Query::Tag(tag) => self.tags.contains(tag),
+            Query::And(left, right) => self.matches(left) && self.matches(right),
+            Query::Or(left, right) => self.matches(left) || self.matches(right),
+            Query::Not(query) => !self.matches(query),
+        }
+    }
+
+    fn field_contains(&self, field: &str, words: &[String], fuzzy: bool) -> bool {
+        self.fields
+            .get(field)
+            .is_some_and(|values| values.iter().any(|value| contains_phrase(value, words, fuzzy)))
+    }
+}
+
+/// The folded words of each value of a field, or of the citekey. Authors and
+/// editors have a value per name, so that a phrase never runs from one name
+/// into the next
+pub fn field_words(entry: &BibTeXEntry, key: &str) -> Vec<Vec<String>> {
+    let names = match key {
+        "author" => entry.author(),
+        "editor" => entry.editor(),
+        "citekey" => return vec![text::words(&entry.citekey)],
+        _ => None,
+    };
+    match names {
+        Some(names) => names.iter().map(|name| text::words(&name.to_bibtex_name())).collect(),
+        None => entry
+            .fields()
+            .iter()
+            .filter(|field| field.key() == key)
+            .map(|field| text::words(&field.value()))
+            .collect(),
+    }
+}
+
+/// Whether the words appear one after another, with the last only having to
+/// start a word. Fuzzy words may also be a few typos out
+fn contains_phrase(value: &[String], phrase: &[String], fuzzy: bool) -> bool {
+    if phrase.is_empty() {
+        return false;
+    }
+    value.windows(phrase.len()).any(|window| window_matches(window, phrase, fuzzy))
+}
+
+/// Whether some words are the words of a phrase, see [`contains_phrase`]
+fn window_matches(window: &[String], phrase: &[String], fuzzy: bool) -> bool {
+    let Some((last, rest)) = phrase.split_last() else {
+        return false;
+    };
+    if fuzzy {
+        return window.iter().zip(phrase).all(|(term, word)| fuzzy::matches(word, term));
+    }
+    window[..rest.len()] == *rest && window[rest.len()].starts_with(last.as_str())
+}
+
+fn pages<'a>(full_text: &'a FullText, entry: &BibTeXEntry) -> &'a [TextPage] {
+    full_text.get(&entry.citekey).map_or(&[], Vec::as_slice)
+}
+
+fn note<'a>(notes: &'a Notes, entry: &BibTeXEntry) -> Option<&'a str> {
+    notes.get(&entry.citekey).map(String::as_str)
+}
+
+/// Whether a query looks in notes at all
+pub fn uses_notes(query: &Query) -> bool {
+    query.fields().contains(&NOTE)
+}
+
+pub fn is_match(entry: &BibTeXEntry, tags: &Tags, full_text: &FullText, notes: &Notes, query: &Query) -> bool {
+    Document::new(entry, tags, pages(full_text, entry), note(notes, entry), &query.fields()).matches(query)
+}
+
+/// The entries matching a query, in the order they are in the library. If
+/// none match exactly, those matching with every word a few typos out
+pub fn search<'a>(
+    entries: &'a [BibTeXEntry],
+    tags: &Tags,
+    full_text: &FullText,
+    notes: &Notes,
+    query: &Query,
+) -> Vec<&'a BibTeXEntry> {
+    let fields = query.fields();
+    let documents: Vec<Document> = entries
+        .iter()
+        .map(|entry| Document::new(entry, tags, pages(full_text, entry), note(notes, entry), &fields))
+        .collect();
+    let matching = |query: &Query| -> Vec<&'a BibTeXEntry> {
+        documents.iter().filter(|document| document.matches(query)).map(|document| document.entry).collect()
+    };
+    let found = matching(query);
+    let fuzzy = query.clone().into_fuzzy();
+    if found.is_empty() && fuzzy != *query {
+        return matching(&fuzzy);
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibtex::parser::BibTeXParser;
+
+    const LIBRARY: &str = r#"
+        @article{Kenway2019,
+            author = {Kenway, Gaetan K. W. and Mader, Charles A. and He, Ping},
+            title = {Effective Adjoint Approaches for Computational Fluid Dynamics},
+            journal = {Progress in Aerospace Sciences},
+            year = {2019}
+        }
+        @book{Muller2012,
+            author = {M{\"u}ller, Hans},
+            title = {Turbulence Modelling},
+            keywords = {cfd, RANS},
+            note = {Preprint},
+            year = {2012}
+        }
+    "#;
+
+    fn citekeys(query: &str) -> Vec<String> {
+        let entries = BibTeXParser::new(LIBRARY).parse().unwrap();
+        let mut tags = Tags::default();
+        tags.add("Muller2012", "Thesis");
+        let query = QueryParser::new(query).parse().unwrap();
+        let mut full_text = FullText::new();
+        full_text.insert(String::from("Muller2012"), vec![TextPage {
+            path: "Muller2012.pdf".into(),
+            number: 3,
+            text: String::from("The largest Lyapunov\nexponent"),
+        }]);
+        let mut notes = Notes::new();
+        notes.insert(String::from("Kenway2019"), String::from("# Adjoints\n\nLeaves out the shock-boundary layer\ninteraction"));
+        search(&entries, &tags, &full_text, &notes, &query).iter().map(|entry| entry.citekey.clone()).collect()
+    }
+
+    #[test]
+    fn test_search() {
+        assert_eq!(citekeys("adjoint"), ["Kenway2019"]);
+        assert_eq!(citekeys("müller"), ["Muller2012"]);
+        assert_eq!(citekeys("author:MULLER"), ["Muller2012"]);
+        assert_eq!(citekeys(r#"title:"adjoint approach""#), ["Kenway2019"]);
+        assert_eq!(citekeys(r#"title:"approach adjoint""#), Vec::<String>::new());
+        // a phrase does not run from one author into the next
+        assert_eq!(citekeys(r#"author:"charles he""#), Vec::<String>::new());
+        assert_eq!(citekeys("year:2010..2015 OR kind:article"), ["Kenway2019", "Muller2012"]);
+        assert_eq!(citekeys("NOT tag:thesis"), ["Kenway2019"]);
+        assert_eq!(citekeys("(cfd OR fluid) year:2019.."), ["Kenway2019"]);
+        assert_eq!(citekeys("citekey:muller"), ["Muller2012"]);
+        assert_eq!(citekeys("author:kenwey~"), ["Kenway2019"]);
+        // with nothing matching exactly, typos are tolerated anyway
+        assert_eq!(citekeys("author:kenwey"), ["Kenway2019"]);
+        assert_eq!(citekeys("kenwey"), ["Kenway2019"]);
+        assert_eq!(citekeys("kenwey OR muller"), ["Muller2012"]);
+        assert_eq!(citekeys(r#""efective ajoint"~"#), ["Kenway2019"]);
+        // the text of linked PDFs is only looked in when asked for
+        assert_eq!(citekeys(r#"fulltext:"lyapunov exponent""#), ["Muller2012"]);
+        assert_eq!(citekeys("lyapunov"), Vec::<String>::new());
+        // and so are notes, along with the note field
+        assert_eq!(citekeys(r#"note:"shock boundary layer""#), ["Kenway2019"]);
+        assert_eq!(citekeys("note:preprint"), ["Muller2012"]);
+        assert_eq!(citekeys("interaction"), Vec::<String>::new());
+    }
+}
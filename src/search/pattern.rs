@@ -0,0 +1,104 @@
+//! Regular expressions over any field, standard or not, for searches the
+//! query language cannot express, e.g. `note=arXiv:\d{4}\.\d{5}`
+
+use std::ops::Range;
+
+use regex::Regex;
+
+use crate::bibtex::entry::BibTeXEntry;
+use crate::style::case::strip_braces;
+
+/// A pattern for the values of one field, written `field=pattern`
+#[derive(Debug, Clone)]
+pub struct FieldPattern {
+    /// The lowercase field name, or `citekey`
+    pub field: String,
+    pub regex: Regex,
+}
+
+/// A value of a field along with where the pattern matched it
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldMatch {
+    pub field: String,
+    /// The value as written in the bibliography, without braces
+    pub value: String,
+    /// The byte ranges of the value that matched
+    pub spans: Vec<Range<usize>>,
+}
+
+impl std::str::FromStr for FieldPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((field, pattern)) = s.split_once('=') else {
+            return Err(format!("expected field=pattern, e.g. title=^On, but found {s}"));
+        };
+        let field = field.trim().to_lowercase();
+        if field.is_empty() {
+            return Err(format!("expected a field name before '=' in {s}"));
+        }
+        let regex = Regex::new(pattern).map_err(|e| format!("invalid pattern for {field}: {e}"))?;
+        Ok(Self { field, regex })
+    }
+}
+
+impl std::fmt::Display for FieldPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.field, self.regex.as_str())
+    }
+}
+
+impl FieldPattern {
+    /// The values of the field in an entry that the pattern matches. Braces
+    /// are left out of the values, so `^On the` finds `{On} the`
+    pub fn find(&self, entry: &BibTeXEntry) -> Vec<FieldMatch> {
+        let values = if self.field == "citekey" {
+            vec![entry.citekey.clone()]
+        } else {
+            entry
+                .fields()
+                .iter()
+                .filter(|field| field.key() == self.field)
+                .map(|field| strip_braces(&field.value()))
+                .collect()
+        };
+        values
+            .into_iter()
+            .filter_map(|value| {
+                let spans: Vec<Range<usize>> = self.regex.find_iter(&value).map(|found| found.range()).collect();
+                (!spans.is_empty()).then(|| FieldMatch { field: self.field.clone(), value, spans })
+            })
+            .collect()
+    }
+
+    pub fn is_match(&self, entry: &BibTeXEntry) -> bool {
+        !self.find(entry).is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibtex::parser::BibTeXParser;
+
+    #[test]
+    fn test_find() {
+        let entries = BibTeXParser::new(r"
+            @article{Smith2019, title = {{On} the Adjoint}, note = {Preprint at arXiv:1901.01234, see arXiv:1902.04321}}
+            @article{Doe2020, title = {Notes On the Adjoint}, eprintclass = {physics.flu-dyn}}
+        ").parse().unwrap();
+
+        let pattern: FieldPattern = r"note=arXiv:\d{4}\.\d{5}".parse().unwrap();
+        let found = pattern.find(&entries[0]);
+        assert_eq!(found[0].spans, [12..28, 34..50]);
+        assert!(!pattern.is_match(&entries[1]));
+
+        let pattern: FieldPattern = "title=^On the".parse().unwrap();
+        assert!(pattern.is_match(&entries[0]) && !pattern.is_match(&entries[1]));
+        let pattern: FieldPattern = "EprintClass=flu".parse().unwrap();
+        assert!(pattern.is_match(&entries[1]));
+
+        assert!("title".parse::<FieldPattern>().is_err());
+        assert!("title=(".parse::<FieldPattern>().unwrap_err().starts_with("invalid pattern for title"));
+    }
+}
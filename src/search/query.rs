@@ -0,0 +1,348 @@
+//! The search query language: words and quoted phrases, optionally scoped to
+//! a field with `field:value`, combined with `AND`, `OR`, `NOT` and
+//! parentheses. Words next to each other are implicitly `AND`ed, and a
+//! word or phrase followed by `~` tolerates typos
+
+use super::error::QueryError;
+use super::text::{fold, words};
+
+/// The fields a query can be scoped to, besides `year`, `kind` and `tag`
+pub const FIELDS: &[&str] = &[
+    "author", "editor", "title", "abstract", "keywords", "journal", "booktitle", "publisher",
+    "series", "school", "institution", "organization", "note", "annote", "address", "doi",
+    "url", "isbn", "issn", "citekey", "fulltext",
+];
+
+/// The fields bare words are looked for in
+pub const DEFAULT_FIELDS: &[&str] = &["title", "author", "abstract", "keywords"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    /// Folded words that must appear one after another, either in the given
+    /// field or else in the title, authors, abstract or keywords. The last
+    /// word only has to start a word, so `adjoint` also finds `adjoints`.
+    /// Fuzzy words match anything within a few typos of them too
+    Words { field: Option<String>, words: Vec<String>, fuzzy: bool },
+    /// An inclusive range of years, open at either end
+    Year { from: Option<u16>, to: Option<u16> },
+    Kind(String),
+    Tag(String),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+}
+
+impl Query {
+    /// The same query with every word tolerating typos
+    pub fn into_fuzzy(self) -> Self {
+        match self {
+            Self::Words { field, words, .. } => Self::Words { field, words, fuzzy: true },
+            Self::And(left, right) => Self::And(Box::new(left.into_fuzzy()), Box::new(right.into_fuzzy())),
+            Self::Or(left, right) => Self::Or(Box::new(left.into_fuzzy()), Box::new(right.into_fuzzy())),
+            Self::Not(query) => Self::Not(Box::new(query.into_fuzzy())),
+            query => query,
+        }
+    }
+
+    /// The fields the query looks in, besides `year`, `kind` and `tag`
+    pub fn fields(&self) -> Vec<&str> {
+        match self {
+            Self::Words { field: Some(field), .. } => vec![field.as_str()],
+            Self::Words { field: None, .. } => DEFAULT_FIELDS.to_vec(),
+            Self::Year { .. } | Self::Kind(_) | Self::Tag(_) => Vec::new(),
+            Self::And(left, right) | Self::Or(left, right) => [left.fields(), right.fields()].concat(),
+            Self::Not(query) => query.fields(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Term { field: Option<(String, usize)>, value: String, value_position: usize },
+}
+
+pub struct QueryParser<'a> {
+    query: &'a str,
+    chars: Vec<char>,
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+}
+
+impl<'a> QueryParser<'a> {
+    pub fn new(query: &'a str) -> Self {
+        Self { query, chars: query.chars().collect(), tokens: Vec::new(), position: 0 }
+    }
+
+    pub fn parse(mut self) -> Result<Query, QueryError> {
+        self.tokenize()?;
+        if self.tokens.is_empty() {
+            return Err(self.error(0, "the query is empty"));
+        }
+        let query = self.parse_or()?;
+        match self.tokens.get(self.position) {
+            Some((Token::Close, position)) => Err(self.error(*position, "unmatched ')'")),
+            Some((_, position)) => Err(self.error(*position, "unexpected term")),
+            None => Ok(query),
+        }
+    }
+
+    fn error(&self, position: usize, message: impl Into<String>) -> QueryError {
+        QueryError::new(self.query, position, message)
+    }
+
+    /// Where the query ends, for errors about something missing
+    fn end(&self) -> usize {
+        self.chars.len()
+    }
+
+    fn tokenize(&mut self) -> Result<(), QueryError> {
+        let mut i = 0;
+        while i < self.chars.len() {
+            let start = i;
+            match self.chars[i] {
+                c if c.is_whitespace() => i += 1,
+                '(' => {
+                    self.tokens.push((Token::Open, start));
+                    i += 1;
+                }
+                ')' => {
+                    self.tokens.push((Token::Close, start));
+                    i += 1;
+                }
+                '"' => {
+                    let (value, next) = self.phrase(i)?;
+                    let (value, next) = self.fuzzy_marker(value, next);
+                    self.tokens.push((Token::Term { field: None, value, value_position: start }, start));
+                    i = next;
+                }
+                _ => {
+                    let end = self.word_end(i);
+                    let word: String = self.chars[start..end].iter().collect();
+                    let token = match (word.as_str(), word.split_once(':')) {
+                        ("AND", _) => Token::And,
+                        ("OR", _)  => Token::Or,
+                        ("NOT", _) => Token::Not,
+                        (_, Some((field, _))) if !field.is_empty() && field.chars().all(char::is_alphabetic) => {
+                            let value_position = start + field.chars().count() + 1;
+                            let (value, next) = if self.chars.get(value_position) == Some(&'"') {
+                                let (value, next) = self.phrase(value_position)?;
+                                self.fuzzy_marker(value, next)
+                            } else {
+                                let end = self.word_end(value_position);
+                                (self.chars[value_position..end].iter().collect(), end)
+                            };
+                            if value.trim().is_empty() {
+                                return Err(self.error(value_position, format!("expected a value after '{field}:'")));
+                            }
+                            self.tokens.push((
+                                Token::Term { field: Some((field.to_lowercase(), start)), value, value_position },
+                                start,
+                            ));
+                            i = next;
+                            continue;
+                        }
+                        _ => Token::Term { field: None, value: word, value_position: start },
+                    };
+                    self.tokens.push((token, start));
+                    i = end;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The end of an unquoted word, which runs up to whitespace, a
+    /// parenthesis or a quote, other than the `\"` of an umlaut
+    fn word_end(&self, mut i: usize) -> usize {
+        while let Some(&c) = self.chars.get(i) {
+            let umlaut = c == '"' && i > 0 && self.chars[i - 1] == '\\';
+            if c.is_whitespace() || matches!(c, '(' | ')') || (c == '"' && !umlaut) {
+                break;
+            }
+            i += 1;
+        }
+        i
+    }
+
+    /// The phrase in the quotes opened at `i`, and where it ends
+    fn phrase(&self, i: usize) -> Result<(String, usize), QueryError> {
+        match self.chars[i + 1..].iter().position(|&c| c == '"') {
+            Some(length) => Ok((self.chars[i + 1..i + 1 + length].iter().collect(), i + length + 2)),
+            None => Err(self.error(i, "unterminated quote")),
+        }
+    }
+
+    /// Keeps a `~` after a phrase with it, as it is kept at the end of a word
+    fn fuzzy_marker(&self, mut value: String, next: usize) -> (String, usize) {
+        if self.chars.get(next) == Some(&'~') {
+            value.push('~');
+            return (value, next + 1);
+        }
+        (value, next)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn next_position(&self) -> usize {
+        self.tokens.get(self.position).map_or(self.end(), |&(_, position)| position)
+    }
+
+    fn parse_or(&mut self) -> Result<Query, QueryError> {
+        let mut query = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            query = Query::Or(Box::new(query), Box::new(self.parse_and()?));
+        }
+        Ok(query)
+    }
+
+    fn parse_and(&mut self) -> Result<Query, QueryError> {
+        let mut query = self.parse_not()?;
+        loop {
+            match self.peek() {
+                Some(Token::And) => self.position += 1,
+                Some(Token::Open | Token::Not | Token::Term { .. }) => {}
+                _ => return Ok(query),
+            }
+            query = Query::And(Box::new(query), Box::new(self.parse_not()?));
+        }
+    }
+
+    fn parse_not(&mut self) -> Result<Query, QueryError> {
+        if self.peek() == Some(&Token::Not) {
+            self.position += 1;
+            return Ok(Query::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_atom()
+    }
+
+    fn parse_atom(&mut self) -> Result<Query, QueryError> {
+        let position = self.next_position();
+        let Some((token, _)) = self.tokens.get(self.position) else {
+            return Err(self.error(position, "expected a term after the operator"));
+        };
+        match token {
+            Token::Open => {
+                self.position += 1;
+                let query = self.parse_or()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(self.error(position, "unmatched '('"));
+                }
+                self.position += 1;
+                Ok(query)
+            }
+            Token::Close => Err(self.error(position, "expected a term before ')'")),
+            Token::And | Token::Or | Token::Not => {
+                Err(self.error(position, "expected a term, quote the word to search for it"))
+            }
+            Token::Term { field, value, value_position } => {
+                let query = self.term(field.as_ref(), value, *value_position)?;
+                self.position += 1;
+                Ok(query)
+            }
+        }
+    }
+
+    fn term(&self, field: Option<&(String, usize)>, value: &str, value_position: usize) -> Result<Query, QueryError> {
+        let field = match field {
+            None => None,
+            Some((field, _)) if field == "year" => {
+                return parse_years(value).ok_or_else(|| {
+                    self.error(value_position, "expected a year or a range of years such as 2015..2020")
+                });
+            }
+            Some((field, _)) if field == "kind" => return Ok(Query::Kind(fold(value))),
+            Some((field, _)) if field == "tag" => return Ok(Query::Tag(fold(value))),
+            Some((field, position)) if !FIELDS.contains(&field.as_str()) => {
+                return Err(self.error(*position, format!(
+                    "unknown field '{field}', expected one of {}, year, kind or tag",
+                    FIELDS.join(", "),
+                )));
+            }
+            Some((field, _)) => Some(field.clone()),
+        };
+        let words = words(value);
+        if words.is_empty() {
+            return Err(self.error(value_position, format!("'{value}' has no letters or digits to search for")));
+        }
+        Ok(Query::Words { field, words, fuzzy: value.ends_with('~') })
+    }
+}
+
+/// `2019`, `2015..2020`, `2015..` or `..2020`
+fn parse_years(value: &str) -> Option<Query> {
+    let year = |s: &str| if s.is_empty() { Ok(None) } else { s.parse().map(Some) };
+    match value.split_once("..") {
+        Some(("", "")) => None,
+        Some((from, to)) => Some(Query::Year { from: year(from).ok()?, to: year(to).ok()? }),
+        None => {
+            let year = value.parse().ok()?;
+            Some(Query::Year { from: Some(year), to: Some(year) })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(field: Option<&str>, words: &[&str]) -> Query {
+        let words = words.iter().map(|w| w.to_string()).collect();
+        Query::Words { field: field.map(String::from), words, fuzzy: false }
+    }
+
+    fn and(left: Query, right: Query) -> Query {
+        Query::And(Box::new(left), Box::new(right))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            QueryParser::new(r#"author:kenway title:"Adjoint Method" year:2015..2020"#).parse().unwrap(),
+            and(
+                and(words(Some("author"), &["kenway"]), words(Some("title"), &["adjoint", "method"])),
+                Query::Year { from: Some(2015), to: Some(2020) },
+            ),
+        );
+        assert_eq!(
+            QueryParser::new("(cfd OR tag:CFD) AND NOT kind:book M{\\\"u}ller").parse().unwrap(),
+            and(
+                and(
+                    Query::Or(Box::new(words(None, &["cfd"])), Box::new(Query::Tag(String::from("cfd")))),
+                    Query::Not(Box::new(Query::Kind(String::from("book")))),
+                ),
+                words(None, &["muller"]),
+            ),
+        );
+        assert_eq!(QueryParser::new("year:..2010").parse().unwrap(), Query::Year { from: None, to: Some(2010) });
+        assert_eq!(
+            QueryParser::new(r#"author:"gaetan kenwey"~"#).parse().unwrap(),
+            Query::Words { field: Some(String::from("author")), words: vec![String::from("gaetan"), String::from("kenwey")], fuzzy: true },
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |query: &str| {
+            let error = QueryParser::new(query).parse().unwrap_err();
+            (error.position, error.message)
+        };
+        assert_eq!(error("adjoint AND"), (11, String::from("expected a term after the operator")));
+        assert_eq!(error("(adjoint OR cfd"), (0, String::from("unmatched '('")));
+        assert_eq!(error("adjoint) cfd"), (7, String::from("unmatched ')'")));
+        assert_eq!(error(r#"title:"adjoint"#), (6, String::from("unterminated quote")));
+        assert_eq!(error("cfd year:20x5").0, 9);
+        assert!(error("cfd auther:kenway").1.starts_with("unknown field 'auther'"));
+        assert_eq!(error("  "), (0, String::from("the query is empty")));
+
+        let error = QueryParser::new("adjoint AND").parse().unwrap_err();
+        assert_eq!(error.to_string(), "invalid query: expected a term after the operator\n  adjoint AND\n             ^");
+    }
+}
@@ -0,0 +1,240 @@
+//! Ordering search results by relevance with BM25F. The frequency of a word
+//! in each field is weighted by the field and normalised by the field's
+//! length against the library's average, and the sum is saturated so that
+//! repeating a word has diminishing returns. Rare words count for more
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::store::{CorpusStats, Posting, Store, StoreError};
+
+use super::{fuzzy, Query};
+
+/// The fields kept in the inverted index and how much a word in each counts
+/// towards a score
+pub const FIELD_WEIGHTS: &[(&str, f64)] = &[
+    ("title",    3.0),
+    ("keywords", 2.0),
+    ("author",   1.5),
+    ("abstract", 1.0),
+];
+
+/// How quickly repeats of a word stop adding to a score
+const K1: f64 = 1.2;
+/// How much a field's length matters, from 0 for not at all to 1 for fully
+const B: f64 = 0.75;
+
+/// The relevance of an entry to a query
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Score {
+    pub total: f64,
+    pub parts: Vec<Part>,
+}
+
+/// What one term in one field contributed to a score
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Part {
+    /// The word of the query
+    pub word: String,
+    /// The term it matched, which the word may only be the start of
+    pub term: String,
+    pub field: String,
+    pub frequency: u32,
+    /// How many words the field has
+    pub length: u32,
+    pub score: f64,
+}
+
+/// A word of a query that results are ranked by
+#[derive(Debug, PartialEq)]
+struct RankedWord {
+    /// The field the word was scoped to, if any
+    field: Option<String>,
+    word: String,
+    /// Whether the word only has to start a term, as the last word of a
+    /// phrase does
+    prefix: bool,
+    /// Whether the word also matches terms a few typos away
+    fuzzy: bool,
+}
+
+/// The scores of the entries containing any of the words of a query, by
+/// citekey. Words under `NOT` and in fields outside the index only filter,
+/// so they are left out
+pub fn rank(store: &Store, query: &Query) -> Result<HashMap<String, Score>, StoreError> {
+    let words = ranked_words(query);
+    let mut scores = HashMap::new();
+    if words.is_empty() {
+        return Ok(scores);
+    }
+    let stats = store.corpus_stats()?;
+    // the terms are only needed, and only worth loading, to look for typos
+    let terms = if words.iter().any(|ranked| ranked.fuzzy) { store.terms()? } else { Vec::new() };
+    for ranked in &words {
+        let mut postings = store.postings(&ranked.word)?;
+        if ranked.fuzzy {
+            for term in terms.iter().filter(|term| !term.starts_with(&ranked.word) && fuzzy::matches(&ranked.word, term)) {
+                postings.extend(store.postings(term)?.into_iter().filter(|posting| &posting.term == term));
+            }
+        }
+        postings.retain(|posting| {
+            (ranked.prefix || ranked.fuzzy || posting.term == ranked.word)
+                && ranked.field.as_ref().is_none_or(|field| field == &posting.field)
+        });
+        score_word(&ranked.word, &postings, &stats, &mut scores);
+    }
+    Ok(scores)
+}
+
+/// Whether a query has any words to rank by
+pub fn is_ranked(query: &Query) -> bool {
+    !ranked_words(query).is_empty()
+}
+
+fn ranked_words(query: &Query) -> Vec<RankedWord> {
+    let mut ranked = Vec::new();
+    collect_words(query, &mut ranked);
+    ranked
+}
+
+fn collect_words(query: &Query, ranked: &mut Vec<RankedWord>) {
+    match query {
+        Query::Words { field, words, fuzzy } => {
+            if field.as_ref().is_some_and(|field| weight(field).is_none()) {
+                return;
+            }
+            for (i, word) in words.iter().enumerate() {
+                let word = RankedWord {
+                    field: field.clone(),
+                    word: word.clone(),
+                    prefix: i + 1 == words.len(),
+                    fuzzy: *fuzzy,
+                };
+                if !ranked.contains(&word) {
+                    ranked.push(word);
+                }
+            }
+        }
+        Query::And(left, right) | Query::Or(left, right) => {
+            collect_words(left, ranked);
+            collect_words(right, ranked);
+        }
+        Query::Year { .. } | Query::Kind(_) | Query::Tag(_) | Query::Not(_) => {}
+    }
+}
+
+fn weight(field: &str) -> Option<f64> {
+    FIELD_WEIGHTS.iter().find(|(name, _)| *name == field).map(|&(_, weight)| weight)
+}
+
+/// Adds the score of one word of the query to the entries it appears in
+fn score_word(word: &str, postings: &[Posting], stats: &CorpusStats, scores: &mut HashMap<String, Score>) {
+    let mut by_entry: HashMap<&str, Vec<&Posting>> = HashMap::new();
+    for posting in postings {
+        by_entry.entry(&posting.citekey).or_default().push(posting);
+    }
+    let entries = stats.entries as f64;
+    let containing = by_entry.len() as f64;
+    let idf = (1.0 + (entries - containing + 0.5) / (containing + 0.5)).ln();
+
+    for (citekey, postings) in by_entry {
+        let weighted: Vec<(&Posting, f64)> = postings
+            .into_iter()
+            .map(|posting| {
+                let average = stats.average_lengths.get(&posting.field).copied().unwrap_or(1.0).max(1.0);
+                let normalisation = 1.0 - B + B * posting.length as f64 / average;
+                let weight = weight(&posting.field).unwrap_or(1.0);
+                (posting, weight * posting.frequency as f64 / normalisation)
+            })
+            .collect();
+        let frequency: f64 = weighted.iter().map(|(_, frequency)| frequency).sum();
+        if frequency <= 0.0 {
+            continue;
+        }
+        let word_score = idf * frequency * (K1 + 1.0) / (K1 + frequency);
+
+        let score = scores.entry(citekey.to_string()).or_default();
+        score.total += word_score;
+        // the word's score is shared between its fields by how much each
+        // added to its frequency
+        score.parts.extend(weighted.into_iter().map(|(posting, frequency_part)| Part {
+            word: word.to_string(),
+            term: posting.term.clone(),
+            field: posting.field.clone(),
+            frequency: posting.frequency,
+            length: posting.length,
+            score: word_score * frequency_part / frequency,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::QueryParser;
+
+    const LIBRARY: &str = r#"
+        @article{Title, title = {Adjoint Methods}, abstract = {Gradients of flows}}
+        @article{Abstract, title = {Gradients of Flows}, abstract = {An adjoint method for gradients of flows}}
+        @article{Twice, title = {Adjoint Adjoints for Adjoint Solvers}}
+        @article{Other, title = {Turbulence}, author = {Adjoint, Alice}}
+        @article{None, title = {Wind Tunnels}}
+    "#;
+
+    fn ranking(query: &str) -> Vec<(String, f64)> {
+        ranking_in("library", LIBRARY, query)
+    }
+
+    fn ranking_in(name: &str, library: &str, query: &str) -> Vec<(String, f64)> {
+        let dir = std::env::temp_dir().join(format!("litrev-rank-{}-{name}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let bib = dir.join("refs.bib");
+        std::fs::write(&bib, library).unwrap();
+        let mut store = Store::open_in_memory().unwrap();
+        store.rebuild(&[bib]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let query = QueryParser::new(query).parse().unwrap();
+        let mut scores: Vec<(String, f64)> = rank(&store, &query)
+            .unwrap()
+            .into_iter()
+            .map(|(citekey, score)| (citekey, score.total))
+            .collect();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scores
+    }
+
+    #[test]
+    fn test_rank() {
+        let scores = ranking("adjoint");
+        let citekeys: Vec<&str> = scores.iter().map(|(citekey, _)| citekey.as_str()).collect();
+        // a word in the title outweighs the same word in the abstract or
+        // authors, and repeating it counts for more but not much more
+        assert_eq!(citekeys, ["Twice", "Title", "Other", "Abstract"]);
+        assert!(scores[0].1 < 2.0 * scores[1].1);
+
+        // words under NOT and terms outside the index only filter
+        assert!(ranking("NOT adjoint").is_empty());
+        assert!(ranking("year:2019 kind:article").is_empty());
+        assert_eq!(ranking("title:adjoint").len(), 2);
+        assert_eq!(ranking(r#""adjoint method""#).len(), 4);
+        assert_eq!(ranking("ajoint~").len(), 4);
+    }
+
+    #[test]
+    fn test_rank_tie() {
+        // once in a title weighs as much as three times in an abstract of
+        // the same length relative to the library's average
+        let library = r#"
+            @article{Title, title = {Vortex Shedding}, abstract = {Cylinder wake flows}}
+            @article{Abstract, title = {Cylinder Wakes}, abstract = {Vortex vortex vortex}}
+        "#;
+        let scores = ranking_in("tie", library, "vortex");
+        assert_eq!(scores.len(), 2);
+        assert_eq!(scores[0].1, scores[1].1);
+        // but a field-scoped word only counts in that field
+        let scores = ranking_in("tie", library, "title:vortex");
+        assert_eq!(scores.iter().map(|(citekey, _)| citekey.as_str()).collect::<Vec<_>>(), ["Title"]);
+    }
+}
@@ -0,0 +1,51 @@
+//! Normalisation of text for matching, so that `Müller`, `M{\"u}ller` and
+//! `muller` are all the same word
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::bibtex::latex;
+
+/// Decodes any LaTeX, then strips accents and lowercases
+pub fn fold(text: &str) -> String {
+    if text.is_ascii() && !text.contains(['\\', '{', '}', '$', '~']) {
+        return text.to_ascii_lowercase();
+    }
+    latex::decode(text)
+        .nfd()
+        .filter(|&c| !is_combining_mark(c))
+        .flat_map(char::to_lowercase)
+        .flat_map(|c| match c {
+            // letters that do not decompose into a base letter and an accent
+            'ß' => "ss".chars().collect::<Vec<_>>(),
+            'æ' => vec!['a', 'e'],
+            'œ' => vec!['o', 'e'],
+            'ø' => vec!['o'],
+            'ł' => vec!['l'],
+            'đ' => vec!['d'],
+            'ı' => vec!['i'],
+            c => vec![c],
+        })
+        .collect()
+}
+
+/// The folded words of some text, split on anything other than a letter or
+/// digit
+pub fn words(text: &str) -> Vec<String> {
+    fold(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words() {
+        assert_eq!(words(r#"M{\"u}ller \& Stra\ss e: Navier--Stokes"#), ["muller", "strasse", "navier", "stokes"]);
+        assert_eq!(words("Ørsted, Åström and Łukasiewicz"), ["orsted", "astrom", "and", "lukasiewicz"]);
+    }
+}
@@ -0,0 +1,54 @@
+//! The citations between entries, found by resolving the reference lists
+//! of their linked PDFs, see [`crate::references`]
+
+use rusqlite::params;
+
+use crate::references::Evidence;
+
+use super::{Store, StoreError};
+
+/// An entry citing another
+#[derive(Debug, Clone, PartialEq)]
+pub struct Citation {
+    pub citing: String,
+    pub cited: String,
+    pub evidence: Evidence,
+    /// The reference to the cited entry as it is in the citing entry's PDF
+    pub reference: String,
+}
+
+impl Store {
+    /// Replaces every citation with these
+    pub fn set_citations(&mut self, citations: &[Citation]) -> Result<(), StoreError> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM citations", [])?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT OR IGNORE INTO citations (citing, cited, evidence, reference) VALUES (?1, ?2, ?3, ?4)"
+            )?;
+            for citation in citations {
+                insert.execute(params![citation.citing, citation.cited, citation.evidence.name(), citation.reference])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Every citation, by citing and then cited citekey
+    pub fn citations(&self) -> Result<Vec<Citation>, StoreError> {
+        let mut statement = self.conn.prepare(
+            "SELECT citing, cited, evidence, reference FROM citations ORDER BY citing, cited"
+        )?;
+        let citations = statement.query_map([], |row| {
+            let evidence: String = row.get(2)?;
+            Ok(Citation {
+                citing: row.get(0)?,
+                cited: row.get(1)?,
+                // only evidence this litrev wrote is ever stored
+                evidence: evidence.parse().unwrap_or(Evidence::AuthorYear),
+                reference: row.get(3)?,
+            })
+        })?;
+        Ok(citations.collect::<Result<_, _>>()?)
+    }
+}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use crate::formats::FormatError;
+
+#[derive(Debug)]
+pub enum StoreError {
+    Sqlite(rusqlite::Error), 
+    Io(String), 
+    Import { path: PathBuf, error: FormatError }, 
+    /// The database was written by a newer version of litrev
+    UnsupportedVersion { found: u32, supported: u32 }, 
+}
+
+impl std::error::Error for StoreError {}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sqlite(e) => {
+                write!(f, "index database: {e}")
+            }
+            Self::Io(e) => {
+                write!(f, "{e}")
+            }
+            Self::Import { path, error } => {
+                write!(f, "{}: {error}", path.display())
+            }
+            Self::UnsupportedVersion { found, supported } => {
+                write!(
+                    f, 
+                    "the index is at schema version {found} but this litrev only supports up to {supported}, \
+                    upgrade litrev or rebuild the index with `litrev index --rebuild`"
+                )
+            }
+        }
+    }
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::Sqlite(e)
+    }
+}
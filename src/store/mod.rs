@@ -0,0 +1,585 @@
+//! The index database in `.litrev/`, which keeps every entry parsed from
+//! the project's bibliographies so that commands can query the library
+//! without parsing it again. Everything in it can be rebuilt from the
+//! bibliographies themselves
+
+mod citations;
+mod error;
+mod notes;
+mod pdfs;
+mod schema;
+mod terms;
+mod texts;
+
+pub use citations::Citation;
+pub use error::StoreError;
+pub use notes::Notes;
+pub use pdfs::StoredPdf;
+pub use terms::{CorpusStats, Posting};
+pub use texts::{PageText, Texts};
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, ToSql, Transaction};
+
+use crate::bibtex::entry::{BibTeXEntry, BibTeXEntryKind};
+use crate::bibtex::fields::BibTeXField;
+use crate::bibtex::parser::BibTeXParser;
+use crate::bibtex::error::Diagnostic;
+use crate::formats::{self, Format};
+
+pub struct Store {
+    conn: Connection,
+}
+
+/// An entry along with where and when it was indexed from
+#[derive(Debug, PartialEq)]
+pub struct StoredEntry {
+    pub entry: BibTeXEntry,
+    pub source: PathBuf,
+    /// The bytes of the source the entry was parsed from, which are only
+    /// known for BibTeX sources
+    pub span: Option<Range<usize>>,
+    /// The hash of the entry's text in its source
+    pub hash: String,
+    /// When the entry was indexed, in seconds since the Unix epoch
+    pub imported_at: i64,
+}
+
+/// A bibliography file as it was when it was last indexed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Source {
+    pub path: PathBuf,
+    /// The modification time of the file, in nanoseconds since the Unix epoch
+    pub modified: i64,
+    /// The hash of the file's contents
+    pub hash: String,
+    pub indexed_at: i64,
+}
+
+/// What an update of the index changed
+#[derive(Debug, Default, PartialEq)]
+pub struct IndexSummary {
+    /// How many sources were added, changed or removed
+    pub files: usize,
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    /// The entries that failed to parse and were left out of the index
+    pub diagnostics: Vec<(PathBuf, Diagnostic)>,
+}
+
+impl IndexSummary {
+    pub fn is_empty(&self) -> bool {
+        self.files == 0
+    }
+}
+
+impl std::fmt::Display for IndexSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} added, {} updated, {} removed", self.added, self.updated, self.removed)
+    }
+}
+
+impl Store {
+    /// Opens the database at `path`, creating it if needed and migrating it
+    /// to the latest schema
+    pub fn open(path: &Path) -> Result<Self, StoreError> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, StoreError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(mut conn: Connection) -> Result<Self, StoreError> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.pragma_update(None, "journal_mode", "wal")?;
+        schema::migrate(&mut conn)?;
+        Ok(Self { conn })
+    }
+
+    /// The schema version of the database
+    pub fn version(&self) -> Result<u32, StoreError> {
+        schema::version(&self.conn)
+    }
+
+    /// Throws away everything in the index and indexes each source again
+    pub fn rebuild(&mut self, sources: &[PathBuf]) -> Result<IndexSummary, StoreError> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM sources", [])?;
+        let mut summary = IndexSummary { files: sources.len(), ..IndexSummary::default() };
+        for path in sources {
+            index_source(&tx, path, &mut summary)?;
+        }
+        tx.commit()?;
+        Ok(summary)
+    }
+
+    /// Brings the index in line with the sources. Only sources whose
+    /// modification time and contents have both changed are parsed again,
+    /// and only the entries that changed within them are re-stored
+    pub fn update(&mut self, sources: &[PathBuf]) -> Result<IndexSummary, StoreError> {
+        let indexed = self.sources_by_id()?;
+        let tx = self.conn.transaction()?;
+        let mut summary = IndexSummary::default();
+
+        for (id, source) in &indexed {
+            if !sources.contains(&source.path) {
+                let count: i64 = tx.query_row("SELECT count(*) FROM entries WHERE source_id = ?1", [id], |row| row.get(0))?;
+                summary.removed += count as usize;
+                tx.execute("DELETE FROM sources WHERE id = ?1", [id])?;
+                summary.files += 1;
+            }
+        }
+
+        for path in sources {
+            let Some((id, source)) = indexed.iter().find(|(_, source)| &source.path == path) else {
+                index_source(&tx, path, &mut summary)?;
+                summary.files += 1;
+                continue;
+            };
+            let (contents, modified) = read_source(path)?;
+            if modified == source.modified {
+                continue;
+            }
+            let contents_hash = hash(contents.as_bytes());
+            // a touched but unchanged file only needs its new time recorded
+            if contents_hash != source.hash {
+                reindex_source(&tx, *id, path, &contents, &mut summary)?;
+                summary.files += 1;
+            }
+            tx.execute(
+                "UPDATE sources SET modified = ?2, hash = ?3, indexed_at = ?4 WHERE id = ?1",
+                params![id, modified, contents_hash, now()],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(summary)
+    }
+
+    /// Whether the index holds exactly these sources, each unchanged since
+    /// it was indexed
+    pub fn is_fresh(&self, sources: &[PathBuf]) -> Result<bool, StoreError> {
+        let indexed = self.sources()?;
+        Ok(indexed.len() == sources.len() && indexed.iter().all(|source| {
+            sources.contains(&source.path) && modified(&source.path).ok() == Some(source.modified)
+        }))
+    }
+
+    /// The indexed sources, in the order they were indexed
+    pub fn sources(&self) -> Result<Vec<Source>, StoreError> {
+        Ok(self.sources_by_id()?.into_iter().map(|(_, source)| source).collect())
+    }
+
+    fn sources_by_id(&self) -> Result<Vec<(i64, Source)>, StoreError> {
+        let mut statement = self.conn.prepare(
+            "SELECT id, path, modified, hash, indexed_at FROM sources ORDER BY id"
+        )?;
+        let sources = statement.query_map([], |row| {
+            Ok((row.get(0)?, Source {
+                path: PathBuf::from(row.get::<_, String>(1)?),
+                modified: row.get(2)?,
+                hash: row.get(3)?,
+                indexed_at: row.get(4)?,
+            }))
+        })?;
+        Ok(sources.collect::<Result<_, _>>()?)
+    }
+
+    /// Every entry, in the order of the sources and then of the entries
+    /// within each source
+    pub fn entries(&self) -> Result<Vec<StoredEntry>, StoreError> {
+        self.query_entries("", &[])
+    }
+
+    /// The first entry with the citekey, if any
+    pub fn entry(&self, citekey: &str) -> Result<Option<StoredEntry>, StoreError> {
+        Ok(self.query_entries("WHERE entries.citekey = ?1", &[&citekey])?.into_iter().next())
+    }
+
+    /// Every citekey with the title of its entry, cheaper than loading the
+    /// entries in full
+    pub fn citekeys(&self) -> Result<Vec<(String, String)>, StoreError> {
+        let mut statement = self.conn.prepare(
+            "SELECT citekey, title FROM entries ORDER BY source_id, position"
+        )?;
+        let citekeys = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(citekeys.collect::<Result<_, _>>()?)
+    }
+
+    /// The entries matching a `WHERE` clause on the entries table, with their
+    /// fields
+    fn query_entries(&self, filter: &str, params: &[&dyn ToSql]) -> Result<Vec<StoredEntry>, StoreError> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT entries.id, entries.citekey, entries.kind, sources.path, entries.span_start,
+                    entries.span_end, entries.hash, entries.imported_at
+             FROM entries JOIN sources ON sources.id = entries.source_id {filter}
+             ORDER BY sources.id, entries.position"
+        ))?;
+        let mut ids = HashMap::new();
+        let mut entries = Vec::new();
+        let mut rows = statement.query(params)?;
+        while let Some(row) = rows.next()? {
+            let kind: String = row.get(2)?;
+            let kind = kind.parse().unwrap_or(BibTeXEntryKind::Unknown);
+            let span = match (row.get::<_, Option<i64>>(4)?, row.get::<_, Option<i64>>(5)?) {
+                (Some(start), Some(end)) => Some(start as usize..end as usize),
+                _ => None,
+            };
+            ids.insert(row.get::<_, i64>(0)?, entries.len());
+            entries.push(StoredEntry {
+                entry: BibTeXEntry::new(kind, row.get(1)?),
+                source: PathBuf::from(row.get::<_, String>(3)?),
+                span,
+                hash: row.get(6)?,
+                imported_at: row.get(7)?,
+            });
+        }
+
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT entry_id, key, value FROM fields
+             WHERE entry_id IN (SELECT entries.id FROM entries {filter})
+             ORDER BY entry_id, position"
+        ))?;
+        let mut rows = statement.query(params)?;
+        while let Some(row) = rows.next()? {
+            if let Some(&i) = ids.get(&row.get::<_, i64>(0)?) {
+                let key: String = row.get(1)?;
+                let value: String = row.get(2)?;
+                entries[i].entry.add_field(BibTeXField::from_key_value(&key, &value));
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Reads a source along with its modification time
+fn read_source(path: &Path) -> Result<(String, i64), StoreError> {
+    let io_error = |e: std::io::Error| StoreError::Io(format!("{}: {e}", path.display()));
+    let contents = fs::read_to_string(path).map_err(io_error)?;
+    let modified = modified(path).map_err(io_error)?;
+    Ok((contents, modified))
+}
+
+/// Parses a source and stores it along with its entries
+fn index_source(tx: &Transaction, path: &Path, summary: &mut IndexSummary) -> Result<(), StoreError> {
+    let (contents, modified) = read_source(path)?;
+    let entries = parse_source(path, &contents, summary)?;
+    let now = now();
+
+    tx.execute(
+        "INSERT INTO sources (path, modified, hash, indexed_at) VALUES (?1, ?2, ?3, ?4)",
+        params![path.to_string_lossy(), modified, hash(contents.as_bytes()), now],
+    )?;
+    let source_id = tx.last_insert_rowid();
+    for (position, (entry, span)) in entries.iter().enumerate() {
+        let entry_hash = entry_hash(&contents, entry, span);
+        insert_entry(tx, source_id, position, entry, span, &entry_hash, now)?;
+    }
+    summary.added += entries.len();
+    Ok(())
+}
+
+/// Re-parses a source that has changed, diffing its entries against those
+/// stored by citekey and hash so that only changed entries are re-stored
+fn reindex_source(
+    tx: &Transaction, 
+    source_id: i64, 
+    path: &Path, 
+    contents: &str, 
+    summary: &mut IndexSummary,
+) -> Result<(), StoreError> {
+    let entries = parse_source(path, contents, summary)?;
+    let now = now();
+
+    // a citekey may appear more than once in a file, so each is matched with
+    // the stored rows in order
+    let mut stored: HashMap<String, VecDeque<(i64, String)>> = HashMap::new();
+    let mut statement = tx.prepare_cached("SELECT id, citekey, hash FROM entries WHERE source_id = ?1 ORDER BY position")?;
+    let mut rows = statement.query([source_id])?;
+    while let Some(row) = rows.next()? {
+        stored.entry(row.get(1)?).or_default().push_back((row.get(0)?, row.get(2)?));
+    }
+
+    for (position, (entry, span)) in entries.iter().enumerate() {
+        let entry_hash = entry_hash(contents, entry, span);
+        let start = span.as_ref().map(|span| span.start as i64);
+        let end = span.as_ref().map(|span| span.end as i64);
+        match stored.get_mut(&entry.citekey).and_then(VecDeque::pop_front) {
+            Some((id, previous)) if previous == entry_hash => {
+                // only where the entry sits in the file may have changed
+                tx.prepare_cached("UPDATE entries SET position = ?2, span_start = ?3, span_end = ?4 WHERE id = ?1")?
+                    .execute(params![id, position as i64, start, end])?;
+            }
+            Some((id, _)) => {
+                tx.prepare_cached(
+                    "UPDATE entries SET position = ?2, kind = ?3, title = ?4, span_start = ?5, span_end = ?6, hash = ?7 
+                     WHERE id = ?1"
+                )?.execute(params![id, position as i64, entry.kind.to_string(), title(entry), start, end, entry_hash])?;
+                tx.prepare_cached("DELETE FROM fields WHERE entry_id = ?1")?.execute([id])?;
+                insert_fields(tx, id, entry)?;
+                terms::index_terms(tx, id, entry)?;
+                summary.updated += 1;
+            }
+            None => {
+                insert_entry(tx, source_id, position, entry, span, &entry_hash, now)?;
+                summary.added += 1;
+            }
+        }
+    }
+
+    for (id, _) in stored.into_values().flatten() {
+        tx.prepare_cached("DELETE FROM entries WHERE id = ?1")?.execute([id])?;
+        summary.removed += 1;
+    }
+    Ok(())
+}
+
+fn insert_entry(
+    tx: &Transaction, 
+    source_id: i64, 
+    position: usize, 
+    entry: &BibTeXEntry, 
+    span: &Option<Range<usize>>, 
+    entry_hash: &str, 
+    now: i64,
+) -> Result<(), StoreError> {
+    tx.prepare_cached(
+        "INSERT INTO entries (source_id, position, citekey, kind, title, span_start, span_end, hash, imported_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"
+    )?.execute(params![
+        source_id,
+        position as i64,
+        entry.citekey,
+        entry.kind.to_string(),
+        title(entry),
+        span.as_ref().map(|span| span.start as i64),
+        span.as_ref().map(|span| span.end as i64),
+        entry_hash,
+        now,
+    ])?;
+    let entry_id = tx.last_insert_rowid();
+    insert_fields(tx, entry_id, entry)?;
+    terms::index_terms(tx, entry_id, entry)
+}
+
+fn insert_fields(tx: &Transaction, entry_id: i64, entry: &BibTeXEntry) -> Result<(), StoreError> {
+    let mut insert = tx.prepare_cached("INSERT INTO fields (entry_id, position, key, value) VALUES (?1, ?2, ?3, ?4)")?;
+    for (position, field) in entry.fields().iter().enumerate() {
+        insert.execute(params![entry_id, position as i64, field.key(), field.value()])?;
+    }
+    Ok(())
+}
+
+fn title(entry: &BibTeXEntry) -> &str {
+    entry.title().map_or("", String::as_str)
+}
+
+/// The hash of an entry's text in its source, or of the entry written as
+/// BibTeX if its span is not known
+fn entry_hash(contents: &str, entry: &BibTeXEntry, span: &Option<Range<usize>>) -> String {
+    match span {
+        Some(span) => hash(&contents.as_bytes()[span.clone()]),
+        None => hash(entry.to_string().as_bytes()),
+    }
+}
+
+/// An entry with the bytes it spans in its source, if known
+type Spanned = (BibTeXEntry, Option<Range<usize>>);
+
+/// The entries of a source along with their spans, which are only tracked
+/// for BibTeX. Broken BibTeX entries are skipped and noted in the summary so
+/// that one half-written entry does not keep the rest of a file out of the
+/// index
+fn parse_source(path: &Path, contents: &str, summary: &mut IndexSummary) -> Result<Vec<Spanned>, StoreError> {
+    if contents.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    if Format::detect(contents) == Some(Format::BibTeX) {
+        let (entries, diagnostics) = BibTeXParser::new(contents).parse_lenient();
+        summary.diagnostics.extend(diagnostics.into_iter().map(|diagnostic| (path.to_path_buf(), diagnostic)));
+        return Ok(entries.into_iter().map(|(entry, span)| (entry, Some(span))).collect());
+    }
+    let entries = formats::import(contents, &HashSet::new())
+        .map_err(|error| StoreError::Import { path: path.to_path_buf(), error })?;
+    Ok(entries.into_iter().map(|entry| (entry, None)).collect())
+}
+
+fn hash(bytes: &[u8]) -> String {
+    blake3::hash(bytes).to_hex().to_string()
+}
+
+/// The modification time of a file in nanoseconds since the Unix epoch
+fn modified(path: &Path) -> std::io::Result<i64> {
+    let modified = fs::metadata(path)?.modified()?;
+    Ok(modified.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_nanos() as i64))
+}
+
+/// The current time in seconds since the Unix epoch
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibtex::error::ParseError;
+
+    const SAMPLE: &str = "@article{Smith2019,
+  author = {Smith, John},
+  title = {Adjoint Methods},
+  month = oct,
+  year = {2019}
+}
+
+@book{Doe2020, title = {A Book}}
+";
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("litrev-store-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_rebuild_and_query() {
+        let dir = scratch_dir("rebuild");
+        let bib = dir.join("refs.bib");
+        fs::write(&bib, SAMPLE).unwrap();
+        let sources = [bib.clone()];
+
+        let mut store = Store::open_in_memory().unwrap();
+        assert!(!store.is_fresh(&sources).unwrap());
+        assert_eq!(store.rebuild(&sources).unwrap().added, 2);
+        assert!(store.is_fresh(&sources).unwrap());
+
+        let parsed = BibTeXParser::new(SAMPLE).parse().unwrap();
+        let stored = store.entries().unwrap();
+        assert_eq!(stored.iter().map(|stored| &stored.entry).collect::<Vec<_>>(), parsed.iter().collect::<Vec<_>>());
+        assert_eq!(stored[0].source, bib);
+        assert_eq!(&SAMPLE[stored[1].span.clone().unwrap()], "@book{Doe2020, title = {A Book}}");
+        assert_eq!(stored[1].hash, hash(b"@book{Doe2020, title = {A Book}}"));
+
+        assert_eq!(store.entry("Doe2020").unwrap().unwrap().entry, parsed[1]);
+        assert_eq!(store.entry("Missing").unwrap(), None);
+        assert_eq!(store.citekeys().unwrap()[0], (String::from("Smith2019"), String::from("Adjoint Methods")));
+
+        // rebuilding replaces rather than adds to the index
+        store.rebuild(&sources).unwrap();
+        assert_eq!(store.entries().unwrap().len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Overwrites a file, giving it a modification time of its own since
+    /// writes in quick succession can share one
+    fn write(path: &Path, contents: &str, seconds: u64) {
+        fs::write(path, contents).unwrap();
+        let time = UNIX_EPOCH + std::time::Duration::from_secs(seconds);
+        fs::File::options().write(true).open(path).unwrap().set_modified(time).unwrap();
+    }
+
+    #[test]
+    fn test_update() {
+        let dir = scratch_dir("update");
+        let (first, second) = (dir.join("first.bib"), dir.join("second.bib"));
+        write(&first, "@misc{A, title = {A}}\n@misc{B, title = {B}}\n@misc{C, title = {C}}", 1);
+        write(&second, "@misc{D, title = {D}}", 1);
+
+        let mut store = Store::open_in_memory().unwrap();
+        let summary = store.update(&[first.clone(), second.clone()]).unwrap();
+        assert_eq!(summary, IndexSummary { files: 2, added: 4, ..IndexSummary::default() });
+        let imported_at = store.entry("A").unwrap().unwrap().imported_at;
+
+        // touching a file without changing it re-stores nothing
+        write(&first, "@misc{A, title = {A}}\n@misc{B, title = {B}}\n@misc{C, title = {C}}", 2);
+        assert!(store.update(&[first.clone(), second.clone()]).unwrap().is_empty());
+
+        write(&first, "@misc{E, title = {E}}\n@misc{A, title = {A}}\n@misc{C, title = {C, revised}}", 3);
+        let summary = store.update(&[first.clone(), second.clone()]).unwrap();
+        assert_eq!(summary, IndexSummary { files: 1, added: 1, updated: 1, removed: 1, ..IndexSummary::default() });
+        let citekeys: Vec<String> = store.citekeys().unwrap().into_iter().map(|(citekey, _)| citekey).collect();
+        assert_eq!(citekeys, ["E", "A", "C", "D"]);
+        let a = store.entry("A").unwrap().unwrap();
+        assert_eq!(a.imported_at, imported_at);
+        assert_eq!(a.span, Some(22..43));
+        assert_eq!(store.entry("C").unwrap().unwrap().entry.title().unwrap(), "C, revised");
+        // the inverted index follows the entries
+        assert_eq!(store.postings("revised").unwrap()[0].citekey, "C");
+        assert!(store.postings("b").unwrap().is_empty());
+
+        // a broken entry is left out without losing the rest of the file
+        write(&second, "@misc{D, title = {D}}\n@misc{F, title = {F}", 4);
+        let summary = store.update(&[first.clone(), second.clone()]).unwrap();
+        assert_eq!(summary.diagnostics, [(second.clone(), Diagnostic { line: 2, error: ParseError::UnexpectedEOF })]);
+        assert!(store.entry("D").unwrap().is_some());
+
+        let summary = store.update(std::slice::from_ref(&first)).unwrap();
+        assert_eq!(summary, IndexSummary { files: 1, removed: 1, ..IndexSummary::default() });
+        assert!(store.is_fresh(std::slice::from_ref(&first)).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_update_unchanged() {
+        let dir = scratch_dir("unchanged");
+        let bib = dir.join("refs.bib");
+        let sources = [bib.clone()];
+        write(&bib, SAMPLE, 1);
+
+        let mut store = Store::open_in_memory().unwrap();
+        store.update(&sources).unwrap();
+        let before = store.entries().unwrap();
+
+        // a file with the same modification time is not even read
+        write(&bib, &SAMPLE.replace("A Book", "A Novel"), 1);
+        assert!(store.update(&sources).unwrap().is_empty());
+        assert_eq!(store.entries().unwrap(), before);
+
+        // one touched without changing has its new time recorded, so that
+        // it is fresh again, and keeps every entry as it was
+        write(&bib, SAMPLE, 2);
+        assert!(!store.is_fresh(&sources).unwrap());
+        assert!(store.update(&sources).unwrap().is_empty());
+        assert!(store.is_fresh(&sources).unwrap());
+        assert_eq!(store.sources().unwrap()[0].modified, 2_000_000_000);
+        assert_eq!(store.entries().unwrap(), before);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_braced_names() {
+        let dir = scratch_dir("names");
+        let bib = dir.join("refs.bib");
+        let names = r#"{NASA Langley Research Center} and {Barnes and Noble} and M{\"u}ller, Hans"#;
+        fs::write(&bib, format!("@techreport{{Nasa2010,\n  author = {{{names}}},\n  title = {{{{CFD}} Vision 2030}}\n}}\n")).unwrap();
+
+        let mut store = Store::open_in_memory().unwrap();
+        store.rebuild(std::slice::from_ref(&bib)).unwrap();
+        let stored = store.entry("Nasa2010").unwrap().unwrap().entry;
+        let authors: Vec<String> = stored.author().unwrap().iter().map(|author| author.to_bibtex_name()).collect();
+        assert_eq!(authors, ["{NASA Langley Research Center}", "{Barnes and Noble}", r#"M{\"u}ller, Hans"#]);
+        assert_eq!(stored, BibTeXParser::new(&fs::read_to_string(&bib).unwrap()).parse().unwrap()[0]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_versions() {
+        let dir = scratch_dir("versions");
+        let path = dir.join("index.db");
+        assert_eq!(Store::open(&path).unwrap().version().unwrap(), schema::latest_version());
+
+        Connection::open(&path).unwrap().pragma_update(None, "user_version", 99).unwrap();
+        assert!(matches!(
+            Store::open(&path),
+            Err(StoreError::UnsupportedVersion { found: 99, .. })
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
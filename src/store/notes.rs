@@ -0,0 +1,68 @@
+//! A cache of the text of the notes on entries, see [`crate::notes`]. A
+//! note is only read again when its modification time changes
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use rusqlite::params;
+
+use crate::notes;
+
+use super::{modified, Store, StoreError};
+
+/// The text of each note below its frontmatter, by the citekey of its entry
+pub type Notes = HashMap<String, String>;
+
+impl Store {
+    /// The notes in a notes directory, reading those that are new or
+    /// changed since they were last read. Notes no longer there are
+    /// forgotten
+    pub fn notes(&mut self, dir: &Path) -> Result<Notes, StoreError> {
+        let io_error = |path: &Path, e: std::io::Error| StoreError::Io(format!("{}: {e}", path.display()));
+        let mut files = Vec::new();
+        match fs::read_dir(dir) {
+            Ok(entries) => {
+                for entry in entries {
+                    let path = entry.map_err(|e| io_error(dir, e))?.path();
+                    let citekey = path.file_stem().and_then(|stem| stem.to_str()).map(String::from);
+                    if let (Some(citekey), true) = (citekey, path.extension().is_some_and(|extension| extension == "md")) {
+                        files.push((citekey, path));
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(io_error(dir, e)),
+        }
+
+        let cached = self.cached_notes()?;
+        let tx = self.conn.transaction()?;
+        for citekey in cached.keys() {
+            if !files.iter().any(|(file, _)| file == citekey) {
+                tx.prepare_cached("DELETE FROM notes WHERE citekey = ?1")?.execute([citekey])?;
+            }
+        }
+        let mut notes = Notes::new();
+        for (citekey, path) in files {
+            let modified = modified(&path).map_err(|e| io_error(&path, e))?;
+            if let Some((_, text)) = cached.get(&citekey).filter(|(time, _)| *time == modified) {
+                notes.insert(citekey, text.clone());
+                continue;
+            }
+            let note = fs::read_to_string(&path).map_err(|e| io_error(&path, e))?;
+            let text = notes::body(&note).to_string();
+            tx.prepare_cached("INSERT OR REPLACE INTO notes (citekey, modified, text) VALUES (?1, ?2, ?3)")?
+                .execute(params![citekey, modified, text])?;
+            notes.insert(citekey, text);
+        }
+        tx.commit()?;
+        Ok(notes)
+    }
+
+    /// The cached notes along with their modification times
+    fn cached_notes(&self) -> Result<HashMap<String, (i64, String)>, StoreError> {
+        let mut statement = self.conn.prepare("SELECT citekey, modified, text FROM notes")?;
+        let notes = statement.query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?;
+        Ok(notes.collect::<Result<_, _>>()?)
+    }
+}
@@ -0,0 +1,110 @@
+//! A cache of what was read from each PDF, see [`crate::pdf`]. A PDF is
+//! only read again when its modification time changes
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::params;
+
+use crate::pdf;
+
+use super::{hash, modified, Store, StoreError};
+
+/// A PDF as it was when it was last read
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredPdf {
+    pub path: PathBuf,
+    /// The hash of the file's contents
+    pub hash: String,
+    pub title: Option<String>,
+    /// The authors in the metadata, separated by `and`
+    pub author: Option<String>,
+    /// The normalised DOIs in the info dictionary and on the first page
+    pub dois: Vec<String>,
+    /// Why the PDF could not be read, if it could not. Its path and hash are
+    /// still known
+    pub error: Option<String>,
+}
+
+impl Store {
+    /// The PDFs at `paths`, reading those that are new or changed since they
+    /// were last read. PDFs no longer among `paths` are forgotten
+    pub fn pdfs(&mut self, paths: &[PathBuf]) -> Result<Vec<StoredPdf>, StoreError> {
+        let cached = self.cached_pdfs()?;
+        let tx = self.conn.transaction()?;
+        for path in cached.keys() {
+            if !paths.contains(path) {
+                tx.prepare_cached("DELETE FROM pdfs WHERE path = ?1")?.execute([path.to_string_lossy()])?;
+            }
+        }
+
+        let mut pdfs = Vec::new();
+        for path in paths {
+            let io_error = |e: std::io::Error| StoreError::Io(format!("{}: {e}", path.display()));
+            let modified = modified(path).map_err(io_error)?;
+            if let Some((_, pdf)) = cached.get(path).filter(|(time, _)| *time == modified) {
+                pdfs.push(pdf.clone());
+                continue;
+            }
+            let pdf = read_pdf(path, hash(&fs::read(path).map_err(io_error)?));
+            tx.prepare_cached(
+                "INSERT OR REPLACE INTO pdfs (path, modified, hash, title, author, dois, error)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"
+            )?.execute(params![
+                path.to_string_lossy(),
+                modified,
+                pdf.hash,
+                pdf.title,
+                pdf.author,
+                pdf.dois.join("\n"),
+                pdf.error,
+            ])?;
+            pdfs.push(pdf);
+        }
+        // the text of a PDF is kept as long as some PDF has its contents
+        tx.execute("DELETE FROM texts WHERE hash NOT IN (SELECT hash FROM pdfs)", [])?;
+        tx.commit()?;
+        Ok(pdfs)
+    }
+
+    /// The cached PDFs along with their modification times
+    fn cached_pdfs(&self) -> Result<HashMap<PathBuf, (i64, StoredPdf)>, StoreError> {
+        let mut statement = self.conn.prepare("SELECT path, modified, hash, title, author, dois, error FROM pdfs")?;
+        let pdfs = statement.query_map([], |row| {
+            let path = PathBuf::from(row.get::<_, String>(0)?);
+            let dois: String = row.get(5)?;
+            let pdf = StoredPdf {
+                path: path.clone(),
+                hash: row.get(2)?,
+                title: row.get(3)?,
+                author: row.get(4)?,
+                dois: dois.lines().map(String::from).collect(),
+                error: row.get(6)?,
+            };
+            Ok((path, (row.get(1)?, pdf)))
+        })?;
+        Ok(pdfs.collect::<Result<_, _>>()?)
+    }
+}
+
+fn read_pdf(path: &Path, hash: String) -> StoredPdf {
+    match pdf::read(path) {
+        Ok(metadata) => StoredPdf {
+            path: path.to_path_buf(),
+            hash,
+            title: metadata.title,
+            author: (!metadata.authors.is_empty()).then(|| metadata.authors.join(" and ")),
+            dois: metadata.dois,
+            error: None,
+        },
+        Err(e) => StoredPdf {
+            path: path.to_path_buf(),
+            hash,
+            title: None,
+            author: None,
+            dois: Vec::new(),
+            error: Some(e.to_string()),
+        },
+    }
+}
@@ -0,0 +1,121 @@
+//! The schema of the index database. Each migration takes the database from
+//! one version to the next and `PRAGMA user_version` records how far it has
+//! got, so a database written by an older litrev is brought up to date when
+//! it is opened. Migrations are only ever appended
+
+use rusqlite::Connection;
+
+use super::error::StoreError;
+
+const MIGRATIONS: &[&str] = &[
+    // 1: bibliography files and the entries parsed from them
+    "CREATE TABLE sources (
+        id INTEGER PRIMARY KEY,
+        path TEXT NOT NULL UNIQUE,
+        modified INTEGER NOT NULL,
+        hash TEXT NOT NULL,
+        indexed_at INTEGER NOT NULL
+    );
+    CREATE TABLE entries (
+        id INTEGER PRIMARY KEY,
+        source_id INTEGER NOT NULL REFERENCES sources(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        citekey TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        title TEXT NOT NULL,
+        span_start INTEGER,
+        span_end INTEGER,
+        hash TEXT NOT NULL,
+        imported_at INTEGER NOT NULL
+    );
+    CREATE INDEX entries_citekey ON entries(citekey);
+    CREATE INDEX entries_source ON entries(source_id);
+    CREATE TABLE fields (
+        entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        key TEXT NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY (entry_id, position)
+    );",
+    // 2: the inverted index searches are ranked with. Clearing the hashes
+    // makes the next update re-store every entry, which fills it in
+    "CREATE TABLE terms (
+        term TEXT NOT NULL,
+        field TEXT NOT NULL,
+        entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+        frequency INTEGER NOT NULL,
+        PRIMARY KEY (term, field, entry_id)
+    ) WITHOUT ROWID;
+    CREATE INDEX terms_entry ON terms(entry_id);
+    CREATE TABLE field_lengths (
+        entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+        field TEXT NOT NULL,
+        length INTEGER NOT NULL,
+        PRIMARY KEY (entry_id, field)
+    ) WITHOUT ROWID;
+    UPDATE sources SET modified = 0, hash = '';
+    UPDATE entries SET hash = '';",
+    // 3: what was read from each PDF in the project's PDF directories, so
+    // that only new and changed PDFs are read again
+    "CREATE TABLE pdfs (
+        path TEXT PRIMARY KEY,
+        modified INTEGER NOT NULL,
+        hash TEXT NOT NULL,
+        title TEXT,
+        author TEXT,
+        dois TEXT NOT NULL,
+        error TEXT
+    );",
+    // 4: the text of every page of each PDF, by the hash of its contents
+    "CREATE TABLE texts (
+        hash TEXT PRIMARY KEY,
+        error TEXT
+    );
+    CREATE TABLE pages (
+        hash TEXT NOT NULL REFERENCES texts(hash) ON DELETE CASCADE,
+        number INTEGER NOT NULL,
+        text TEXT NOT NULL,
+        PRIMARY KEY (hash, number)
+    ) WITHOUT ROWID;",
+    // 5: the citations between entries found in the reference lists of
+    // their PDFs, by citekey since entries are re-stored under new IDs
+    "CREATE TABLE citations (
+        citing TEXT NOT NULL,
+        cited TEXT NOT NULL,
+        evidence TEXT NOT NULL,
+        reference TEXT NOT NULL,
+        PRIMARY KEY (citing, cited)
+    ) WITHOUT ROWID;
+    CREATE INDEX citations_cited ON citations(cited);",
+    // 6: the text of the markdown notes on entries, read again when their
+    // modification time changes
+    "CREATE TABLE notes (
+        citekey TEXT PRIMARY KEY,
+        modified INTEGER NOT NULL,
+        text TEXT NOT NULL
+    ) WITHOUT ROWID;",
+];
+
+/// The schema version this litrev writes
+pub fn latest_version() -> u32 {
+    MIGRATIONS.len() as u32
+}
+
+pub fn version(conn: &Connection) -> Result<u32, StoreError> {
+    Ok(conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+}
+
+/// Applies every migration the database has not had yet
+pub fn migrate(conn: &mut Connection) -> Result<(), StoreError> {
+    let found = version(conn)?;
+    if found > latest_version() {
+        return Err(StoreError::UnsupportedVersion { found, supported: latest_version() });
+    }
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(found as usize) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", i as u32 + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
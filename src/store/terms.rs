@@ -0,0 +1,101 @@
+//! The inverted index over the words of the fields searches are ranked by,
+//! see [`crate::search::rank`]
+
+use std::collections::HashMap;
+
+use rusqlite::{params, Transaction};
+
+use crate::bibtex::entry::BibTeXEntry;
+use crate::search::{self, rank::FIELD_WEIGHTS};
+
+use super::{Store, StoreError};
+
+/// A term as it appears in one field of an entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct Posting {
+    pub citekey: String,
+    pub term: String,
+    pub field: String,
+    /// How many times the term appears in the field
+    pub frequency: u32,
+    /// How many words the field has
+    pub length: u32,
+}
+
+/// What ranking needs to know about the library as a whole
+#[derive(Debug, Default, PartialEq)]
+pub struct CorpusStats {
+    pub entries: u32,
+    /// The average number of words in each field over every entry, counting
+    /// those without the field as having none
+    pub average_lengths: HashMap<String, f64>,
+}
+
+impl Store {
+    /// The postings of every term starting with `prefix`
+    pub fn postings(&self, prefix: &str) -> Result<Vec<Posting>, StoreError> {
+        let mut statement = self.conn.prepare_cached(
+            "SELECT entries.citekey, terms.term, terms.field, terms.frequency, field_lengths.length
+             FROM terms
+             JOIN entries ON entries.id = terms.entry_id
+             JOIN field_lengths ON field_lengths.entry_id = terms.entry_id AND field_lengths.field = terms.field
+             WHERE terms.term >= ?1 AND terms.term < ?2"
+        )?;
+        // the terms are compared byte by byte, so this is past every term
+        // with the prefix
+        let end = format!("{prefix}\u{10FFFF}");
+        let postings = statement.query_map(params![prefix, end], |row| {
+            Ok(Posting {
+                citekey: row.get(0)?,
+                term: row.get(1)?,
+                field: row.get(2)?,
+                frequency: row.get(3)?,
+                length: row.get(4)?,
+            })
+        })?;
+        Ok(postings.collect::<Result<_, _>>()?)
+    }
+
+    /// Every distinct term in the index
+    pub fn terms(&self) -> Result<Vec<String>, StoreError> {
+        let mut statement = self.conn.prepare("SELECT DISTINCT term FROM terms")?;
+        let terms = statement.query_map([], |row| row.get(0))?;
+        Ok(terms.collect::<Result<_, _>>()?)
+    }
+
+    pub fn corpus_stats(&self) -> Result<CorpusStats, StoreError> {
+        let entries: u32 = self.conn.query_row("SELECT count(*) FROM entries", [], |row| row.get(0))?;
+        let mut statement = self.conn.prepare("SELECT field, sum(length) FROM field_lengths GROUP BY field")?;
+        let totals = statement.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+        let mut average_lengths = HashMap::new();
+        for total in totals {
+            let (field, total) = total?;
+            average_lengths.insert(field, total as f64 / entries.max(1) as f64);
+        }
+        Ok(CorpusStats { entries, average_lengths })
+    }
+}
+
+/// Replaces the terms stored for an entry with those of its ranked fields
+pub(super) fn index_terms(tx: &Transaction, entry_id: i64, entry: &BibTeXEntry) -> Result<(), StoreError> {
+    tx.prepare_cached("DELETE FROM terms WHERE entry_id = ?1")?.execute([entry_id])?;
+    tx.prepare_cached("DELETE FROM field_lengths WHERE entry_id = ?1")?.execute([entry_id])?;
+
+    let mut insert_length = tx.prepare_cached("INSERT INTO field_lengths (entry_id, field, length) VALUES (?1, ?2, ?3)")?;
+    let mut insert_term = tx.prepare_cached("INSERT INTO terms (term, field, entry_id, frequency) VALUES (?1, ?2, ?3, ?4)")?;
+    for (field, _) in FIELD_WEIGHTS {
+        let words: Vec<String> = search::field_words(entry, field).into_iter().flatten().collect();
+        if words.is_empty() {
+            continue;
+        }
+        let mut frequencies: HashMap<&str, u32> = HashMap::new();
+        for word in &words {
+            *frequencies.entry(word).or_default() += 1;
+        }
+        insert_length.execute(params![entry_id, field, words.len() as i64])?;
+        for (term, frequency) in frequencies {
+            insert_term.execute(params![term, field, entry_id, frequency])?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,69 @@
+//! A cache of the full text of PDFs, by the hash of their contents so that
+//! a PDF is only read in full once, however often it is renamed or moved
+
+use std::collections::HashMap;
+
+use rusqlite::params;
+
+use crate::pdf::{self, Page};
+
+use super::{Store, StoredPdf, StoreError};
+
+/// The text of the pages of each PDF, by the hash of its contents
+pub type Texts = HashMap<String, Vec<PageText>>;
+
+/// The text of one page of a PDF
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageText {
+    /// The page number, from 1
+    pub number: u32,
+    /// The lines of the page, separated by newlines
+    pub text: String,
+}
+
+impl Store {
+    /// The text of each readable PDF's pages, by the hash of its contents.
+    /// PDFs whose text is not cached yet are read in full first
+    pub fn texts(&mut self, pdfs: &[StoredPdf]) -> Result<Texts, StoreError> {
+        let mut texts = self.cached_texts()?;
+        let tx = self.conn.transaction()?;
+        for pdf in pdfs.iter().filter(|pdf| pdf.error.is_none()) {
+            if texts.contains_key(&pdf.hash) {
+                continue;
+            }
+            // a PDF that could be read before but not now has no text, and
+            // is not read again until it changes
+            let (pages, error) = match pdf::read_pages(&pdf.path) {
+                Ok(pages) => (pages.iter().map(page_text).collect(), None),
+                Err(e) => (Vec::new(), Some(e.to_string())),
+            };
+            tx.prepare_cached("INSERT OR REPLACE INTO texts (hash, error) VALUES (?1, ?2)")?.execute(params![pdf.hash, error])?;
+            let mut insert = tx.prepare_cached("INSERT INTO pages (hash, number, text) VALUES (?1, ?2, ?3)")?;
+            for page in &pages {
+                insert.execute(params![pdf.hash, page.number, page.text])?;
+            }
+            texts.insert(pdf.hash.clone(), pages);
+        }
+        tx.commit()?;
+        Ok(texts)
+    }
+
+    fn cached_texts(&self) -> Result<Texts, StoreError> {
+        let mut texts = Texts::new();
+        let mut statement = self.conn.prepare("SELECT hash FROM texts")?;
+        for hash in statement.query_map([], |row| row.get::<_, String>(0))? {
+            texts.insert(hash?, Vec::new());
+        }
+        let mut statement = self.conn.prepare("SELECT hash, number, text FROM pages ORDER BY hash, number")?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            let hash: String = row.get(0)?;
+            texts.entry(hash).or_default().push(PageText { number: row.get(1)?, text: row.get(2)? });
+        }
+        Ok(texts)
+    }
+}
+
+fn page_text(page: &Page) -> PageText {
+    PageText { number: page.number, text: page.text() }
+}
@@ -0,0 +1,974 @@
+//! The reference styles built into litrev
+
+use crate::bibtex::entry::{BibTeXEntry, BibTeXEntryKind};
+use crate::bibtex::fields::Authors;
+use crate::bibtex::latex;
+
+use super::case::{capitalise, sentence_case, title_case};
+use super::markup::Rendered;
+use super::names::{self, family_given, family_initials, given_family, initials_family, join, truncate};
+
+/// The broad kinds of publication the styles distinguish between
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Genre {
+    Article,
+    Book,
+    /// A paper or chapter within some larger work, e.g. proceedings
+    Chapter,
+    Thesis { doctoral: bool },
+    Report,
+    Other,
+}
+
+impl From<&BibTeXEntryKind> for Genre {
+    fn from(kind: &BibTeXEntryKind) -> Self {
+        match kind {
+            BibTeXEntryKind::Article       => Self::Article,
+            BibTeXEntryKind::Book
+            | BibTeXEntryKind::Booklet
+            | BibTeXEntryKind::Proceedings
+            | BibTeXEntryKind::Manual      => Self::Book,
+            BibTeXEntryKind::Inbook
+            | BibTeXEntryKind::InCollection
+            | BibTeXEntryKind::InProceedings
+            | BibTeXEntryKind::Conference  => Self::Chapter,
+            BibTeXEntryKind::PhdThesis     => Self::Thesis { doctoral: true },
+            BibTeXEntryKind::MasterThesis  => Self::Thesis { doctoral: false },
+            BibTeXEntryKind::TechReport    => Self::Report,
+            BibTeXEntryKind::Misc
+            | BibTeXEntryKind::Unpublished
+            | BibTeXEntryKind::Unknown     => Self::Other,
+        }
+    }
+}
+
+fn title(entry: &BibTeXEntry) -> Option<String> {
+    entry.title().map(|t| latex::decode(t))
+}
+
+fn container(entry: &BibTeXEntry) -> Option<String> {
+    entry.journal().or(entry.book_title()).map(|t| latex::decode(t))
+}
+
+/// The publisher, or the school or institution for theses and reports
+fn publisher(entry: &BibTeXEntry) -> Option<String> {
+    entry.publisher()
+        .or(entry.school())
+        .or(entry.institution())
+        .or(entry.organization())
+        .map(|p| latex::decode(p))
+}
+
+fn address(entry: &BibTeXEntry) -> Option<String> {
+    entry.address().map(|a| latex::decode(a))
+}
+
+fn how_published(entry: &BibTeXEntry) -> Option<String> {
+    entry.how_published().map(|h| latex::decode(h))
+}
+
+/// The page ranges joined by en dashes, e.g. "12–34". Pages that are not
+/// numbers, such as "A12--A19" or "e1234", are kept as written with their
+/// dashes made en dashes
+fn pages(entry: &BibTeXEntry) -> Option<String> {
+    let Some(pages) = entry.pages() else {
+        let pages = entry.non_standard_field("pages")?;
+        return Some(latex::decode(pages).replace('-', "\u{2013}"));
+    };
+    let ranges: Vec<String> = pages
+        .iter()
+        .map(|range| match range.end() {
+            Some(end) => format!("{}\u{2013}{}", range.start(), end),
+            None => range.start().to_string(),
+        })
+        .collect();
+    Some(ranges.join(", "))
+}
+
+fn is_page_range(entry: &BibTeXEntry) -> bool {
+    match entry.pages() {
+        Some(pages) => pages.first_page() != pages.last_page(),
+        None => entry.non_standard_field("pages").is_some_and(|pages| pages.contains([',', '-'])),
+    }
+}
+
+fn doi_url(entry: &BibTeXEntry) -> Option<String> {
+    entry.doi().map(|doi| format!("https://doi.org/{}", doi.trim()))
+}
+
+fn url(entry: &BibTeXEntry) -> Option<String> {
+    doi_url(entry).or_else(|| entry.non_standard_field("url").cloned())
+}
+
+fn ordinal(n: u16) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (1, 11) | (2, 12) | (3, 13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+fn month_abbreviation(month: u8) -> Option<&'static str> {
+    let abbreviation = match month {
+        1 => "Jan.", 2 => "Feb.", 3 => "Mar.", 4 => "Apr.", 5 => "May", 6 => "Jun.",
+        7 => "Jul.", 8 => "Aug.", 9 => "Sept.", 10 => "Oct.", 11 => "Nov.", 12 => "Dec.",
+        _ => return None,
+    };
+    Some(abbreviation)
+}
+
+/// A title in quotes with the trailing punctuation placed inside them,
+/// unless the title already ends in a question or exclamation mark
+fn quoted(title: &str, punctuation: &str) -> String {
+    if title.ends_with(['?', '!']) {
+        format!("\u{201c}{title}\u{201d}")
+    } else {
+        format!("\u{201c}{title}{punctuation}\u{201d}")
+    }
+}
+
+/// Appends the details of a numeric style after a comma, which a quoted
+/// title already ends with, e.g. `“Title,” vol. 3, 2019`
+fn push_details(r: &mut Rendered, details: &[String]) {
+    if details.is_empty() {
+        return;
+    }
+    if !r.ends_with(&[',', ' ', '\u{201d}']) {
+        r.text(",");
+    }
+    if !r.ends_with(&[' ']) {
+        r.text(" ");
+    }
+    r.text(&details.join(", "));
+}
+
+/// "Ed." or "Eds.", or in lowercase for the styles that use it
+fn editor_role(editors: &Authors, lowercase: bool) -> &'static str {
+    match (editors.len() > 1, lowercase) {
+        (true, false) => "Eds.",
+        (false, false) => "Ed.",
+        (true, true) => "eds.",
+        (false, true) => "ed.",
+    }
+}
+
+/// The editors of an edited book, which is listed under them when it has
+/// no author
+fn book_editors(entry: &BibTeXEntry) -> Option<&Authors> {
+    match entry.author() {
+        Some(_) => None,
+        None => entry.editor().filter(|_| Genre::from(&entry.kind) == Genre::Book),
+    }
+}
+
+/// Adds a trailing full stop to a name list unless it already ends in one
+fn with_full_stop(text: &str) -> String {
+    if text.ends_with('.') { text.to_string() } else { format!("{text}.") }
+}
+
+/// APA 7th edition
+pub fn apa(entry: &BibTeXEntry) -> Rendered {
+    let genre = Genre::from(&entry.kind);
+    let mut r = Rendered::new();
+    let title = title(entry).map(|t| sentence_case(&t));
+    let date = entry.year().map_or(String::from("n.d."), |y| y.to_string());
+
+    let apa_names = |authors: &Authors| -> String {
+        // APA lists up to 20 authors, then the first 19, an ellipsis and the last
+        let mut names: Vec<String> = authors.iter().map(|a| family_initials(a, " ")).collect();
+        if names.len() > 20 {
+            let last = names.pop().unwrap_or_default();
+            names.truncate(19);
+            return format!("{}, . . . {}", names.join(", "), last);
+        }
+        join(&names, "&", true, true)
+    };
+    let editor_names = |editors: &Authors| -> String {
+        let names: Vec<String> = editors.iter().map(initials_family).collect();
+        format!("{} ({})", join(&names, "&", true, false), editor_role(editors, false))
+    };
+
+    // edited books are listed under their editors
+    let author = entry.author().map(apa_names).or_else(|| {
+        book_editors(entry).map(|editors| format!("{} ({})", apa_names(editors), editor_role(editors, false)))
+    });
+
+    let italic_title = matches!(genre, Genre::Book | Genre::Thesis { .. } | Genre::Report | Genre::Other);
+    match author {
+        Some(author) => {
+            r.text(&with_full_stop(&author));
+            r.text(&format!(" ({date}). "));
+            if let Some(title) = &title {
+                if italic_title { r.italic(title); } else { r.text(title); }
+            }
+        }
+        // without an author the title moves into the author position
+        None => {
+            if let Some(title) = &title {
+                if italic_title { r.italic(title); } else { r.text(title); }
+            }
+            r.terminate(".");
+            r.text(&format!(" ({date})"));
+        }
+    }
+
+    match genre {
+        Genre::Article => {
+            r.terminate(".");
+            if let Some(journal) = container(entry) {
+                r.text(" ").italic(&journal);
+                if let Some(volume) = entry.volume() {
+                    r.text(", ").italic(&volume.to_string());
+                }
+                if let Some(number) = entry.number() {
+                    r.text(&format!("({number})"));
+                }
+                if let Some(pages) = pages(entry) {
+                    r.text(&format!(", {pages}"));
+                }
+                r.text(".");
+            }
+        }
+        Genre::Book => {
+            if let Some(edition) = entry.edition().filter(|&&e| e > 1) {
+                r.text(&format!(" ({} ed.)", ordinal(*edition)));
+            }
+            r.terminate(".");
+            if let Some(publisher) = publisher(entry) {
+                r.text(&format!(" {publisher}."));
+            }
+        }
+        Genre::Chapter => {
+            r.terminate(".");
+            r.text(" In ");
+            if let Some(editors) = entry.editor() {
+                r.text(&format!("{}, ", editor_names(editors)));
+            }
+            // container titles keep their title case, unlike the work's own
+            if let Some(book_title) = container(entry) {
+                r.italic(&book_title);
+            }
+            if let Some(pages) = pages(entry) {
+                let prefix = if is_page_range(entry) { "pp." } else { "p." };
+                r.text(&format!(" ({prefix} {pages})"));
+            }
+            r.terminate(".");
+            if let Some(publisher) = publisher(entry) {
+                r.text(&format!(" {publisher}."));
+            }
+        }
+        Genre::Thesis { doctoral } => {
+            let kind = if doctoral { "Doctoral dissertation" } else { "Master's thesis" };
+            match publisher(entry) {
+                Some(school) => r.text(&format!(" [{kind}, {school}].")),
+                None => r.text(&format!(" [{kind}].")),
+            };
+        }
+        Genre::Report => {
+            if let Some(number) = entry.number() {
+                r.text(&format!(" (Report No. {number})"));
+            }
+            r.terminate(".");
+            if let Some(institution) = publisher(entry) {
+                r.text(&format!(" {institution}."));
+            }
+        }
+        Genre::Other => {
+            r.terminate(".");
+            if let Some(publisher) = publisher(entry).or(how_published(entry)) {
+                r.text(&format!(" {publisher}."));
+            }
+        }
+    }
+
+    if let Some(url) = url(entry) {
+        r.text(" ").link(&url);
+    }
+    r
+}
+
+/// IEEE
+pub fn ieee(entry: &BibTeXEntry) -> Rendered {
+    let genre = Genre::from(&entry.kind);
+    let mut r = Rendered::new();
+    let title = title(entry);
+
+    let ieee_names = |authors: &Authors| -> String {
+        let (names, truncated) = truncate(authors, |_, a| initials_family(a), 7, 1);
+        if truncated { format!("{} et al.", names[0]) } else { join(&names, "and", true, false) }
+    };
+    // without any names the title leads
+    if let Some(authors) = entry.author() {
+        r.text(&format!("{}, ", ieee_names(authors)));
+    } else if let Some(editors) = book_editors(entry) {
+        r.text(&format!("{}, {}, ", ieee_names(editors), editor_role(editors, false)));
+    }
+
+    let date = {
+        let month = entry.month().and_then(|m| month_abbreviation(*m));
+        match (month, entry.year()) {
+            (Some(month), Some(year)) => Some(format!("{month} {year}")),
+            (None, Some(year)) => Some(year.to_string()),
+            _ => None,
+        }
+    };
+
+    let mut details: Vec<String> = Vec::new();
+    match genre {
+        Genre::Article => {
+            if let Some(title) = &title {
+                r.text(&format!("{} ", quoted(&sentence_case(title), ",")));
+            }
+            if let Some(journal) = container(entry) {
+                r.italic(&journal);
+            }
+            details.extend(entry.volume().map(|v| format!("vol. {v}")));
+            details.extend(entry.number().map(|n| format!("no. {n}")));
+            details.extend(pages(entry).map(|p| if is_page_range(entry) { format!("pp. {p}") } else { format!("p. {p}") }));
+            details.extend(date);
+            details.extend(entry.doi().map(|doi| format!("doi: {doi}")));
+        }
+        Genre::Book => {
+            if let Some(title) = &title {
+                r.italic(&title_case(title));
+            }
+            details.extend(entry.edition().filter(|&&e| e > 1).map(|e| format!("{} ed", ordinal(*e))));
+            let place = match (address(entry), publisher(entry)) {
+                (Some(address), Some(publisher)) => Some(format!("{address}: {publisher}")),
+                (None, Some(publisher)) => Some(publisher),
+                _ => None,
+            };
+            if !details.is_empty() {
+                r.text(&format!(", {}", details.join(", ")));
+                details.clear();
+            }
+            r.text(". ");
+            if let Some(place) = place {
+                r.text(&format!("{place}, "));
+            }
+            r.text(&entry.year().map_or(String::from("n.d"), |y| y.to_string()));
+        }
+        Genre::Chapter => {
+            if let Some(title) = &title {
+                r.text(&format!("{} ", quoted(&sentence_case(title), ",")));
+            }
+            r.text("in ");
+            if let Some(book_title) = container(entry) {
+                r.italic(&book_title);
+            }
+            if let Some(editors) = entry.editor() {
+                let names: Vec<String> = editors.iter().map(initials_family).collect();
+                details.push(format!("{} {}", join(&names, "and", true, false), editor_role(editors, false)));
+            }
+            details.extend(address(entry));
+            details.extend(date);
+            details.extend(pages(entry).map(|p| if is_page_range(entry) { format!("pp. {p}") } else { format!("p. {p}") }));
+            details.extend(entry.doi().map(|doi| format!("doi: {doi}")));
+        }
+        Genre::Thesis { doctoral } => {
+            if let Some(title) = &title {
+                r.text(&quoted(&sentence_case(title), ","));
+            }
+            r.text(if doctoral { " Ph.D. dissertation" } else { " M.S. thesis" });
+            details.extend(publisher(entry));
+            details.extend(address(entry));
+            details.extend(date);
+        }
+        Genre::Report => {
+            if let Some(title) = &title {
+                r.text(&quoted(&sentence_case(title), ","));
+            }
+            r.text(" ");
+            r.text(&publisher(entry).unwrap_or_default());
+            details.extend(address(entry));
+            details.push(match entry.number() {
+                Some(number) => format!("Tech. Rep. {number}"),
+                None => String::from("Tech. Rep."),
+            });
+            details.extend(date);
+        }
+        Genre::Other => {
+            if let Some(title) = &title {
+                r.text(&quoted(&sentence_case(title), ","));
+            }
+            details.extend(how_published(entry));
+            details.extend(date);
+        }
+    }
+
+    push_details(&mut r, &details);
+    r.terminate(".");
+
+    if genre == Genre::Other {
+        if let Some(url) = entry.non_standard_field("url") {
+            r.text(" [Online]. Available: ").link(url);
+        }
+    }
+    r
+}
+
+/// Chicago Manual of Style, 17th edition, author-date
+pub fn chicago(entry: &BibTeXEntry) -> Rendered {
+    let genre = Genre::from(&entry.kind);
+    let mut r = Rendered::new();
+    let title = title(entry).map(|t| title_case(&t));
+    let year = entry.year().map_or(String::from("n.d."), |y| y.to_string());
+
+    // only the first name is inverted, and lists of more than ten are cut to seven
+    let chicago_names = |authors: &Authors| -> String {
+        let (names, truncated) = truncate(
+            authors,
+            |i, a| if i == 0 { family_given(a) } else { given_family(a) },
+            11,
+            7,
+        );
+        // the comma after the inverted first name is kept even for two names
+        if truncated { format!("{}, et al.", names.join(", ")) } else { join(&names, "and", true, true) }
+    };
+    let names = entry.author().map(chicago_names).or_else(|| {
+        book_editors(entry).map(|editors| format!("{}, {}", chicago_names(editors), editor_role(editors, true)))
+    });
+
+    let italic_title = matches!(genre, Genre::Book | Genre::Report);
+    let push_title = |r: &mut Rendered| {
+        if let Some(title) = &title {
+            if italic_title {
+                r.italic(title);
+                r.terminate(".");
+            } else {
+                r.text(&quoted(title, "."));
+            }
+        }
+    };
+
+    match names {
+        Some(names) => {
+            r.text(&format!("{} {}. ", with_full_stop(&names), year));
+            push_title(&mut r);
+        }
+        None => {
+            push_title(&mut r);
+            r.text(&format!(" {year}."));
+        }
+    }
+
+    let place = match (address(entry), publisher(entry)) {
+        (Some(address), Some(publisher)) => Some(format!("{address}: {publisher}")),
+        (None, Some(publisher)) => Some(publisher),
+        _ => None,
+    };
+
+    match genre {
+        Genre::Article => {
+            if let Some(journal) = container(entry) {
+                r.text(" ").italic(&journal);
+            }
+            if let Some(volume) = entry.volume() {
+                r.text(&format!(" {volume}"));
+            }
+            if let Some(number) = entry.number() {
+                r.text(&format!(" ({number})"));
+            }
+            if let Some(pages) = pages(entry) {
+                r.text(&format!(": {pages}"));
+            }
+            r.terminate(".");
+        }
+        Genre::Book | Genre::Report | Genre::Other => {
+            if let Some(edition) = entry.edition().filter(|&&e| e > 1) {
+                r.text(&format!(" {} ed.", ordinal(*edition)));
+            }
+            if let (Genre::Report, Some(number)) = (genre, entry.number()) {
+                r.text(&format!(" Report {number}."));
+            }
+            if let Some(place) = place {
+                r.text(&format!(" {place}."));
+            }
+        }
+        Genre::Chapter => {
+            r.text(" In ");
+            if let Some(book_title) = container(entry) {
+                r.italic(&title_case(&book_title));
+            }
+            if let Some(editors) = entry.editor() {
+                let names: Vec<String> = editors.iter().map(given_family).collect();
+                r.text(&format!(", edited by {}", join(&names, "and", true, false)));
+            }
+            if let Some(pages) = pages(entry) {
+                r.text(&format!(", {pages}"));
+            }
+            r.text(".");
+            if let Some(place) = place {
+                r.text(&format!(" {place}."));
+            }
+        }
+        Genre::Thesis { doctoral } => {
+            let kind = if doctoral { "PhD diss." } else { "Master's thesis" };
+            match publisher(entry) {
+                Some(school) => r.text(&format!(" {kind}, {school}.")),
+                None => r.text(&format!(" {kind}.")),
+            };
+        }
+    }
+
+    if let Some(url) = url(entry) {
+        r.text(" ").link(&url);
+        r.text(".");
+    }
+    r
+}
+
+/// Harvard, following the Cite Them Right conventions
+pub fn harvard(entry: &BibTeXEntry) -> Rendered {
+    let genre = Genre::from(&entry.kind);
+    let mut r = Rendered::new();
+    let title = title(entry);
+    let year = entry.year().map_or(String::from("no date"), |y| y.to_string());
+
+    // a title leading the reference starts with a capital like a name would
+    let title = match entry.author().or(book_editors(entry)) {
+        Some(_) => title,
+        None => title.map(|title| capitalise(&title)),
+    };
+    let harvard_names = |authors: &Authors| -> String {
+        let (names, truncated) = truncate(authors, |_, a| family_initials(a, ""), 4, 1);
+        if truncated { format!("{} et al.", names[0]) } else { join(&names, "and", false, false) }
+    };
+    let names = entry.author().map(harvard_names).or_else(|| {
+        book_editors(entry).map(|editors| format!("{} ({})", harvard_names(editors), editor_role(editors, true)))
+    });
+    let italic_title = matches!(genre, Genre::Book | Genre::Thesis { .. } | Genre::Report | Genre::Other);
+    let push_title = |r: &mut Rendered| {
+        if let Some(title) = &title {
+            if italic_title {
+                r.italic(title);
+            } else {
+                r.text(&format!("\u{2018}{title}\u{2019}"));
+            }
+        }
+    };
+
+    match names {
+        Some(names) => {
+            r.text(&format!("{names} ({year}) "));
+            push_title(&mut r);
+        }
+        // without an author the title moves into the author position
+        None => {
+            push_title(&mut r);
+            r.text(&format!(" ({year})"));
+        }
+    }
+
+    let place = match (address(entry), publisher(entry)) {
+        (Some(address), Some(publisher)) => Some(format!("{address}: {publisher}")),
+        (None, Some(publisher)) => Some(publisher),
+        _ => None,
+    };
+
+    match genre {
+        Genre::Article => {
+            if let Some(journal) = container(entry) {
+                r.text(", ").italic(&journal);
+            }
+            if let Some(volume) = entry.volume() {
+                r.text(&format!(", {volume}"));
+                if let Some(number) = entry.number() {
+                    r.text(&format!("({number})"));
+                }
+            }
+            if let Some(pages) = pages(entry) {
+                let prefix = if is_page_range(entry) { "pp." } else { "p." };
+                r.text(&format!(", {prefix} {pages}"));
+            }
+            r.terminate(".");
+        }
+        Genre::Book | Genre::Other => {
+            r.terminate(".");
+            if let Some(edition) = entry.edition().filter(|&&e| e > 1) {
+                r.text(&format!(" {} edn.", ordinal(*edition)));
+            }
+            if let Some(place) = place {
+                r.text(&format!(" {place}."));
+            }
+        }
+        Genre::Chapter => {
+            r.text(", in ");
+            if let Some(editors) = entry.editor() {
+                let names: Vec<String> = editors.iter().map(|e| family_initials(e, "")).collect();
+                r.text(&format!("{} ({}) ", join(&names, "and", false, false), editor_role(editors, true)));
+            }
+            if let Some(book_title) = container(entry) {
+                r.italic(&book_title);
+            }
+            r.terminate(".");
+            if let Some(place) = place {
+                r.text(&format!(" {place}"));
+            }
+            if let Some(pages) = pages(entry) {
+                let prefix = if is_page_range(entry) { "pp." } else { "p." };
+                r.text(&format!(", {prefix} {pages}"));
+            }
+            r.terminate(".");
+        }
+        Genre::Thesis { doctoral } => {
+            r.terminate(".");
+            r.text(if doctoral { " PhD thesis." } else { " Master's thesis." });
+            if let Some(school) = publisher(entry) {
+                r.text(&format!(" {school}."));
+            }
+        }
+        Genre::Report => {
+            r.terminate(".");
+            if let Some(number) = entry.number() {
+                r.text(&format!(" Report {number}."));
+            }
+            if let Some(place) = place {
+                r.text(&format!(" {place}."));
+            }
+        }
+    }
+
+    if let Some(url) = url(entry) {
+        r.text(" Available at: ").link(&url);
+        r.text(".");
+    }
+    r
+}
+
+/// The numeric style of the AIAA journals
+pub fn aiaa(entry: &BibTeXEntry) -> Rendered {
+    let genre = Genre::from(&entry.kind);
+    let mut r = Rendered::new();
+    let title = title(entry).map(|t| title_case(&t));
+    let year = entry.year().map(|y| y.to_string());
+
+    let aiaa_names = |authors: &Authors| -> String {
+        let (names, truncated) = truncate(authors, |_, a| family_initials(a, " "), 11, 1);
+        if truncated { format!("{} et al.", names[0]) } else { join(&names, "and", true, true) }
+    };
+    // without any names the title leads
+    if let Some(authors) = entry.author() {
+        r.text(&format!("{}, ", aiaa_names(authors)));
+    } else if let Some(editors) = book_editors(entry) {
+        r.text(&format!("{} ({}), ", aiaa_names(editors), editor_role(editors, true)));
+    }
+
+    let mut details: Vec<String> = Vec::new();
+    match genre {
+        Genre::Article => {
+            if let Some(title) = &title {
+                r.text(&format!("{} ", quoted(title, ",")));
+            }
+            if let Some(journal) = container(entry) {
+                r.italic(&journal);
+            }
+            details.extend(entry.volume().map(|v| format!("Vol. {v}")));
+            details.extend(entry.number().map(|n| format!("No. {n}")));
+            details.extend(year);
+            details.extend(pages(entry).map(|p| if is_page_range(entry) { format!("pp. {p}") } else { format!("p. {p}") }));
+        }
+        Genre::Book => {
+            if let Some(title) = &title {
+                r.italic(title);
+            }
+            details.extend(entry.edition().filter(|&&e| e > 1).map(|e| format!("{} ed.", ordinal(*e))));
+            details.extend(publisher(entry));
+            details.extend(address(entry));
+            details.extend(year);
+        }
+        Genre::Chapter => {
+            if let Some(title) = &title {
+                r.text(&format!("{} ", quoted(title, ",")));
+            }
+            if let Some(book_title) = container(entry) {
+                r.italic(&book_title);
+            }
+            if let Some(editors) = entry.editor() {
+                let names: Vec<String> = editors.iter().map(initials_family).collect();
+                details.push(format!("edited by {}", join(&names, "and", true, false)));
+            }
+            details.extend(publisher(entry));
+            details.extend(address(entry));
+            details.extend(year);
+            details.extend(pages(entry).map(|p| if is_page_range(entry) { format!("pp. {p}") } else { format!("p. {p}") }));
+        }
+        Genre::Thesis { doctoral } => {
+            if let Some(title) = &title {
+                r.text(&quoted(title, ","));
+            }
+            r.text(if doctoral { " Ph.D. Dissertation" } else { " M.S. Thesis" });
+            details.extend(publisher(entry));
+            details.extend(address(entry));
+            details.extend(year);
+        }
+        Genre::Report => {
+            if let Some(title) = &title {
+                r.text(&quoted(title, ","));
+            }
+            r.text(" ");
+            r.text(&publisher(entry).unwrap_or_default());
+            details.extend(entry.number().map(|n| format!("Rept. {n}")));
+            details.extend(address(entry));
+            details.extend(year);
+        }
+        Genre::Other => {
+            if let Some(title) = &title {
+                r.text(&quoted(title, ","));
+            }
+            details.extend(how_published(entry));
+            details.extend(year);
+        }
+    }
+
+    push_details(&mut r, &details);
+    r.terminate(".");
+
+    if let Some(url) = url(entry) {
+        r.text(" ").link(&url);
+    }
+    r
+}
+
+/// The family name of the first author, used to sort author-date bibliographies
+pub fn sort_key(entry: &BibTeXEntry) -> (String, u16, String) {
+    let family = entry
+        .author()
+        .or(entry.editor())
+        .and_then(Authors::first)
+        .map(names::family)
+        .or_else(|| title(entry))
+        .unwrap_or_default()
+        .to_lowercase();
+    (family, entry.year().copied().unwrap_or(u16::MAX), title(entry).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bibtex::parser::BibTeXParser;
+    use crate::style::markup::Markup;
+
+    const BIBTEX: &str = r#"
+        @article{kenway2019,
+            author = {Kenway, Gaetan K. W. and Mader, Charles A. and He, Ping and Martins, Joaquim R. R. A.},
+            title = {Effective Adjoint Approaches for Computational Fluid Dynamics},
+            journal = {Progress in Aerospace Sciences},
+            volume = 110,
+            number = 3,
+            pages = {100542--100560},
+            year = 2019,
+            month = oct,
+            doi = {10.1016/j.paerosci.2019.05.002},
+        }
+        @inproceedings{he2018,
+            author = {He, Ping and Doe, Jane},
+            title = {An aerodynamic design optimization framework using a discrete adjoint approach with {OpenFOAM}},
+            booktitle = {2018 AIAA Aviation Forum},
+            editor = {Smith, John},
+            publisher = {AIAA},
+            address = {Atlanta, GA},
+            pages = {1--20},
+            year = 2018,
+        }
+        @book{anderson2004,
+            author = {Anderson, John D.},
+            title = {Modern Compressible Flow: with Historical Perspective},
+            edition = 3,
+            publisher = {McGraw-Hill},
+            address = {New York},
+            year = 2004,
+        }
+        @phdthesis{roe2021,
+            author = {Roe, Richard},
+            title = {Hypersonic boundary layer transition},
+            school = {University of Oxford},
+            year = 2021,
+        }
+        @article{muller2000,
+            author = {M{\"u}ller, J{\"o}rg},
+            title = {Navier--Stokes Solvers},
+            journal = {Journal of Fluid Mechanics},
+            pages = {A12--A19},
+            year = 2000,
+        }
+        @article{anon2000,
+            title = {no authors here},
+            year = 2000,
+        }
+        @book{smith2010,
+            editor = {Smith, John and Doe, Jane},
+            title = {Edited Book},
+            publisher = {Wiley},
+            year = 2010,
+        }
+    "#;
+
+    fn entry(citekey: &str) -> BibTeXEntry {
+        BibTeXParser::new(BIBTEX)
+            .parse()
+            .unwrap()
+            .into_iter()
+            .find(|e| e.citekey == citekey)
+            .unwrap()
+    }
+
+    fn text(rendered: Rendered) -> String {
+        rendered.render(Markup::Text)
+    }
+
+    #[test]
+    fn test_apa() {
+        assert_eq!(
+            text(apa(&entry("kenway2019"))),
+            "Kenway, G. K. W., Mader, C. A., He, P., & Martins, J. R. R. A. (2019). Effective adjoint approaches \
+             for computational fluid dynamics. Progress in Aerospace Sciences, 110(3), 100542\u{2013}100560. \
+             https://doi.org/10.1016/j.paerosci.2019.05.002"
+        );
+        assert_eq!(
+            text(apa(&entry("he2018"))),
+            "He, P., & Doe, J. (2018). An aerodynamic design optimization framework using a discrete adjoint \
+             approach with OpenFOAM. In J. Smith (Ed.), 2018 AIAA Aviation Forum (pp. 1\u{2013}20). AIAA."
+        );
+        assert_eq!(
+            text(apa(&entry("anderson2004"))),
+            "Anderson, J. D. (2004). Modern compressible flow: With historical perspective (3rd ed.). McGraw-Hill."
+        );
+        assert_eq!(
+            text(apa(&entry("roe2021"))),
+            "Roe, R. (2021). Hypersonic boundary layer transition [Doctoral dissertation, University of Oxford]."
+        );
+    }
+
+    #[test]
+    fn test_apa_markup() {
+        let rendered = apa(&entry("kenway2019"));
+        let markdown = rendered.render(Markup::Markdown);
+        assert!(markdown.contains("*Progress in Aerospace Sciences*, *110*(3)"));
+        assert!(markdown.ends_with("<https://doi.org/10.1016/j.paerosci.2019.05.002>"));
+
+        let html = rendered.render(Markup::Html);
+        assert!(html.contains("<i>Progress in Aerospace Sciences</i>, <i>110</i>(3)"));
+        assert!(html.contains("<a href=\"https://doi.org/10.1016/j.paerosci.2019.05.002\">"));
+    }
+
+    #[test]
+    fn test_ieee() {
+        assert_eq!(
+            text(ieee(&entry("kenway2019"))),
+            "G. K. W. Kenway, C. A. Mader, P. He, and J. R. R. A. Martins, \u{201c}Effective adjoint approaches \
+             for computational fluid dynamics,\u{201d} Progress in Aerospace Sciences, vol. 110, no. 3, \
+             pp. 100542\u{2013}100560, Oct. 2019, doi: 10.1016/j.paerosci.2019.05.002."
+        );
+        assert_eq!(
+            text(ieee(&entry("anderson2004"))),
+            "J. D. Anderson, Modern Compressible Flow: With Historical Perspective, 3rd ed. New York: McGraw-Hill, 2004."
+        );
+        assert_eq!(
+            text(ieee(&entry("roe2021"))),
+            "R. Roe, \u{201c}Hypersonic boundary layer transition,\u{201d} Ph.D. dissertation, University of Oxford, 2021."
+        );
+    }
+
+    #[test]
+    fn test_ieee_et_al() {
+        let names = (1..=7).map(|i| format!("Author{i}, A.")).collect::<Vec<_>>().join(" and ");
+        let input = format!("@article{{many, author = {{{names}}}, title = {{T}}, journal = {{J}}, year = 2020}}");
+        let entry = BibTeXParser::new(&input).parse().unwrap().remove(0);
+        assert!(text(ieee(&entry)).starts_with("A. Author1 et al., "));
+    }
+
+    #[test]
+    fn test_chicago() {
+        assert_eq!(
+            text(chicago(&entry("kenway2019"))),
+            "Kenway, Gaetan K. W., Charles A. Mader, Ping He, and Joaquim R. R. A. Martins. 2019. \
+             \u{201c}Effective Adjoint Approaches for Computational Fluid Dynamics.\u{201d} Progress in \
+             Aerospace Sciences 110 (3): 100542\u{2013}100560. https://doi.org/10.1016/j.paerosci.2019.05.002."
+        );
+        assert_eq!(
+            text(chicago(&entry("he2018"))),
+            "He, Ping, and Jane Doe. 2018. \u{201c}An Aerodynamic Design Optimization Framework Using a \
+             Discrete Adjoint Approach with OpenFOAM.\u{201d} In 2018 AIAA Aviation Forum, edited by John \
+             Smith, 1\u{2013}20. Atlanta, GA: AIAA."
+        );
+    }
+
+    #[test]
+    fn test_harvard() {
+        assert_eq!(
+            text(harvard(&entry("kenway2019"))),
+            "Kenway, G.K.W. et al. (2019) \u{2018}Effective Adjoint Approaches for Computational Fluid \
+             Dynamics\u{2019}, Progress in Aerospace Sciences, 110(3), pp. 100542\u{2013}100560. Available at: \
+             https://doi.org/10.1016/j.paerosci.2019.05.002."
+        );
+        assert_eq!(
+            text(harvard(&entry("anderson2004"))),
+            "Anderson, J.D. (2004) Modern Compressible Flow: with Historical Perspective. 3rd edn. New York: McGraw-Hill."
+        );
+    }
+
+    #[test]
+    fn test_aiaa() {
+        assert_eq!(
+            text(aiaa(&entry("kenway2019"))),
+            "Kenway, G. K. W., Mader, C. A., He, P., and Martins, J. R. R. A., \u{201c}Effective Adjoint \
+             Approaches for Computational Fluid Dynamics,\u{201d} Progress in Aerospace Sciences, Vol. 110, \
+             No. 3, 2019, pp. 100542\u{2013}100560. https://doi.org/10.1016/j.paerosci.2019.05.002"
+        );
+        assert_eq!(
+            text(aiaa(&entry("he2018"))),
+            "He, P., and Doe, J., \u{201c}An Aerodynamic Design Optimization Framework Using a Discrete Adjoint \
+             Approach with OpenFOAM,\u{201d} 2018 AIAA Aviation Forum, edited by J. Smith, AIAA, Atlanta, GA, \
+             2018, pp. 1\u{2013}20."
+        );
+    }
+
+    #[test]
+    fn test_latex_and_pages() {
+        let rendered = apa(&entry("muller2000"));
+        assert_eq!(
+            rendered.render(Markup::Text),
+            "M\u{fc}ller, J. (2000). Navier\u{2013}Stokes solvers. Journal of Fluid Mechanics, A12\u{2013}A19."
+        );
+        assert_eq!(
+            rendered.render(Markup::Markdown),
+            "M\u{fc}ller, J. (2000). Navier\u{2013}Stokes solvers. *Journal of Fluid Mechanics*, A12\u{2013}A19."
+        );
+        assert_eq!(
+            rendered.render(Markup::Html),
+            "M\u{fc}ller, J. (2000). Navier\u{2013}Stokes solvers. <i>Journal of Fluid Mechanics</i>, A12\u{2013}A19."
+        );
+
+        for style in [ieee, chicago, harvard, aiaa] {
+            let rendered = style(&entry("muller2000"));
+            for markup in [Markup::Text, Markup::Markdown, Markup::Html] {
+                let output = rendered.render(markup);
+                assert!(output.contains("M\u{fc}ller"), "{output}");
+                assert!(output.contains("Navier\u{2013}Stokes"), "{output}");
+                assert!(output.contains("A12\u{2013}A19"), "{output}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_author() {
+        assert_eq!(text(apa(&entry("anon2000"))), "No authors here. (2000).");
+        assert_eq!(text(ieee(&entry("anon2000"))), "\u{201c}No authors here,\u{201d} 2000.");
+        assert_eq!(text(chicago(&entry("anon2000"))), "\u{201c}No Authors Here.\u{201d} 2000.");
+        assert_eq!(text(harvard(&entry("anon2000"))), "\u{2018}No authors here\u{2019} (2000).");
+        assert_eq!(text(aiaa(&entry("anon2000"))), "\u{201c}No Authors Here,\u{201d} 2000.");
+    }
+
+    #[test]
+    fn test_edited_book() {
+        assert_eq!(text(apa(&entry("smith2010"))), "Smith, J., & Doe, J. (Eds.). (2010). Edited book. Wiley.");
+        assert_eq!(text(ieee(&entry("smith2010"))), "J. Smith and J. Doe, Eds., Edited Book. Wiley, 2010.");
+        assert_eq!(text(chicago(&entry("smith2010"))), "Smith, John, and Jane Doe, eds. 2010. Edited Book. Wiley.");
+        assert_eq!(text(harvard(&entry("smith2010"))), "Smith, J. and Doe, J. (eds.) (2010) Edited Book. Wiley.");
+        assert_eq!(text(aiaa(&entry("smith2010"))), "Smith, J., and Doe, J. (eds.), Edited Book, Wiley, 2010.");
+    }
+}
@@ -0,0 +1,135 @@
+//! Title casing. Words wrapped in braces, e.g. `{NASA}` or `{Navier-Stokes}`,
+//! are protected from any change of case the way BibTeX treats them
+
+/// Words kept in lowercase by title case unless they start or end the title
+const MINOR_WORDS: &[&str] = &[
+    "a", "an", "the", "and", "but", "or", "nor", "for", "so", "yet", "as", "at", 
+    "by", "in", "of", "off", "on", "per", "to", "up", "via", "vs", "with", "from", 
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Position {
+    /// The first word of the title or of a subtitle after a colon
+    First, 
+    Middle, 
+    Last, 
+}
+
+/// Capitalises only the first word of the title and of any subtitle, 
+/// leaving acronyms and words with inner capitals such as "McDonald" alone
+pub fn sentence_case(title: &str) -> String {
+    transform_words(title, |word, position| {
+        if position == Position::First {
+            capitalise(word)
+        } else if word.chars().skip(1).any(char::is_uppercase) {
+            word.to_string()
+        } else {
+            word.to_lowercase()
+        }
+    })
+}
+
+/// Capitalises every word except the minor ones, such as articles and short
+/// prepositions, in the middle of the title
+pub fn title_case(title: &str) -> String {
+    transform_words(title, |word, position| {
+        word.split('-')
+            .enumerate()
+            .map(|(i, part)| {
+                let bare: String = part.chars().filter(|c| c.is_alphanumeric()).collect();
+                let is_minor = MINOR_WORDS.contains(&bare.to_lowercase().as_str());
+                if is_minor && (position == Position::Middle || i > 0) {
+                    part.to_lowercase()
+                } else {
+                    capitalise(part)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("-")
+    })
+}
+
+/// Removes the protective braces from a BibTeX value
+pub fn strip_braces(s: &str) -> String {
+    s.chars().filter(|&c| c != '{' && c != '}').collect()
+}
+
+/// Capitalises the first letter, after any leading punctuation
+pub fn capitalise(word: &str) -> String {
+    let mut chars = word.chars();
+    let mut capitalised = String::with_capacity(word.len());
+    // skip over any leading punctuation such as quotes or brackets
+    for c in chars.by_ref() {
+        if c.is_alphanumeric() {
+            capitalised.extend(c.to_uppercase());
+            break;
+        }
+        capitalised.push(c);
+    }
+    capitalised.extend(chars);
+    capitalised
+}
+
+fn transform_words(title: &str, transform: impl Fn(&str, Position) -> String) -> String {
+    let words: Vec<&str> = title.split(' ').collect();
+    let last_index = words.iter().rposition(|w| !w.is_empty()).unwrap_or(0);
+
+    let mut output = Vec::with_capacity(words.len());
+    let mut depth = 0usize;
+    let mut starts_clause = true;
+
+    for (i, word) in words.iter().enumerate() {
+        let protected = depth > 0 || word.starts_with('{');
+        for c in word.chars() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+
+        if word.is_empty() {
+            output.push(String::new());
+            continue;
+        }
+
+        let position = if starts_clause {
+            Position::First
+        } else if i == last_index {
+            Position::Last
+        } else {
+            Position::Middle
+        };
+        output.push(if protected { word.to_string() } else { transform(word, position) });
+        starts_clause = word.ends_with([':', '?', '!', '.']) && depth == 0;
+    }
+
+    strip_braces(&output.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentence_case() {
+        assert_eq!(
+            sentence_case("Effective Adjoint Approaches for CFD: A Review of {Navier-Stokes} Solvers"),
+            "Effective adjoint approaches for CFD: A review of Navier-Stokes solvers"
+        );
+        assert_eq!(sentence_case("the {MDO} framework of McDonald"), "The MDO framework of McDonald");
+    }
+
+    #[test]
+    fn test_title_case() {
+        assert_eq!(
+            title_case("effective adjoint approaches for computational fluid dynamics"),
+            "Effective Adjoint Approaches for Computational Fluid Dynamics"
+        );
+        assert_eq!(
+            title_case("shock-boundary layer interaction: the state of the art in {pH} testing"),
+            "Shock-Boundary Layer Interaction: The State of the Art in pH Testing"
+        );
+        assert_eq!(title_case("what is it for"), "What Is It For");
+    }
+}
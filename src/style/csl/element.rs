@@ -0,0 +1,52 @@
+//! An owned copy of the XML of a style, so the parsed style does not borrow
+//! the file it was read from
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct Element {
+    /// The local name, without the CSL namespace
+    pub name: String, 
+    pub attributes: HashMap<String, String>, 
+    pub children: Vec<Element>, 
+    /// The text directly inside the element, e.g. of a `<term>`
+    pub text: String, 
+}
+
+impl Element {
+    pub fn from_node(node: roxmltree::Node) -> Self {
+        let attributes = node
+            .attributes()
+            .map(|attribute| (attribute.name().to_string(), attribute.value().to_string()))
+            .collect();
+        let children = node
+            .children()
+            .filter(|child| child.is_element())
+            .map(Element::from_node)
+            .collect();
+        let text = node
+            .children()
+            .filter(|child| child.is_text())
+            .filter_map(|child| child.text())
+            .collect::<String>();
+
+        Self { name: node.tag_name().name().to_string(), attributes, children, text }
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    /// The space separated values of an attribute, e.g. `type="book thesis"`
+    pub fn attribute_list(&self, name: &str) -> Vec<&str> {
+        self.attribute(name).map_or_else(Vec::new, |value| value.split_whitespace().collect())
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
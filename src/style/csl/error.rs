@@ -0,0 +1,38 @@
+#[derive(Debug)]
+pub enum CslError {
+    Io(String), 
+    Xml(String), 
+    /// The root element is not a CSL `<style>`
+    NotAStyle, 
+    /// A required element, e.g. `<citation>`, is missing
+    MissingElement(&'static str), 
+    UnknownMacro(String), 
+    UnknownCitekey(String), 
+}
+
+impl std::fmt::Display for CslError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read style: {e}"),
+            Self::Xml(e) => write!(f, "invalid style XML: {e}"),
+            Self::NotAStyle => write!(f, "not a CSL style, expected a <style> root element"),
+            Self::MissingElement(name) => write!(f, "style has no <{name}> element"),
+            Self::UnknownMacro(name) => write!(f, "style calls undefined macro '{name}'"),
+            Self::UnknownCitekey(citekey) => write!(f, "no entry with citekey '{citekey}'"),
+        }
+    }
+}
+
+impl std::error::Error for CslError {}
+
+impl From<std::io::Error> for CslError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e.to_string())
+    }
+}
+
+impl From<roxmltree::Error> for CslError {
+    fn from(e: roxmltree::Error) -> Self {
+        Self::Xml(e.to_string())
+    }
+}
@@ -0,0 +1,221 @@
+//! The localised terms a style refers to, e.g. "et al." or "pp.". Only
+//! American English is built in, but a style can override any term with a
+//! `<locale>` element of its own
+
+use std::collections::HashMap;
+
+use super::element::Element;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum TermForm {
+    Long, 
+    Short, 
+    Verb, 
+    VerbShort, 
+    Symbol, 
+}
+
+impl TermForm {
+    pub fn from_attribute(form: Option<&str>) -> Self {
+        match form {
+            Some("short")      => Self::Short, 
+            Some("verb")       => Self::Verb, 
+            Some("verb-short") => Self::VerbShort, 
+            Some("symbol")     => Self::Symbol, 
+            _                  => Self::Long, 
+        }
+    }
+
+    /// The form to try next when a term has no value in this form
+    fn fallback(self) -> Option<Self> {
+        match self {
+            Self::Long      => None, 
+            Self::Short     => Some(Self::Long), 
+            Self::Verb      => Some(Self::Long), 
+            Self::VerbShort => Some(Self::Verb), 
+            Self::Symbol    => Some(Self::Short), 
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Term {
+    single: String, 
+    multiple: String, 
+}
+
+/// `(name, form, singular, plural)`
+const EN_US_TERMS: &[(&str, TermForm, &str, &str)] = &[
+    ("accessed", TermForm::Long, "accessed", "accessed"), 
+    ("and", TermForm::Long, "and", "and"), 
+    ("and", TermForm::Symbol, "&", "&"), 
+    ("and others", TermForm::Long, "and others", "and others"), 
+    ("anonymous", TermForm::Long, "anonymous", "anonymous"), 
+    ("anonymous", TermForm::Short, "anon.", "anon."), 
+    ("at", TermForm::Long, "at", "at"), 
+    ("available at", TermForm::Long, "available at", "available at"), 
+    ("by", TermForm::Long, "by", "by"), 
+    ("circa", TermForm::Long, "circa", "circa"), 
+    ("circa", TermForm::Short, "c.", "c."), 
+    ("et-al", TermForm::Long, "et al.", "et al."), 
+    ("forthcoming", TermForm::Long, "forthcoming", "forthcoming"), 
+    ("from", TermForm::Long, "from", "from"), 
+    ("ibid", TermForm::Long, "ibid.", "ibid."), 
+    ("in", TermForm::Long, "in", "in"), 
+    ("in press", TermForm::Long, "in press", "in press"), 
+    ("no date", TermForm::Long, "no date", "no date"), 
+    ("no date", TermForm::Short, "n.d.", "n.d."), 
+    ("online", TermForm::Long, "online", "online"), 
+    ("presented at", TermForm::Long, "presented at the", "presented at the"), 
+    ("retrieved", TermForm::Long, "retrieved", "retrieved"), 
+    ("open-quote", TermForm::Long, "\u{201c}", "\u{201c}"), 
+    ("close-quote", TermForm::Long, "\u{201d}", "\u{201d}"), 
+    ("open-inner-quote", TermForm::Long, "\u{2018}", "\u{2018}"), 
+    ("close-inner-quote", TermForm::Long, "\u{2019}", "\u{2019}"), 
+    ("page-range-delimiter", TermForm::Long, "\u{2013}", "\u{2013}"), 
+    ("chapter", TermForm::Long, "chapter", "chapters"), 
+    ("chapter", TermForm::Short, "chap.", "chaps."), 
+    ("edition", TermForm::Long, "edition", "editions"), 
+    ("edition", TermForm::Short, "ed.", "eds."), 
+    ("issue", TermForm::Long, "issue", "issues"), 
+    ("issue", TermForm::Short, "no.", "nos."), 
+    ("number", TermForm::Long, "number", "numbers"), 
+    ("number", TermForm::Short, "no.", "nos."), 
+    ("page", TermForm::Long, "page", "pages"), 
+    ("page", TermForm::Short, "p.", "pp."), 
+    ("volume", TermForm::Long, "volume", "volumes"), 
+    ("volume", TermForm::Short, "vol.", "vols."), 
+    ("editor", TermForm::Long, "editor", "editors"), 
+    ("editor", TermForm::Short, "ed.", "eds."), 
+    ("editor", TermForm::Verb, "edited by", "edited by"), 
+    ("editor", TermForm::VerbShort, "ed. by", "ed. by"), 
+    ("translator", TermForm::Long, "translator", "translators"), 
+    ("translator", TermForm::Short, "tran.", "trans."), 
+    ("translator", TermForm::Verb, "translated by", "translated by"), 
+    ("translator", TermForm::VerbShort, "trans. by", "trans. by"), 
+    ("month-01", TermForm::Long, "January", "January"), 
+    ("month-02", TermForm::Long, "February", "February"), 
+    ("month-03", TermForm::Long, "March", "March"), 
+    ("month-04", TermForm::Long, "April", "April"), 
+    ("month-05", TermForm::Long, "May", "May"), 
+    ("month-06", TermForm::Long, "June", "June"), 
+    ("month-07", TermForm::Long, "July", "July"), 
+    ("month-08", TermForm::Long, "August", "August"), 
+    ("month-09", TermForm::Long, "September", "September"), 
+    ("month-10", TermForm::Long, "October", "October"), 
+    ("month-11", TermForm::Long, "November", "November"), 
+    ("month-12", TermForm::Long, "December", "December"), 
+    ("month-01", TermForm::Short, "Jan.", "Jan."), 
+    ("month-02", TermForm::Short, "Feb.", "Feb."), 
+    ("month-03", TermForm::Short, "Mar.", "Mar."), 
+    ("month-04", TermForm::Short, "Apr.", "Apr."), 
+    ("month-05", TermForm::Short, "May", "May"), 
+    ("month-06", TermForm::Short, "Jun.", "Jun."), 
+    ("month-07", TermForm::Short, "Jul.", "Jul."), 
+    ("month-08", TermForm::Short, "Aug.", "Aug."), 
+    ("month-09", TermForm::Short, "Sep.", "Sep."), 
+    ("month-10", TermForm::Short, "Oct.", "Oct."), 
+    ("month-11", TermForm::Short, "Nov.", "Nov."), 
+    ("month-12", TermForm::Short, "Dec.", "Dec."), 
+    ("long-ordinal-01", TermForm::Long, "first", "first"), 
+    ("long-ordinal-02", TermForm::Long, "second", "second"), 
+    ("long-ordinal-03", TermForm::Long, "third", "third"), 
+    ("long-ordinal-04", TermForm::Long, "fourth", "fourth"), 
+    ("long-ordinal-05", TermForm::Long, "fifth", "fifth"), 
+    ("long-ordinal-06", TermForm::Long, "sixth", "sixth"), 
+    ("long-ordinal-07", TermForm::Long, "seventh", "seventh"), 
+    ("long-ordinal-08", TermForm::Long, "eighth", "eighth"), 
+    ("long-ordinal-09", TermForm::Long, "ninth", "ninth"), 
+    ("long-ordinal-10", TermForm::Long, "tenth", "tenth"), 
+];
+
+#[derive(Debug, Clone)]
+pub(super) struct Locale {
+    terms: HashMap<(String, TermForm), Term>, 
+    /// Whether a comma or full stop after a closing quote moves inside it,
+    /// as American English does
+    pub punctuation_in_quote: bool, 
+}
+
+impl Locale {
+    pub fn en_us() -> Self {
+        let terms = EN_US_TERMS
+            .iter()
+            .map(|&(name, form, single, multiple)| {
+                let term = Term { single: single.to_string(), multiple: multiple.to_string() };
+                ((name.to_string(), form), term)
+            })
+            .collect();
+        Self { terms, punctuation_in_quote: true }
+    }
+
+    /// Overrides terms with those of a style's `<locale>` element
+    pub fn merge(&mut self, locale: &Element) {
+        if let Some(options) = locale.child("style-options") {
+            if let Some(value) = options.attribute("punctuation-in-quote") {
+                self.punctuation_in_quote = value == "true";
+            }
+        }
+        let Some(terms) = locale.child("terms") else {
+            return;
+        };
+        for term in terms.children_named("term") {
+            let Some(name) = term.attribute("name") else {
+                continue;
+            };
+            let form = TermForm::from_attribute(term.attribute("form"));
+            let single = term.child("single").map_or(term.text.as_str(), |s| s.text.as_str());
+            let multiple = term.child("multiple").map_or(single, |m| m.text.as_str());
+            let term = Term { single: single.to_string(), multiple: multiple.to_string() };
+            self.terms.insert((name.to_string(), form), term);
+        }
+    }
+
+    /// The value of a term, falling back through the other forms as the
+    /// specification describes, e.g. "symbol" to "short" to "long"
+    pub fn term(&self, name: &str, form: TermForm, plural: bool) -> Option<&str> {
+        let mut form = Some(form);
+        while let Some(current) = form {
+            if let Some(term) = self.terms.get(&(name.to_string(), current)) {
+                return Some(if plural { &term.multiple } else { &term.single });
+            }
+            form = current.fallback();
+        }
+        None
+    }
+
+    pub fn month(&self, month: u8, form: TermForm) -> Option<&str> {
+        self.term(&format!("month-{month:02}"), form, false)
+    }
+
+    pub fn long_ordinal(&self, n: u32) -> String {
+        self.term(&format!("long-ordinal-{n:02}"), TermForm::Long, false)
+            .map_or_else(|| ordinal(n), str::to_string)
+    }
+}
+
+pub(super) fn ordinal(n: u32) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (1, 11) | (2, 12) | (3, 13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+pub(super) fn roman(mut n: u32) -> String {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "m"), (900, "cm"), (500, "d"), (400, "cd"), (100, "c"), (90, "xc"), 
+        (50, "l"), (40, "xl"), (10, "x"), (9, "ix"), (5, "v"), (4, "iv"), (1, "i"), 
+    ];
+    let mut numeral = String::new();
+    for (value, symbol) in NUMERALS {
+        while n >= value {
+            numeral.push_str(symbol);
+            n -= value;
+        }
+    }
+    numeral
+}
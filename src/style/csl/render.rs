@@ -0,0 +1,716 @@
+//! Evaluation of a style's rendering elements against a single entry
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::bibtex::entry::BibTeXEntry;
+use crate::bibtex::fields::{Author, Authors};
+use crate::bibtex::latex;
+use crate::style::case::{sentence_case, strip_braces, title_case};
+use crate::style::markup::Rendered;
+use crate::style::names;
+
+use super::element::Element;
+use super::error::CslError;
+use super::locale::{self, Locale, TermForm};
+use super::variables::{self, Date};
+use super::CslStyle;
+
+/// What an element rendered, and whether it called any variables, which
+/// decides whether an enclosing group is shown
+#[derive(Debug, Default)]
+pub(super) struct Output {
+    pub rendered: Rendered,
+    /// Whether any variable was called
+    called: bool,
+    /// Whether any of the called variables had a value
+    found: bool,
+}
+
+impl Output {
+    fn term(rendered: Rendered) -> Self {
+        Self { rendered, called: false, found: false }
+    }
+
+    fn variable(rendered: Rendered) -> Self {
+        let found = !rendered.is_empty();
+        Self { rendered, called: true, found }
+    }
+
+    fn empty_variable() -> Self {
+        Self { rendered: Rendered::new(), called: true, found: false }
+    }
+}
+
+pub(super) struct Context<'a> {
+    style: &'a CslStyle,
+    entry: &'a BibTeXEntry,
+    citation_number: Option<usize>,
+    /// The name options inherited from the style and from the citation or
+    /// bibliography element
+    name_options: &'a HashMap<String, String>,
+    /// Whether a sort key is being rendered, which puts every name in sort
+    /// order
+    sorting: bool,
+    /// Variables already rendered as a substitute for an empty names
+    /// element, which are not repeated
+    suppressed: RefCell<Vec<String>>,
+    /// Every variable that has rendered a value so far
+    rendered_variables: RefCell<Vec<String>>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(
+        style: &'a CslStyle,
+        entry: &'a BibTeXEntry,
+        citation_number: Option<usize>,
+        name_options: &'a HashMap<String, String>,
+    ) -> Self {
+        Self {
+            style,
+            entry,
+            citation_number,
+            name_options,
+            sorting: false,
+            suppressed: RefCell::new(Vec::new()),
+            rendered_variables: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn sorting(mut self) -> Self {
+        self.sorting = true;
+        self
+    }
+
+    pub fn render_children(&self, children: &[Element], delimiter: &str) -> Result<Output, CslError> {
+        let mut output = Output::default();
+        for child in children {
+            // the branches of a choose are joined by the enclosing delimiter
+            // as though they were children of the enclosing element
+            let rendered = match child.name.as_str() {
+                "choose" => self.choose(child, delimiter)?,
+                _ => self.render(child)?,
+            };
+            output.called |= rendered.called;
+            output.found |= rendered.found;
+            if rendered.rendered.is_empty() {
+                continue;
+            }
+            if !output.rendered.is_empty() {
+                push(&mut output.rendered, plain(delimiter));
+            }
+            push(&mut output.rendered, rendered.rendered);
+        }
+        Ok(output)
+    }
+
+    fn render(&self, element: &Element) -> Result<Output, CslError> {
+        match element.name.as_str() {
+            "text"   => self.text(element),
+            "number" => Ok(self.number(element)),
+            "label"  => Ok(self.label(element)),
+            "names"  => self.names(element),
+            "date"   => Ok(self.date(element)),
+            "group"  => self.group(element),
+            "choose" => self.choose(element, ""),
+            _        => Ok(Output::default()),
+        }
+    }
+
+    /// The value of a standard variable, unless it was used up as a
+    /// substitute
+    fn variable(&self, name: &str) -> Option<String> {
+        if self.is_suppressed(name) {
+            return None;
+        }
+        let value = match name {
+            "citation-number" => self.citation_number.map(|n| n.to_string()),
+            _ => variables::standard(self.entry, name),
+        };
+        if value.is_some() {
+            self.rendered_variables.borrow_mut().push(name.to_string());
+        }
+        value
+    }
+
+    fn is_suppressed(&self, name: &str) -> bool {
+        self.suppressed.borrow().iter().any(|suppressed| suppressed == name)
+    }
+
+    fn has_variable(&self, name: &str) -> bool {
+        !self.is_suppressed(name)
+            && (variables::standard(self.entry, name).is_some()
+                || variables::names(self.entry, name).is_some()
+                || variables::date(self.entry, name).is_some()
+                || (name == "citation-number" && self.citation_number.is_some()))
+    }
+
+    fn text(&self, element: &Element) -> Result<Output, CslError> {
+        if let Some(name) = element.attribute("variable") {
+            let value = match element.attribute("form") {
+                Some("short") => self.variable(&format!("{name}-short")).or_else(|| self.variable(name)),
+                _ => self.variable(name),
+            };
+            let Some(value) = value else {
+                return Ok(Output::empty_variable());
+            };
+            let mut rendered = Rendered::new();
+            if name == "URL" {
+                rendered.link(value.trim());
+            } else {
+                rendered.text(&case(element, &value));
+            }
+            return Ok(Output::variable(self.decorate(element, rendered)));
+        }
+
+        if let Some(name) = element.attribute("macro") {
+            let called = self.style.macros
+                .get(name)
+                .ok_or_else(|| CslError::UnknownMacro(name.to_string()))?;
+            let output = self.render_children(&called.children, "")?;
+            return Ok(Output { rendered: self.format(element, output.rendered), ..output });
+        }
+
+        let value = if let Some(term) = element.attribute("term") {
+            let form = TermForm::from_attribute(element.attribute("form"));
+            let plural = element.attribute("plural") == Some("true");
+            self.style.locale.term(term, form, plural).unwrap_or_default()
+        } else {
+            element.attribute("value").unwrap_or_default()
+        };
+        Ok(Output::term(self.decorate(element, plain(&case(element, value)))))
+    }
+
+    fn number(&self, element: &Element) -> Output {
+        let Some(value) = element.attribute("variable").and_then(|name| self.variable(name)) else {
+            return Output::empty_variable();
+        };
+        let formatted = match value.trim().parse::<u32>() {
+            Ok(n) => match element.attribute("form") {
+                Some("ordinal")      => locale::ordinal(n),
+                Some("long-ordinal") => self.style.locale.long_ordinal(n),
+                Some("roman")        => locale::roman(n),
+                _                    => n.to_string(),
+            },
+            Err(_) => value,
+        };
+        Output::variable(self.decorate(element, plain(&case(element, &formatted))))
+    }
+
+    /// The term for a variable, e.g. "pp." before a page range. Labels do
+    /// not count as calling their variable
+    fn label(&self, element: &Element) -> Output {
+        let Some(name) = element.attribute("variable") else {
+            return Output::default();
+        };
+        let contextual = if let Some(names) = variables::names(self.entry, name) {
+            names.len() > 1
+        } else if let Some(value) = variables::standard(self.entry, name) {
+            value.contains(['-', '\u{2013}', ',', '&'])
+        } else {
+            return Output::default();
+        };
+        self.label_term(element, name, contextual)
+    }
+
+    fn label_term(&self, element: &Element, variable: &str, contextual: bool) -> Output {
+        let plural = match element.attribute("plural") {
+            Some("always") => true,
+            Some("never") => false,
+            _ => contextual,
+        };
+        let term = match variable {
+            "number-of-pages" | "page-first" => "page",
+            term => term,
+        };
+        let form = TermForm::from_attribute(element.attribute("form"));
+        let value = self.style.locale.term(term, form, plural).unwrap_or_default();
+        Output::term(self.decorate(element, plain(&case(element, value))))
+    }
+
+    fn names(&self, element: &Element) -> Result<Output, CslError> {
+        let name = element.child("name");
+        let et_al = element.child("et-al");
+        let label = element.child("label");
+        let label_first = label.is_some() && {
+            let position = |n: &str| element.children.iter().position(|child| child.name == n);
+            position("label") < position("name")
+        };
+        let delimiter = element
+            .attribute("delimiter")
+            .or_else(|| self.name_options.get("names-delimiter").map(String::as_str))
+            .unwrap_or(", ");
+
+        let mut lists = Vec::new();
+        for variable in element.attribute_list("variable") {
+            if self.is_suppressed(variable) {
+                continue;
+            }
+            let Some(list) = variables::names(self.entry, variable) else {
+                continue;
+            };
+            self.rendered_variables.borrow_mut().push(variable.to_string());
+
+            let mut rendered = self.name_list(list, name, et_al);
+            if let Some(label) = label {
+                let term = self.label_term(label, variable, list.len() > 1).rendered;
+                rendered = if label_first {
+                    let mut labelled = term;
+                    labelled.append(rendered);
+                    labelled
+                } else {
+                    push(&mut rendered, term);
+                    rendered
+                };
+            }
+            lists.push(rendered);
+        }
+
+        if lists.is_empty() {
+            return self.substitute(element);
+        }
+
+        let mut joined = Rendered::new();
+        for list in lists {
+            if !joined.is_empty() {
+                joined.text(delimiter);
+            }
+            joined.append(list);
+        }
+        Ok(Output::variable(self.format(element, joined)))
+    }
+
+    /// Renders the first child of `<substitute>` that has a value, and
+    /// suppresses the variables it used for the rest of the entry
+    fn substitute(&self, element: &Element) -> Result<Output, CslError> {
+        let Some(substitute) = element.child("substitute") else {
+            return Ok(Output::empty_variable());
+        };
+        for child in &substitute.children {
+            let start = self.rendered_variables.borrow().len();
+            let output = if child.name == "names" && child.child("name").is_none() {
+                // the shorthand `<names variable="editor"/>` takes on the
+                // name, et-al and label of the names element it substitutes
+                let mut inherited = child.clone();
+                inherited.children.extend(
+                    element.children.iter().filter(|c| c.name != "substitute").cloned(),
+                );
+                self.names(&inherited)?
+            } else {
+                self.render(child)?
+            };
+
+            if !output.rendered.is_empty() {
+                let used = self.rendered_variables.borrow()[start..].to_vec();
+                self.suppressed.borrow_mut().extend(used);
+                return Ok(Output::variable(self.format(element, output.rendered)));
+            }
+        }
+        Ok(Output::empty_variable())
+    }
+
+    /// A name option set on the `<name>` element, or else inherited
+    fn name_option<'e>(&'e self, name: Option<&'e Element>, option: &str) -> Option<&'e str> {
+        let inherited = match option {
+            "form" => "name-form",
+            "delimiter" => "name-delimiter",
+            option => option,
+        };
+        name.and_then(|name| name.attribute(option))
+            .or_else(|| self.name_options.get(inherited).map(String::as_str))
+    }
+
+    fn name_list(&self, list: &Authors, name: Option<&Element>, et_al: Option<&Element>) -> Rendered {
+        let option = |key: &str| self.name_option(name, key);
+        let delimiter = option("delimiter").unwrap_or(", ");
+        let et_al_min: usize = option("et-al-min").and_then(|n| n.parse().ok()).unwrap_or(0);
+        let use_first: usize = option("et-al-use-first").and_then(|n| n.parse().ok()).unwrap_or(1);
+
+        let authors: Vec<&Author> = list.iter().collect();
+        let truncated = et_al_min > 0 && authors.len() >= et_al_min && use_first < authors.len();
+        let shown = if truncated { use_first } else { authors.len() };
+        let form = option("form").unwrap_or("long");
+        if form == "count" {
+            return plain(&shown.to_string());
+        }
+
+        let sort_order = if self.sorting { "all" } else { option("name-as-sort-order").unwrap_or("") };
+        let inverted = |i: usize| sort_order == "all" || (sort_order == "first" && i == 0);
+        let precedes = |setting: Option<&str>, contextual: bool, last_inverted: bool| match setting {
+            Some("always") => true,
+            Some("never") => false,
+            Some("after-inverted-name") => last_inverted,
+            _ => contextual,
+        };
+        let formatted: Vec<String> = authors[..shown]
+            .iter()
+            .enumerate()
+            .map(|(i, author)| self.name(author, name, form, inverted(i)))
+            .collect();
+
+        let mut rendered = Rendered::new();
+        if truncated {
+            rendered.text(&formatted.join(delimiter));
+            // APA's "A, B, C, . . . Z" for long lists
+            if option("et-al-use-last") == Some("true") && shown + 2 <= authors.len() {
+                let last = authors.len() - 1;
+                let last_name = self.name(authors[last], name, form, inverted(last));
+                rendered.text(&format!("{delimiter}\u{2026} {last_name}"));
+                return rendered;
+            }
+
+            let separator = if precedes(option("delimiter-precedes-et-al"), shown >= 2, inverted(shown - 1)) {
+                delimiter
+            } else {
+                " "
+            };
+            let term = et_al.and_then(|e| e.attribute("term")).unwrap_or("et-al");
+            let mut et_al_text = plain(self.style.locale.term(term, TermForm::Long, false).unwrap_or("et al."));
+            if let Some(et_al) = et_al {
+                et_al_text = self.format(et_al, et_al_text);
+            }
+            rendered.text(separator).append(et_al_text);
+            return rendered;
+        }
+
+        let and = match option("and") {
+            Some("text") => self.style.locale.term("and", TermForm::Long, false),
+            Some("symbol") => Some("&"),
+            _ => None,
+        };
+        let count = formatted.len();
+        let Some((last, init)) = formatted.split_last() else {
+            return rendered;
+        };
+        if init.is_empty() {
+            rendered.text(last);
+            return rendered;
+        }
+        let separator = match and {
+            Some(and) if precedes(option("delimiter-precedes-last"), count >= 3, inverted(count - 2)) => {
+                format!("{delimiter}{and} ")
+            }
+            Some(and) => format!(" {and} "),
+            None => delimiter.to_string(),
+        };
+        rendered.text(&format!("{}{separator}{last}", init.join(delimiter)));
+        rendered
+    }
+
+    fn name(&self, author: &Author, name: Option<&Element>, form: &str, inverted: bool) -> String {
+        let part_case = |part: &str, value: String| {
+            let case = name
+                .into_iter()
+                .flat_map(|name| name.children_named("name-part"))
+                .find(|name_part| name_part.attribute("name") == Some(part))
+                .and_then(|name_part| name_part.attribute("text-case"));
+            match case {
+                Some(case) => apply_case(&value, case),
+                None => value,
+            }
+        };
+
+        let family = part_case("family", names::family(author));
+        if form == "short" {
+            return family;
+        }
+
+        let initialize_with = self.name_option(name, "initialize-with")
+            .filter(|_| self.name_option(name, "initialize") != Some("false"));
+        let given = match initialize_with {
+            Some(with) => initialize(&names::given(author), with),
+            None => names::given(author),
+        };
+        let given = part_case("given", given);
+        let suffix = author.suffix().map(latex::decode);
+
+        if inverted {
+            let separator = self.name_option(name, "sort-separator").unwrap_or(", ");
+            [Some(family), Some(given), suffix]
+                .into_iter()
+                .flatten()
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(separator)
+        } else {
+            [Some(given), Some(family), suffix]
+                .into_iter()
+                .flatten()
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+    }
+
+    fn date(&self, element: &Element) -> Output {
+        let Some(name) = element.attribute("variable") else {
+            return Output::default();
+        };
+        let date = variables::date(self.entry, name).filter(|_| !self.is_suppressed(name));
+        let Some(date) = date else {
+            return Output::empty_variable();
+        };
+        self.rendered_variables.borrow_mut().push(name.to_string());
+
+        let (parts, delimiter) = match element.attribute("form") {
+            Some(form) => (localised_parts(element, form), ""),
+            None => {
+                let parts = element.children_named("date-part").cloned().collect();
+                (parts, element.attribute("delimiter").unwrap_or_default())
+            }
+        };
+
+        let mut rendered = Rendered::new();
+        for part in parts.iter().filter_map(|part| self.date_part(part, &date)) {
+            if !rendered.is_empty() {
+                rendered.text(delimiter);
+            }
+            rendered.append(part);
+        }
+        Output::variable(self.format(element, rendered))
+    }
+
+    fn date_part(&self, part: &Element, date: &Date) -> Option<Rendered> {
+        let form = part.attribute("form");
+        let value = match part.attribute("name")? {
+            "year" => match form {
+                Some("short") => format!("{:02}", date.year % 100),
+                _ => date.year.to_string(),
+            },
+            "month" => {
+                let month = date.month?;
+                match form {
+                    Some("numeric") => month.to_string(),
+                    Some("numeric-leading-zeros") => format!("{month:02}"),
+                    Some("short") => self.style.locale.month(month, TermForm::Short)?.to_string(),
+                    _ => self.style.locale.month(month, TermForm::Long)?.to_string(),
+                }
+            }
+            "day" => {
+                let day = date.day?;
+                match form {
+                    Some("numeric-leading-zeros") => format!("{day:02}"),
+                    Some("ordinal") => locale::ordinal(day as u32),
+                    _ => day.to_string(),
+                }
+            }
+            _ => return None,
+        };
+        Some(self.decorate(part, plain(&case(part, &value))))
+    }
+
+    /// A group is left out entirely when it calls variables but none of
+    /// them have a value, e.g. "(Ed.)" without an editor
+    fn group(&self, element: &Element) -> Result<Output, CslError> {
+        let delimiter = element.attribute("delimiter").unwrap_or_default();
+        let output = self.render_children(&element.children, delimiter)?;
+        if output.called && !output.found {
+            return Ok(Output::empty_variable());
+        }
+        Ok(Output { rendered: self.format(element, output.rendered), ..output })
+    }
+
+    fn choose(&self, element: &Element, delimiter: &str) -> Result<Output, CslError> {
+        for branch in &element.children {
+            let matched = match branch.name.as_str() {
+                "if" | "else-if" => self.condition(branch),
+                "else" => true,
+                _ => false,
+            };
+            if matched {
+                return self.render_children(&branch.children, delimiter);
+            }
+        }
+        Ok(Output::default())
+    }
+
+    fn condition(&self, branch: &Element) -> bool {
+        let mut results = Vec::new();
+        let item_type = variables::item_type(self.entry);
+        results.extend(branch.attribute_list("type").iter().map(|t| *t == item_type));
+        results.extend(branch.attribute_list("variable").iter().map(|v| self.has_variable(v)));
+        results.extend(branch.attribute_list("is-numeric").iter().map(|v| {
+            match *v {
+                "citation-number" => self.citation_number.is_some(),
+                v => variables::standard(self.entry, v).is_some_and(|value| variables::is_numeric(&value)),
+            }
+        }));
+        // every citation is treated as the first cite of its entry, and
+        // there are no locators or uncertain dates in BibTeX
+        results.extend(branch.attribute_list("position").iter().map(|p| *p == "first"));
+        for unsupported in ["is-uncertain-date", "locator", "disambiguate"] {
+            results.extend(branch.attribute_list(unsupported).iter().map(|_| false));
+        }
+
+        match branch.attribute("match") {
+            Some("any") => results.iter().any(|&r| r),
+            Some("none") => !results.iter().any(|&r| r),
+            _ => results.iter().all(|&r| r),
+        }
+    }
+
+    /// Applies the formatting attributes of an element to its output
+    fn format(&self, element: &Element, mut rendered: Rendered) -> Rendered {
+        if let Some(case) = element.attribute("text-case") {
+            rendered.map_text(|text| apply_case(text, case));
+        }
+        self.decorate(element, rendered)
+    }
+
+    /// Applies the formatting attributes other than the text case, which
+    /// leaf elements apply to the raw value so that braces are respected
+    pub fn decorate(&self, element: &Element, rendered: Rendered) -> Rendered {
+        decorate(&self.style.locale, element, rendered)
+    }
+}
+
+pub(super) fn decorate(locale: &Locale, element: &Element, mut rendered: Rendered) -> Rendered {
+    if rendered.is_empty() {
+        return rendered;
+    }
+    if element.attribute("strip-periods") == Some("true") {
+        rendered.map_text(|text| text.replace('.', ""));
+    }
+    if element.attribute("quotes") == Some("true") {
+        let mut quoted = plain(locale.term("open-quote", TermForm::Long, false).unwrap_or("\""));
+        quoted.append(rendered);
+        quoted.text(locale.term("close-quote", TermForm::Long, false).unwrap_or("\""));
+        rendered = quoted;
+    }
+    if element.attribute("font-style") == Some("italic") {
+        rendered = rendered.into_italic();
+    }
+
+    let mut affixed = plain(element.attribute("prefix").unwrap_or_default());
+    affixed.append(rendered);
+    if let Some(suffix) = element.attribute("suffix") {
+        push(&mut affixed, plain(suffix));
+    }
+    affixed
+}
+
+/// Moves a comma or full stop after a closing quote inside it when the
+/// locale asks for it
+pub(super) fn punctuate_quotes(locale: &Locale, rendered: &mut Rendered) {
+    if locale.punctuation_in_quote {
+        rendered.map_text(|text| text.replace("\u{201d}.", ".\u{201d}").replace("\u{201d},", ",\u{201d}"));
+    }
+}
+
+/// Appends, dropping a leading full stop that would double up with the
+/// punctuation before it, e.g. a title ending in "?" followed by ".", and a
+/// leading space after one, e.g. a term's suffix followed by a delimiter
+pub(super) fn push(target: &mut Rendered, mut piece: Rendered) {
+    if target.ends_with(&['.', '?', '!']) {
+        piece.strip_first_char('.');
+    }
+    if target.ends_with(&[' ']) {
+        piece.strip_first_char(' ');
+    }
+    target.append(piece);
+}
+
+fn plain(text: &str) -> Rendered {
+    let mut rendered = Rendered::new();
+    rendered.text(text);
+    rendered
+}
+
+/// Applies the text case of an element to a raw value, or just strips the
+/// braces when there is none
+fn case(element: &Element, raw: &str) -> String {
+    match element.attribute("text-case") {
+        Some(case) => apply_case(raw, case),
+        None => strip_braces(raw),
+    }
+}
+
+fn apply_case(text: &str, case: &str) -> String {
+    match case {
+        "sentence" => sentence_case(text),
+        "title" => title_case(text),
+        "lowercase" => strip_braces(text).to_lowercase(),
+        "uppercase" => strip_braces(text).to_uppercase(),
+        "capitalize-first" => capitalize_first(&strip_braces(text)),
+        "capitalize-all" => strip_braces(text)
+            .split(' ')
+            .map(capitalize_first)
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => strip_braces(text),
+    }
+}
+
+fn capitalize_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Reduces forenames to initials followed by `with`, e.g. "Jean-Pierre Karl"
+/// becomes "J.-P. K." with ". " and "J.-P.K." with "."
+fn initialize(given: &str, with: &str) -> String {
+    let mark = with.trim_end();
+    let joiner = if with.ends_with(' ') { " " } else { "" };
+    given
+        .split_whitespace()
+        .map(|word| {
+            word.split('-')
+                .filter_map(|part| part.chars().find(|c| c.is_alphabetic()))
+                .map(|c| format!("{c}{mark}"))
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .filter(|initials| !initials.is_empty())
+        .collect::<Vec<_>>()
+        .join(joiner)
+}
+
+/// The date parts of a localised date, e.g. "October 15, 2019" for the
+/// text form, with any overrides from the date's own `<date-part>` children
+fn localised_parts(date: &Element, form: &str) -> Vec<Element> {
+    let part = |name: &str, form: &str, suffix: &str| {
+        let mut part = Element { name: String::from("date-part"), ..Element::default() };
+        part.attributes.insert(String::from("name"), name.to_string());
+        part.attributes.insert(String::from("form"), form.to_string());
+        if !suffix.is_empty() {
+            part.attributes.insert(String::from("suffix"), suffix.to_string());
+        }
+        part
+    };
+    let mut parts = match form {
+        "numeric" => vec![part("month", "numeric", "/"), part("day", "numeric", "/"), part("year", "long", "")],
+        _ => vec![part("month", "long", " "), part("day", "numeric", ", "), part("year", "long", "")],
+    };
+
+    let shown: &[&str] = match date.attribute("date-parts") {
+        Some("year") => &["year"],
+        Some("year-month") => &["year", "month"],
+        _ => &["year", "month", "day"],
+    };
+    parts.retain(|part| part.attribute("name").is_some_and(|name| shown.contains(&name)));
+
+    for part in &mut parts {
+        let overrides = date
+            .children_named("date-part")
+            .find(|o| o.attribute("name") == part.attribute("name"));
+        if let Some(overrides) = overrides {
+            part.attributes.extend(overrides.attributes.clone());
+        }
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initialize() {
+        assert_eq!(initialize("Jean-Pierre Karl", ". "), "J.-P. K.");
+        assert_eq!(initialize("Jean-Pierre Karl", "."), "J.-P.K.");
+        assert_eq!(initialize("John", ""), "J");
+    }
+}
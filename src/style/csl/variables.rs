@@ -0,0 +1,144 @@
+//! The mapping from BibTeX fields onto CSL variables
+
+use crate::bibtex::entry::{BibTeXEntry, BibTeXEntryKind};
+use crate::bibtex::fields::Authors;
+use crate::bibtex::latex;
+
+/// The CSL item type of an entry, e.g. "article-journal"
+pub(super) fn item_type(entry: &BibTeXEntry) -> &'static str {
+    match entry.kind {
+        BibTeXEntryKind::Article       => "article-journal", 
+        BibTeXEntryKind::Book
+        | BibTeXEntryKind::Booklet
+        | BibTeXEntryKind::Proceedings
+        | BibTeXEntryKind::Manual      => "book", 
+        BibTeXEntryKind::Inbook
+        | BibTeXEntryKind::InCollection => "chapter", 
+        BibTeXEntryKind::InProceedings
+        | BibTeXEntryKind::Conference  => "paper-conference", 
+        BibTeXEntryKind::PhdThesis
+        | BibTeXEntryKind::MasterThesis => "thesis", 
+        BibTeXEntryKind::TechReport    => "report", 
+        BibTeXEntryKind::Unpublished   => "manuscript", 
+        BibTeXEntryKind::Misc
+        | BibTeXEntryKind::Unknown     => "article", 
+    }
+}
+
+/// The value of a standard or number variable decoded from LaTeX, with the
+/// braces that protect words left in so that case changes can respect them
+pub(super) fn standard(entry: &BibTeXEntry, name: &str) -> Option<String> {
+    let text = |value: Option<&String>| value.map(|value| latex::decode_protected(value));
+    let value = match name {
+        "title"            => text(entry.title()), 
+        "container-title"  => text(entry.journal().or(entry.book_title())), 
+        "collection-title" => text(entry.series()), 
+        "event"            => text(entry.book_title().filter(|_| item_type(entry) == "paper-conference")), 
+        "publisher"        => text(entry.publisher()
+            .or(entry.school())
+            .or(entry.institution())
+            .or(entry.organization())), 
+        "publisher-place"  => text(entry.address()), 
+        "volume"           => entry.volume().map(u16::to_string), 
+        "issue"            => entry.number().filter(|_| item_type(entry) != "report").map(u16::to_string), 
+        "number"           => entry.number().filter(|_| item_type(entry) == "report").map(u16::to_string), 
+        "edition"          => entry.edition().map(u16::to_string), 
+        "chapter-number"   => entry.chapter().map(u8::to_string), 
+        "page"             => entry.pages().map(|pages| {
+            pages
+                .iter()
+                .map(|range| match range.end() {
+                    Some(end) => format!("{}\u{2013}{}", range.start(), end),
+                    None => range.start().to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        }).or_else(|| raw_pages(entry)), 
+        "page-first"       => match entry.pages() {
+            Some(pages) => pages.first_page().map(|p| p.to_string()),
+            None => raw_pages(entry).and_then(|pages| pages.split([',', '\u{2013}']).next().map(|p| p.trim().to_string())),
+        }, 
+        "genre"            => text(entry.entry_type()).or_else(|| match entry.kind {
+            BibTeXEntryKind::PhdThesis    => Some(String::from("PhD thesis")), 
+            BibTeXEntryKind::MasterThesis => Some(String::from("Master's thesis")), 
+            _ => None,
+        }), 
+        "note"             => text(entry.note()), 
+        "DOI"              => entry.doi().map(|doi| doi.trim().to_string()), 
+        "citation-key"     => Some(entry.citekey.clone()), 
+        "URL" | "ISBN" | "ISSN" | "PMID" | "abstract" | "keyword" | "language" => {
+            let key = match name {
+                "keyword" => "keywords", 
+                key => key, 
+            };
+            entry.non_standard_field(&key.to_lowercase()).cloned()
+        }
+        _ => None,
+    };
+    value.filter(|v| !v.trim().is_empty())
+}
+
+/// Pages that are not numbers, such as "A12--A19" or "e1234", as written
+/// with their dashes made en dashes
+fn raw_pages(entry: &BibTeXEntry) -> Option<String> {
+    entry.non_standard_field("pages").map(|pages| latex::decode(pages).replace('-', "\u{2013}"))
+}
+
+pub(super) fn names<'a>(entry: &'a BibTeXEntry, name: &str) -> Option<&'a Authors> {
+    let names = match name {
+        "author" => entry.author(), 
+        "editor" => entry.editor(), 
+        _ => None,
+    };
+    names.filter(|names| !names.is_empty())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Date {
+    pub year: u16, 
+    pub month: Option<u8>, 
+    pub day: Option<u8>, 
+}
+
+impl Date {
+    /// A key that sorts dates chronologically, e.g. "20190300"
+    pub fn sort_key(&self) -> String {
+        format!("{:04}{:02}{:02}", self.year, self.month.unwrap_or(0), self.day.unwrap_or(0))
+    }
+}
+
+pub(super) fn date(entry: &BibTeXEntry, name: &str) -> Option<Date> {
+    match name {
+        "issued" => entry.year().map(|&year| Date {
+            year,
+            month: entry.month().copied(),
+            day: entry.day().copied(),
+        }), 
+        // biblatex's `urldate = {2024-05-01}`
+        "accessed" => {
+            let urldate = entry.non_standard_field("urldate")?;
+            let mut parts = urldate.trim().split('-');
+            Some(Date {
+                year: parts.next()?.parse().ok()?,
+                month: parts.next().and_then(|m| m.parse().ok()),
+                day: parts.next().and_then(|d| d.parse().ok()),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Whether a value counts as numeric, e.g. "12", "12-14" or "2nd" but not
+/// "Supplement 3"
+pub(super) fn is_numeric(value: &str) -> bool {
+    let value = value.trim();
+    value.chars().any(|c| c.is_ascii_digit())
+        && value
+            .split(|c: char| matches!(c, '-' | '\u{2013}' | ',' | '&') || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .all(|part| {
+                let digits = part.trim_start_matches(|c: char| c.is_alphabetic());
+                let digits = digits.trim_end_matches(|c: char| c.is_alphabetic());
+                !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+            })
+}
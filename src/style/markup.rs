@@ -0,0 +1,192 @@
+/// The markup a formatted reference is written in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Markup {
+    Text, 
+    Markdown, 
+    Html, 
+}
+
+impl std::str::FromStr for Markup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" | "plain" => Ok(Self::Text),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "html"            => Ok(Self::Html),
+            _ => Err(format!("invalid markup: {s}, expected text, markdown or html")),
+        }
+    }
+}
+
+impl std::fmt::Display for Markup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text     => write!(f, "text"), 
+            Self::Markdown => write!(f, "markdown"), 
+            Self::Html     => write!(f, "html"), 
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Span {
+    Text(String), 
+    Italic(String), 
+    Link(String), 
+}
+
+/// A formatted reference, kept as styled spans until it is rendered into
+/// some markup
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Rendered {
+    spans: Vec<Span>, 
+}
+
+impl Rendered {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&mut self, text: &str) -> &mut Self {
+        if let Some(Span::Text(last)) = self.spans.last_mut() {
+            last.push_str(text);
+        } else if !text.is_empty() {
+            self.spans.push(Span::Text(text.to_string()));
+        }
+        self
+    }
+
+    pub fn italic(&mut self, text: &str) -> &mut Self {
+        if !text.is_empty() {
+            self.spans.push(Span::Italic(text.to_string()));
+        }
+        self
+    }
+
+    pub fn link(&mut self, url: &str) -> &mut Self {
+        self.spans.push(Span::Link(url.to_string()));
+        self
+    }
+
+    pub fn append(&mut self, other: Rendered) -> &mut Self {
+        for span in other.spans {
+            match span {
+                Span::Text(text) => {
+                    self.text(&text);
+                }
+                span => self.spans.push(span),
+            }
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.iter().all(|span| match span {
+            Span::Text(t) | Span::Italic(t) | Span::Link(t) => t.is_empty(),
+        })
+    }
+
+    /// Turns every plain text span italic, leaving links alone
+    pub fn into_italic(self) -> Self {
+        let spans = self.spans
+            .into_iter()
+            .map(|span| match span {
+                Span::Text(t) => Span::Italic(t),
+                span => span,
+            })
+            .collect();
+        Self { spans }
+    }
+
+    /// Applies a transformation, e.g. a change of case, to the text of
+    /// every span other than links
+    pub fn map_text(&mut self, transform: impl Fn(&str) -> String) {
+        for span in &mut self.spans {
+            if let Span::Text(t) | Span::Italic(t) = span {
+                *t = transform(t);
+            }
+        }
+    }
+
+    /// Removes a leading character, returning whether there was one
+    pub fn strip_first_char(&mut self, c: char) -> bool {
+        let first = self.spans.iter_mut().find_map(|span| match span {
+            Span::Text(t) | Span::Italic(t) | Span::Link(t) if !t.is_empty() => Some(t),
+            _ => None,
+        });
+        match first {
+            Some(text) if text.starts_with(c) => {
+                text.remove(0);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The plain text of the reference, ignoring any styling
+    pub fn plain_text(&self) -> String {
+        self.render(Markup::Text)
+    }
+
+    /// Whether the text ends with any of the given characters, ignoring styling
+    pub fn ends_with(&self, chars: &[char]) -> bool {
+        let last = self.spans.iter().rev().find_map(|span| match span {
+            Span::Text(t) | Span::Italic(t) | Span::Link(t) if !t.is_empty() => Some(t),
+            _ => None,
+        });
+        last.is_some_and(|t| t.ends_with(chars))
+    }
+
+    /// Adds the punctuation unless the text already ends with some, e.g. a
+    /// title ending in a question mark
+    pub fn terminate(&mut self, punctuation: &str) -> &mut Self {
+        if !self.ends_with(&['.', '?', '!']) {
+            self.text(punctuation);
+        }
+        self
+    }
+
+    pub fn render(&self, markup: Markup) -> String {
+        let mut output = String::new();
+        for span in &self.spans {
+            match (span, markup) {
+                (Span::Text(t) | Span::Italic(t) | Span::Link(t), Markup::Text) => output.push_str(t),
+                (Span::Text(t), Markup::Markdown) => output.push_str(&escape_markdown(t)),
+                (Span::Italic(t), Markup::Markdown) => {
+                    output.push('*');
+                    output.push_str(&escape_markdown(t));
+                    output.push('*');
+                }
+                (Span::Link(url), Markup::Markdown) => output.push_str(&format!("<{url}>")),
+                (Span::Text(t), Markup::Html) => output.push_str(&escape_html(t)),
+                (Span::Italic(t), Markup::Html) => {
+                    output.push_str(&format!("<i>{}</i>", escape_html(t)));
+                }
+                (Span::Link(url), Markup::Html) => {
+                    let url = escape_html(url);
+                    output.push_str(&format!("<a href=\"{url}\">{url}</a>"));
+                }
+            }
+        }
+        output
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
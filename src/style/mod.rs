@@ -0,0 +1,103 @@
+//! Formatting of entries as references in a citation style, e.g. for
+//! pasting into an email or a slide
+
+pub mod case;
+pub mod csl;
+pub mod markup;
+
+mod builtin;
+mod names;
+
+pub use markup::{Markup, Rendered};
+
+use crate::bibtex::entry::BibTeXEntry;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Apa, 
+    Ieee, 
+    ChicagoAuthorDate, 
+    Harvard, 
+    Aiaa, 
+}
+
+impl Style {
+    /// Whether references are numbered in citation order rather than
+    /// listed alphabetically by author
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Self::Ieee | Self::Aiaa)
+    }
+}
+
+impl std::str::FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "apa" | "apa7"                           => Ok(Self::Apa), 
+            "ieee"                                   => Ok(Self::Ieee), 
+            "chicago" | "chicago-author-date"        => Ok(Self::ChicagoAuthorDate), 
+            "harvard"                                => Ok(Self::Harvard), 
+            "aiaa"                                   => Ok(Self::Aiaa), 
+            _ => Err(format!("unknown style: {s}, expected apa, ieee, chicago, harvard or aiaa")),
+        }
+    }
+}
+
+impl std::fmt::Display for Style {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Apa               => write!(f, "apa"), 
+            Self::Ieee              => write!(f, "ieee"), 
+            Self::ChicagoAuthorDate => write!(f, "chicago"), 
+            Self::Harvard           => write!(f, "harvard"), 
+            Self::Aiaa              => write!(f, "aiaa"), 
+        }
+    }
+}
+
+pub struct ReferenceFormatter {
+    style: Style, 
+    markup: Markup, 
+}
+
+impl ReferenceFormatter {
+    pub fn new(style: Style, markup: Markup) -> Self {
+        Self { style, markup }
+    }
+
+    /// Formats a single entry as a reference, without any numbering
+    pub fn render(&self, entry: &BibTeXEntry) -> Rendered {
+        match self.style {
+            Style::Apa               => builtin::apa(entry), 
+            Style::Ieee              => builtin::ieee(entry), 
+            Style::ChicagoAuthorDate => builtin::chicago(entry), 
+            Style::Harvard           => builtin::harvard(entry), 
+            Style::Aiaa              => builtin::aiaa(entry), 
+        }
+    }
+
+    pub fn format(&self, entry: &BibTeXEntry) -> String {
+        self.render(entry).render(self.markup)
+    }
+
+    /// Formats a list of references, numbered in the given order for
+    /// numeric styles and sorted by author and year for the others
+    pub fn format_bibliography(&self, entries: &[&BibTeXEntry]) -> Vec<String> {
+        if self.style.is_numeric() {
+            return entries
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| {
+                    let mut numbered = Rendered::new();
+                    numbered.text(&format!("[{}] ", i + 1)).append(self.render(entry));
+                    numbered.render(self.markup)
+                })
+                .collect();
+        }
+
+        let mut sorted = entries.to_vec();
+        sorted.sort_by_key(|entry| builtin::sort_key(entry));
+        sorted.iter().map(|entry| self.format(entry)).collect()
+    }
+}
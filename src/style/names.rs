@@ -0,0 +1,114 @@
+use crate::bibtex::fields::{Author, Authors};
+use crate::bibtex::latex;
+
+/// The family name including any "von" part, e.g. "van der Waals"
+pub fn family(author: &Author) -> String {
+    match author.prefix() {
+        Some(prefix) => latex::decode(&format!("{} {}", prefix, author.surname())),
+        None => latex::decode(author.surname()),
+    }
+}
+
+pub fn given(author: &Author) -> String {
+    latex::decode(author.forename())
+}
+
+/// The "Jr." or "III" after the name
+fn suffix(author: &Author) -> Option<String> {
+    author.suffix().map(latex::decode)
+}
+
+/// The initials of the forenames, keeping hyphenated names together, e.g.
+/// "Jean-Pierre K." becomes "J.-P. K." or "J.-P.K." without the spacing
+pub fn initials(author: &Author, separator: &str) -> String {
+    given(author)
+        .split_whitespace()
+        .map(|name| {
+            name.split('-')
+                .filter_map(|part| part.chars().find(|c| c.is_alphabetic()))
+                .map(|c| format!("{c}."))
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .filter(|initial| !initial.is_empty())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// "Family, I. I." or just the family name when there are no forenames
+pub fn family_initials(author: &Author, separator: &str) -> String {
+    let initials = initials(author, separator);
+    let mut name = family(author);
+    if !initials.is_empty() {
+        name.push_str(", ");
+        name.push_str(&initials);
+    }
+    if let Some(suffix) = suffix(author) {
+        name.push_str(", ");
+        name.push_str(&suffix);
+    }
+    name
+}
+
+/// "I. I. Family"
+pub fn initials_family(author: &Author) -> String {
+    let initials = initials(author, " ");
+    let name = if initials.is_empty() { family(author) } else { format!("{} {}", initials, family(author)) };
+    match suffix(author) {
+        Some(suffix) => format!("{name}, {suffix}"),
+        None => name,
+    }
+}
+
+/// "Family, Given"
+pub fn family_given(author: &Author) -> String {
+    let given = given(author);
+    let mut name = family(author);
+    if !given.is_empty() {
+        name.push_str(", ");
+        name.push_str(&given);
+    }
+    if let Some(suffix) = suffix(author) {
+        name.push_str(", ");
+        name.push_str(&suffix);
+    }
+    name
+}
+
+/// "Given Family"
+pub fn given_family(author: &Author) -> String {
+    let given = given(author);
+    let name = if given.is_empty() { family(author) } else { format!("{} {}", given, family(author)) };
+    match suffix(author) {
+        Some(suffix) => format!("{name}, {suffix}"),
+        None => name,
+    }
+}
+
+/// Joins a list of names as "A, B, and C", where the comma before the
+/// conjunction is only used for three or more names unless `always_comma`
+pub fn join(names: &[String], conjunction: &str, serial_comma: bool, always_comma: bool) -> String {
+    match names {
+        [] => String::new(),
+        [only] => only.clone(),
+        [first, second] if !always_comma => format!("{first} {conjunction} {second}"),
+        [rest @ .., last] => {
+            let comma = if serial_comma || always_comma { "," } else { "" };
+            format!("{}{} {} {}", rest.join(", "), comma, conjunction, last)
+        }
+    }
+}
+
+/// Collects the formatted names, truncating the list to the first 
+/// `use_first` followed by "et al." once it reaches `et_al_min`
+pub fn truncate(
+    authors: &Authors, 
+    format: impl Fn(usize, &Author) -> String, 
+    et_al_min: usize, 
+    use_first: usize, 
+) -> (Vec<String>, bool) {
+    let truncated = authors.len() >= et_al_min;
+    let take = if truncated { use_first } else { authors.len() };
+    let names = authors.iter().take(take).enumerate().map(|(i, a)| format(i, a)).collect();
+    (names, truncated)
+}
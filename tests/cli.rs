@@ -0,0 +1,178 @@
+//! Runs the `litrev` binary against small throwaway projects
+
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+const LIBRARY: &str = r#"@article{Kenway2019,
+    author = {Kenway, Gaetan K. W. and Mader, Charles A.},
+    title = {Effective Adjoint Approaches for Computational Fluid Dynamics},
+    journal = {Progress in Aerospace Sciences},
+    year = {2019},
+    doi = {10.1016/j.paerosci.2019.05.002}
+}
+"#;
+
+/// A project in a directory of its own, removed again when dropped
+struct Project {
+    root: PathBuf,
+}
+
+impl Project {
+    /// A project tracking `refs.bib` with the given contents
+    fn new(name: &str, bib: &str) -> Self {
+        let root = std::env::temp_dir().join(format!("litrev-cli-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("refs.bib"), bib).unwrap();
+        let project = Self { root };
+        let init = project.run(&["init"]);
+        assert!(init.status.success(), "{}", String::from_utf8_lossy(&init.stderr));
+        project
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        self.command(args).output().unwrap()
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_litrev"));
+        command
+            .args(args)
+            .current_dir(&self.root)
+            // keep the user's own library out of it
+            .env("LITREV_HOME", self.root.join("global"));
+        command
+    }
+}
+
+impl Drop for Project {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+fn exit_code(output: &Output) -> Option<i32> {
+    output.status.code()
+}
+
+#[test]
+fn test_export_format() {
+    let project = Project::new("export", LIBRARY);
+
+    let output = project.run(&["export", "--format", "hayagriva"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).starts_with("Kenway2019:\n  type: article\n"));
+
+    let output = project.run(&["export", "--to", "ris"]);
+    assert!(stdout(&output).starts_with("TY  - JOUR\nID  - Kenway2019\n"));
+
+    let output = project.run(&["export", "--format", "json"]);
+    assert_eq!(exit_code(&output), Some(2));
+    let output = project.run(&["export"]);
+    assert_eq!(exit_code(&output), Some(2));
+}
+
+#[test]
+fn test_open_print() {
+    let bib = format!("{LIBRARY}\n@book{{Doe2020,\n    author = {{Doe, Jane}},\n    title = {{Notes}},\n    year = {{2020}}\n}}\n");
+    let project = Project::new("open", &bib);
+
+    // the DOI when there is no PDF
+    let output = project.run(&["open", "Kenway2019", "--print"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "https://doi.org/10.1016/j.paerosci.2019.05.002\n");
+
+    // the PDF once there is one
+    let pdf = project.root.join("kenway.pdf");
+    fs::write(&pdf, "%PDF-1.4\n").unwrap();
+    let output = project.run(&["link", "--confirm", "Kenway2019", "kenway.pdf"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let output = project.run(&["open", "Kenway2019", "--print"]);
+    assert_eq!(stdout(&output), format!("{}\n", pdf.canonicalize().unwrap().display()));
+
+    let output = project.run(&["open", "Doe2020", "--print"]);
+    assert_eq!(exit_code(&output), Some(1));
+    assert!(stderr(&output).contains("Doe2020 has no PDF, DOI or URL to open"), "{}", stderr(&output));
+}
+
+#[test]
+fn test_unknown_citekey() {
+    let project = Project::new("unknown", LIBRARY);
+
+    for args in [&["show", "Kenway2091"][..], &["open", "Kenway2091", "--print"], &["note", "Kenway2091", "--path"]] {
+        let output = project.run(args);
+        assert_eq!(exit_code(&output), Some(4), "{args:?}");
+        assert!(stderr(&output).contains("no entry with citekey 'Kenway2091', did you mean 'Kenway2019'?"), "{}", stderr(&output));
+    }
+}
+
+#[test]
+fn test_search_typo() {
+    let project = Project::new("typo", LIBRARY);
+
+    // with nothing matching exactly, the entries a few typos away
+    let output = project.run(&["search", "author:kenwey"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Kenway2019"), "{}", stdout(&output));
+    assert!(stderr(&output).contains("Nothing matches exactly"), "{}", stderr(&output));
+}
+
+#[test]
+fn test_watch_removed_pdf() {
+    let project = Project::new("watch", LIBRARY);
+    let config = project.root.join(".litrev/config.toml");
+    fs::write(&config, fs::read_to_string(&config).unwrap().replace("pdf_dirs = []", r#"pdf_dirs = ["papers"]"#)).unwrap();
+    let pdf = project.root.join("papers/Kenway2019.pdf");
+    fs::create_dir_all(pdf.parent().unwrap()).unwrap();
+    fs::write(&pdf, "%PDF-1.4\n").unwrap();
+    let output = project.run(&["link"]);
+    assert!(stdout(&output).contains("Linked 1 of 1 entries"), "{}", stderr(&output));
+
+    let mut watch = project.command(&["watch", "--debounce", "50"]).stdout(Stdio::piped()).spawn().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    let lines = BufReader::new(watch.stdout.take().unwrap()).lines();
+    std::thread::spawn(move || lines.map_while(Result::ok).try_for_each(|line| sender.send(line)));
+    let next_line = || receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert!(next_line().starts_with("Watching 1 bibliographies and 1 PDF directories"));
+
+    // the link to a PDF that is gone is dropped, so the DOI is opened again
+    fs::remove_file(&pdf).unwrap();
+    assert_eq!(next_line(), "papers/Kenway2019.pdf removed");
+    assert_eq!(next_line(), "Linked 0 of 1 entries to 0 PDFs");
+    watch.kill().unwrap();
+    watch.wait().unwrap();
+    let output = project.run(&["open", "Kenway2019", "--print"]);
+    assert_eq!(stdout(&output), "https://doi.org/10.1016/j.paerosci.2019.05.002\n");
+}
+
+#[test]
+fn test_saved_search() {
+    let project = Project::new("saved", LIBRARY);
+
+    assert!(project.run(&["search", "adjoint", "--save", "reading"]).status.success());
+    assert!(stdout(&project.run(&["list", "--group", "reading"])).contains("Kenway2019"));
+    // saving again under the same name replaces the search
+    let output = project.run(&["search", "turbulence", "--save", "reading"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!stdout(&project.run(&["list", "--group", "reading"])).contains("Kenway2019"));
+
+    let output = project.run(&["search", "--forget", "reading"]);
+    assert_eq!(stdout(&output), "Deleted the saved search 'reading'\n");
+    let output = project.run(&["list", "--group", "reading"]);
+    assert_eq!(exit_code(&output), Some(1));
+    assert!(stderr(&output).contains("no saved search named 'reading'"), "{}", stderr(&output));
+    let output = project.run(&["search", "--forget", "reading"]);
+    assert_eq!(exit_code(&output), Some(1));
+}